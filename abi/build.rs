@@ -17,8 +17,8 @@ fn main() {
             "reservation.ReservationFilter",
             &["resource_id", "user_id", "status", "desc"],
         )
-        .with_derive_builder_option("reservation.ReservationFilter", &["cursor"])
-        .with_derive_builder_option("reservation.ReservationQuery", &["start", "end"])
+        .with_derive_builder_option("reservation.ReservationFilter", &["cursor", "as_of"])
+        .with_derive_builder_option("reservation.ReservationQuery", &["start", "end", "as_of"])
         .with_type_attributes(
             &[
                 "reservation.ReservationFilter",
@@ -5,8 +5,10 @@ use regex::Regex;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ReservationConflictInfo {
-    Parsed(ReservationConflict),
+    Parsed(Box<ReservationConflict>),
     Unparsed(String),
+    /// the resource is fully reserved: resource id, capacity, units left
+    CapacityExceeded(String, i32, i32),
 }
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ReservationConflict {
@@ -18,6 +20,25 @@ pub struct ReservationWindow {
     pub rid: String,
     pub start: DateTime<Utc>,
     pub end: DateTime<Utc>,
+    /// the window with the buffers of the resource, which is what conflicts
+    pub padded_start: DateTime<Utc>,
+    pub padded_end: DateTime<Utc>,
+}
+
+impl ReservationWindow {
+    pub fn new(
+        rid: impl Into<String>,
+        (start, end): (DateTime<Utc>, DateTime<Utc>),
+        (padded_start, padded_end): (DateTime<Utc>, DateTime<Utc>),
+    ) -> Self {
+        Self {
+            rid: rid.into(),
+            start,
+            end,
+            padded_start,
+            padded_end,
+        }
+    }
 }
 
 impl FromStr for ReservationConflictInfo {
@@ -25,7 +46,7 @@ impl FromStr for ReservationConflictInfo {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if let Ok(conflict) = s.parse() {
-            Ok(ReservationConflictInfo::Parsed(conflict))
+            Ok(ReservationConflictInfo::Parsed(Box::new(conflict)))
         } else {
             Ok(ReservationConflictInfo::Unparsed(s.to_string()))
         }
@@ -57,15 +78,17 @@ impl TryFrom<HashMap<String, String>> for ReservationWindow {
     type Error = ();
 
     fn try_from(value: HashMap<String, String>) -> Result<Self, Self::Error> {
+        // a message of the constraint on the padded timespan has no booked window, it is kept
+        // unparsed rather than taking the padded window as the booked one
         let timespan_str = value.get("timespan").ok_or(())?.replace('"', "");
         let mut split = timespan_str.splitn(2, ',');
         let start = parse_datetime(split.next().ok_or(())?)?;
         let end = parse_datetime(split.next().ok_or(())?)?;
-        Ok(Self {
-            rid: value.get("resource_id").ok_or(())?.to_string(),
-            start,
-            end,
-        })
+        Ok(Self::new(
+            value.get("resource_id").ok_or(())?,
+            (start, end),
+            (start, end),
+        ))
     }
 }
 
@@ -117,7 +140,6 @@ mod tests {
     #[test]
     fn parse_info_should_work() {
         let info = ParsedInfo::from_str(ERR_MSG).unwrap();
-        println!("{:?}", info);
         assert_eq!(info.new.get("resource_id").unwrap(), "ocean-view-room-713");
         assert_eq!(
             info.new.get("timespan").unwrap(),
@@ -144,10 +166,24 @@ mod tests {
         assert_eq!(window.end.to_rfc3339(), "2022-12-30T19:00:00+00:00");
     }
 
+    #[test]
+    fn padded_timespan_should_not_be_taken_as_booked_window() {
+        let mut map = HashMap::new();
+        map.insert("resource_id".to_string(), "ocean-view-room-713".to_string());
+        map.insert(
+            "padded_timespan".to_string(),
+            "\"2022-12-26 19:00:00+00\",\"2022-12-30 19:00:00+00\"".to_string(),
+        );
+        assert!(ReservationWindow::try_from(map).is_err());
+
+        let msg = ERR_MSG.replace("timespan", "padded_timespan");
+        let info: ReservationConflictInfo = msg.parse().unwrap();
+        assert_eq!(info, ReservationConflictInfo::Unparsed(msg));
+    }
+
     #[test]
     fn conflict_error_message_should_parse() {
         let info: ReservationConflictInfo = ERR_MSG.parse().unwrap();
-        println!("{:?}", info);
         match info {
             ReservationConflictInfo::Parsed(conflict) => {
                 assert_eq!(conflict.new.rid, "ocean-view-room-713");
@@ -157,7 +193,7 @@ mod tests {
                 assert_eq!(conflict.old.start.to_rfc3339(), "2022-12-25T22:00:00+00:00");
                 assert_eq!(conflict.old.end.to_rfc3339(), "2022-12-28T19:00:00+00:00");
             }
-            ReservationConflictInfo::Unparsed(_) => panic!("should be parsed"),
+            _ => panic!("should be parsed"),
         }
     }
 }
@@ -1,3 +1,4 @@
+use crate::{utils::format_secs, ReservationStatus};
 use sqlx::postgres::PgDatabaseError;
 use thiserror::Error;
 mod conflict;
@@ -17,6 +18,9 @@ pub enum Error {
     #[error("Invalid user id {0}")]
     InvalidUserId(String),
 
+    #[error("Invalid or unknown api token")]
+    Unauthenticated,
+
     #[error("Invalid resource id {0}")]
     InvalidResourceId(String),
 
@@ -26,6 +30,9 @@ pub enum Error {
     #[error("conflict reservation: {0:?}")]
     ConflictReservation(ReservationConflictInfo),
 
+    #[error("conflict reservation for item {0} in batch: {1:?}")]
+    ConflictBatchReservation(usize, ReservationConflictInfo),
+
     // #[error("database error: {0}")]
     // DbError(#[from] sqlx::Error),
     #[error("Reservation error")]
@@ -40,7 +47,96 @@ pub enum Error {
     InvalidPageSize(i64),
     #[error("Invalid status: {0}")]
     InvalidStatus(i32),
+    #[error("Invalid consumer id: {0}")]
+    InvalidConsumerId(String),
+    #[error("Invalid update type: {0}")]
+    InvalidUpdateType(i32),
+    #[error("Invalid recurrence rule: {0}")]
+    InvalidRRule(String),
+    #[error("Recurrence rule expands to more than {0} occurrences")]
+    TooManyOccurrences(usize),
+    #[error("Invalid series conflict mode: {0}")]
+    InvalidConflictMode(i32),
+    #[error("Resource {0} is fully reserved: capacity {1}, {2} left")]
+    CapacityExceeded(String, i32, i32),
+    #[error("Resource {0} has {2} reservations at once, more than the capacity of {1}")]
+    CapacityInUse(String, i32, i64),
+    #[error("Resource not found: {0}")]
+    ResourceNotFound(String),
+    #[error("Resource already exists: {0}")]
+    ResourceAlreadyExists(String),
+    #[error("Resource is retired: {0}")]
+    ResourceRetired(String),
+    #[error("Invalid timezone: {0}")]
+    InvalidTimezone(String),
+    #[error("Reservation lasts {}, shorter than the minimum of {}", format_secs(*.0), format_secs(*.1))]
+    DurationTooShort(i64, i64),
+    #[error("Reservation lasts {}, longer than the maximum of {}", format_secs(*.0), format_secs(*.1))]
+    DurationTooLong(i64, i64),
+    #[error("Reservation must be made at least {} ahead", format_secs(*.0))]
+    LeadTimeTooShort(i64),
+    #[error("Reservation starts beyond the booking horizon of {}", format_secs(*.0))]
+    BeyondHorizon(i64),
+    #[error("Invalid booking policy: {0}")]
+    InvalidPolicy(String),
+    #[error("Invalid buffer: {0} seconds")]
+    InvalidBuffer(i64),
+    #[error("Invalid capacity: {0}")]
+    InvalidCapacity(i32),
+    #[error("Invalid block mode: {0}")]
+    InvalidBlockMode(i32),
+    #[error("Invalid hold ttl: {0} seconds")]
+    InvalidHoldTtl(i64),
+    #[error("Reservation {0} is at version {2}, not {1}")]
+    VersionMismatch(i64, i64, i64),
+    #[error("Invalid update mask path: {0:?}")]
+    InvalidUpdateMask(String),
+    #[error("Invalid idempotency key: {0}")]
+    InvalidIdempotencyKey(String),
+    #[error("Idempotency key {0} is used for another request")]
+    IdempotencyKeyReused(String),
+    #[error("Operation with idempotency key {0} is in progress")]
+    IdempotencyKeyInProgress(String),
+    #[error("Waitlist entry not found: {0}")]
+    WaitlistEntryNotFound(i64),
+    #[error("Pool not found: {0}")]
+    PoolNotFound(String),
+    #[error("Pool already exists: {0}")]
+    PoolAlreadyExists(String),
+    #[error("Invalid pool: {0}")]
+    InvalidPool(String),
+    #[error("Invalid pool strategy: {0}")]
+    InvalidPoolStrategy(i32),
+    #[error("No resource of pool {0} is available")]
+    NoAvailableResource(String),
+    #[error("Invalid min duration: {0} seconds")]
+    InvalidMinDuration(i64),
+    #[error("Hold expired for reservation {0}")]
+    HoldExpired(i64),
+    #[error("Reservations are only kept as of {0} on")]
+    AsOfBeforeChangeLog(String),
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+    #[error("Invalid transition from {from} to {to}")]
+    InvalidTransition {
+        from: ReservationStatus,
+        to: ReservationStatus,
+    },
+}
+impl Error {
+    /// report a conflict or a full resource as the failure of item `index` in a batch
+    pub fn in_batch(self, index: usize) -> Self {
+        match self {
+            Self::ConflictReservation(info) => Self::ConflictBatchReservation(index, info),
+            Self::CapacityExceeded(rid, capacity, left) => Self::ConflictBatchReservation(
+                index,
+                ReservationConflictInfo::CapacityExceeded(rid, capacity, left),
+            ),
+            e => e,
+        }
+    }
 }
+
 impl PartialEq for Error {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -50,9 +146,63 @@ impl PartialEq for Error {
             (Self::InvalidResourceId(v1), Self::InvalidResourceId(v2)) => v1 == v2,
             (Self::InvalidReservationId(v1), Self::InvalidReservationId(v2)) => v1 == v2,
             (Self::ConflictReservation(v1), Self::ConflictReservation(v2)) => v1 == v2,
+            (Self::ConflictBatchReservation(i1, v1), Self::ConflictBatchReservation(i2, v2)) => {
+                i1 == i2 && v1 == v2
+            }
             (Self::Unknown, Self::Unknown) => true,
+            (Self::Unauthenticated, Self::Unauthenticated) => true,
+            (Self::AsOfBeforeChangeLog(v1), Self::AsOfBeforeChangeLog(v2)) => v1 == v2,
+            (Self::PermissionDenied(v1), Self::PermissionDenied(v2)) => v1 == v2,
             (Self::InvalidTime, Self::InvalidTime) => true,
             (Self::NotFound, Self::NotFound) => true,
+            (Self::InvalidCursor(v1), Self::InvalidCursor(v2)) => v1 == v2,
+            (Self::InvalidPageSize(v1), Self::InvalidPageSize(v2)) => v1 == v2,
+            (Self::InvalidStatus(v1), Self::InvalidStatus(v2)) => v1 == v2,
+            (Self::InvalidConsumerId(v1), Self::InvalidConsumerId(v2)) => v1 == v2,
+            (Self::InvalidUpdateType(v1), Self::InvalidUpdateType(v2)) => v1 == v2,
+            (Self::InvalidRRule(v1), Self::InvalidRRule(v2)) => v1 == v2,
+            (Self::TooManyOccurrences(v1), Self::TooManyOccurrences(v2)) => v1 == v2,
+            (Self::InvalidConflictMode(v1), Self::InvalidConflictMode(v2)) => v1 == v2,
+            (Self::CapacityExceeded(r1, c1, l1), Self::CapacityExceeded(r2, c2, l2)) => {
+                r1 == r2 && c1 == c2 && l1 == l2
+            }
+            (Self::CapacityInUse(r1, c1, u1), Self::CapacityInUse(r2, c2, u2)) => {
+                r1 == r2 && c1 == c2 && u1 == u2
+            }
+            (Self::ResourceNotFound(v1), Self::ResourceNotFound(v2)) => v1 == v2,
+            (Self::ResourceAlreadyExists(v1), Self::ResourceAlreadyExists(v2)) => v1 == v2,
+            (Self::ResourceRetired(v1), Self::ResourceRetired(v2)) => v1 == v2,
+            (Self::InvalidTimezone(v1), Self::InvalidTimezone(v2)) => v1 == v2,
+            (Self::DurationTooShort(a1, m1), Self::DurationTooShort(a2, m2)) => {
+                a1 == a2 && m1 == m2
+            }
+            (Self::DurationTooLong(a1, m1), Self::DurationTooLong(a2, m2)) => a1 == a2 && m1 == m2,
+            (Self::LeadTimeTooShort(v1), Self::LeadTimeTooShort(v2)) => v1 == v2,
+            (Self::BeyondHorizon(v1), Self::BeyondHorizon(v2)) => v1 == v2,
+            (Self::InvalidPolicy(v1), Self::InvalidPolicy(v2)) => v1 == v2,
+            (Self::InvalidBuffer(v1), Self::InvalidBuffer(v2)) => v1 == v2,
+            (Self::InvalidCapacity(v1), Self::InvalidCapacity(v2)) => v1 == v2,
+            (Self::InvalidBlockMode(v1), Self::InvalidBlockMode(v2)) => v1 == v2,
+            (Self::InvalidHoldTtl(v1), Self::InvalidHoldTtl(v2)) => v1 == v2,
+            (Self::InvalidMinDuration(v1), Self::InvalidMinDuration(v2)) => v1 == v2,
+            (Self::PoolNotFound(v1), Self::PoolNotFound(v2)) => v1 == v2,
+            (Self::WaitlistEntryNotFound(v1), Self::WaitlistEntryNotFound(v2)) => v1 == v2,
+            (Self::InvalidUpdateMask(v1), Self::InvalidUpdateMask(v2)) => v1 == v2,
+            (Self::InvalidIdempotencyKey(v1), Self::InvalidIdempotencyKey(v2)) => v1 == v2,
+            (Self::IdempotencyKeyReused(v1), Self::IdempotencyKeyReused(v2)) => v1 == v2,
+            (Self::IdempotencyKeyInProgress(v1), Self::IdempotencyKeyInProgress(v2)) => v1 == v2,
+            (Self::VersionMismatch(a1, b1, c1), Self::VersionMismatch(a2, b2, c2)) => {
+                a1 == a2 && b1 == b2 && c1 == c2
+            }
+            (Self::PoolAlreadyExists(v1), Self::PoolAlreadyExists(v2)) => v1 == v2,
+            (Self::InvalidPool(v1), Self::InvalidPool(v2)) => v1 == v2,
+            (Self::InvalidPoolStrategy(v1), Self::InvalidPoolStrategy(v2)) => v1 == v2,
+            (Self::NoAvailableResource(v1), Self::NoAvailableResource(v2)) => v1 == v2,
+            (Self::HoldExpired(v1), Self::HoldExpired(v2)) => v1 == v2,
+            (
+                Self::InvalidTransition { from: f1, to: t1 },
+                Self::InvalidTransition { from: f2, to: t2 },
+            ) => f1 == f2 && t1 == t2,
             _ => false,
         }
     }
@@ -93,6 +243,33 @@ impl From<crate::Error> for tonic::Status {
             crate::Error::ConflictReservation(info) => {
                 tonic::Status::failed_precondition(format!("Conflict reservation: {:?}", info))
             }
+            crate::Error::ConflictBatchReservation(index, info) => {
+                tonic::Status::failed_precondition(format!(
+                    "Conflict reservation for item {} in batch: {:?}",
+                    index, info
+                ))
+            }
+            crate::Error::CapacityExceeded(..) => tonic::Status::failed_precondition(e.to_string()),
+            crate::Error::CapacityInUse(..) => tonic::Status::failed_precondition(e.to_string()),
+            crate::Error::ResourceNotFound(_) => tonic::Status::not_found(e.to_string()),
+            crate::Error::ResourceAlreadyExists(_) => tonic::Status::already_exists(e.to_string()),
+            crate::Error::ResourceRetired(_) => tonic::Status::failed_precondition(e.to_string()),
+            crate::Error::PoolNotFound(_) => tonic::Status::not_found(e.to_string()),
+            crate::Error::WaitlistEntryNotFound(_) => tonic::Status::not_found(e.to_string()),
+            crate::Error::VersionMismatch(..) => tonic::Status::aborted(e.to_string()),
+            // the retry could be sent again once the first request is done
+            crate::Error::IdempotencyKeyInProgress(_) => tonic::Status::aborted(e.to_string()),
+            crate::Error::PoolAlreadyExists(_) => tonic::Status::already_exists(e.to_string()),
+            crate::Error::NoAvailableResource(_) | crate::Error::InvalidTransition { .. } => {
+                tonic::Status::failed_precondition(e.to_string())
+            }
+            // lead time and horizon depend on when the reservation is made
+            crate::Error::LeadTimeTooShort(_) | crate::Error::BeyondHorizon(_) => {
+                tonic::Status::failed_precondition(e.to_string())
+            }
+            crate::Error::HoldExpired(id) => {
+                tonic::Status::failed_precondition(format!("Hold expired for reservation {}", id))
+            }
             crate::Error::NotFound => {
                 tonic::Status::not_found("No reservation found by the given condition")
             }
@@ -106,9 +283,31 @@ impl From<crate::Error> for tonic::Status {
                 tonic::Status::invalid_argument(format!("Invalid resource id: {}", id))
             }
             crate::Error::Unknown => tonic::Status::unknown("unknown error"),
-            Error::InvalidPageSize(_) | Error::InvalidStatus(_) | Error::InvalidCursor(_) => {
-                tonic::Status::invalid_argument(e.to_string())
-            }
+            crate::Error::Unauthenticated => tonic::Status::unauthenticated(e.to_string()),
+            crate::Error::PermissionDenied(_) => tonic::Status::permission_denied(e.to_string()),
+            crate::Error::AsOfBeforeChangeLog(_) => tonic::Status::out_of_range(e.to_string()),
+            Error::InvalidPageSize(_)
+            | Error::InvalidStatus(_)
+            | Error::InvalidCursor(_)
+            | Error::InvalidConsumerId(_)
+            | Error::InvalidUpdateType(_)
+            | Error::InvalidRRule(_)
+            | Error::TooManyOccurrences(_)
+            | Error::InvalidConflictMode(_)
+            | Error::InvalidTimezone(_)
+            | Error::DurationTooShort(..)
+            | Error::DurationTooLong(..)
+            | Error::InvalidPolicy(_)
+            | Error::InvalidBuffer(_)
+            | Error::InvalidCapacity(_)
+            | Error::InvalidBlockMode(_)
+            | Error::InvalidHoldTtl(_)
+            | Error::InvalidMinDuration(_)
+            | Error::InvalidPool(_)
+            | Error::InvalidUpdateMask(_)
+            | Error::InvalidIdempotencyKey(_)
+            | Error::IdempotencyKeyReused(_)
+            | Error::InvalidPoolStrategy(_) => tonic::Status::invalid_argument(e.to_string()),
         }
     }
 }
@@ -1,11 +1,13 @@
 mod error;
 mod pager;
 mod pb;
+mod rrule;
 mod types;
 mod utils;
 
 pub use error::*;
 pub use pb::*;
+pub use rrule::*;
 pub use types::*;
 pub use utils::*;
 
@@ -32,6 +34,9 @@ pub enum RsvpStatus {
     Pending,
     Blocked,
     Confirmed,
+    Cancelled,
+    #[sqlx(rename = "checked_in")]
+    CheckedIn,
     Unknown,
 }
 impl From<RsvpStatus> for ReservationStatus {
@@ -40,11 +45,32 @@ impl From<RsvpStatus> for ReservationStatus {
             RsvpStatus::Pending => ReservationStatus::Pending,
             RsvpStatus::Blocked => ReservationStatus::Blocked,
             RsvpStatus::Confirmed => ReservationStatus::Confirmed,
+            RsvpStatus::Cancelled => ReservationStatus::Cancelled,
+            RsvpStatus::CheckedIn => ReservationStatus::CheckedIn,
             RsvpStatus::Unknown => ReservationStatus::Unknown,
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "reservation_update_type", rename_all = "lowercase")]
+pub enum RsvpUpdateType {
+    Unknown,
+    Create,
+    Update,
+    Delete,
+}
+impl From<RsvpUpdateType> for ReservationUpdateType {
+    fn from(op: RsvpUpdateType) -> Self {
+        match op {
+            RsvpUpdateType::Unknown => ReservationUpdateType::Unknown,
+            RsvpUpdateType::Create => ReservationUpdateType::Create,
+            RsvpUpdateType::Update => ReservationUpdateType::Update,
+            RsvpUpdateType::Delete => ReservationUpdateType::Delete,
+        }
+    }
+}
+
 impl Validator for ReservationId {
     fn validate(&self) -> Result<(), Error> {
         if *self <= 0 {
@@ -54,6 +80,7 @@ impl Validator for ReservationId {
     }
 }
 
-pub trait ToSql {
-    fn to_sql(&self) -> String;
+/// build a query whose values are bound as arguments instead of formatted into the SQL
+pub trait ToQuery {
+    fn to_query(&self) -> BoundQuery<'_>;
 }
@@ -23,25 +23,120 @@ pub struct Reservation {
     /// extra fields
     #[prost(string, tag = "7")]
     pub note: ::prost::alloc::string::String,
+    /// id of the series the reservation belongs to, 0 if it is not a recurring reservation
+    #[prost(int64, tag = "8")]
+    pub series_id: i64,
+    /// a pending reservation is a hold, it would be released at this time if not confirmed.
+    /// It is set by the server from the hold_ttl of the request, the value sent by a client is ignored
+    #[prost(message, optional, tag = "9")]
+    pub expires_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// bumped on every change, pass it as expected_version to update only the version read
+    #[prost(int64, tag = "10")]
+    pub version: i64,
 }
 /// To make a reservation, send a ReservationRequest with Reservation object (id should be empty)
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ReserveRequest {
     #[prost(message, optional, tag = "1")]
     pub reservation: ::core::option::Option<Reservation>,
+    /// if set, hold the pending reservation for this long, it would be released if not confirmed in time
+    #[prost(message, optional, tag = "2")]
+    pub hold_ttl: ::core::option::Option<::prost_types::Duration>,
+    /// a retry with the same key gets the original response, x-idempotency-key metadata is used if empty
+    /// a key is kept for each caller and operation, it can't be reused for a different request
+    #[prost(string, tag = "3")]
+    pub idempotency_key: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ReserveResponse {
     #[prost(message, optional, tag = "1")]
     pub reservation: ::core::option::Option<Reservation>,
 }
-///   To update a reservation, send a UpdateRequest only note field will be updated
+/// To make several reservations at once, send a BatchReserveRequest, either all or none of them are made
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchReserveRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub reservations: ::prost::alloc::vec::Vec<Reservation>,
+    /// if set, hold the pending reservations for this long, they would be released if not confirmed in time
+    #[prost(message, optional, tag = "2")]
+    pub hold_ttl: ::core::option::Option<::prost_types::Duration>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchReserveResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub reservations: ::prost::alloc::vec::Vec<Reservation>,
+}
+/// To make a recurring reservation, send a ReserveSeriesRequest with the first occurrence and an iCalendar RRULE,
+/// e.g. FREQ=WEEKLY;BYDAY=TU;COUNT=10. Supported parts are FREQ, INTERVAL, COUNT, UNTIL and BYDAY.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReserveSeriesRequest {
+    /// the first occurrence, every occurrence has the same duration, resource, user and note
+    #[prost(message, optional, tag = "1")]
+    pub reservation: ::core::option::Option<Reservation>,
+    /// recurrence rule, must be bounded by COUNT or UNTIL, COUNT and INTERVAL are at most 366 and 1000.
+    /// It is expanded in the timezone of the resource, so the occurrences keep their local time
+    #[prost(string, tag = "2")]
+    pub rrule: ::prost::alloc::string::String,
+    #[prost(enumeration = "SeriesConflictMode", tag = "3")]
+    pub conflict_mode: i32,
+    /// if set, hold the pending occurrences for this long, they would be released if not confirmed in time
+    #[prost(message, optional, tag = "4")]
+    pub hold_ttl: ::core::option::Option<::prost_types::Duration>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReserveSeriesResponse {
+    #[prost(int64, tag = "1")]
+    pub series_id: i64,
+    /// reserved occurrences
+    #[prost(message, repeated, tag = "2")]
+    pub reservations: ::prost::alloc::vec::Vec<Reservation>,
+    /// occurrences skipped due to conflicts, only for SERIES_CONFLICT_MODE_SKIP
+    #[prost(message, repeated, tag = "3")]
+    pub skipped: ::prost::alloc::vec::Vec<Reservation>,
+}
+/// To cancel an occurrence and all the following ones in its series, send a CancelSeriesRequest. The
+/// occurrence must not be cancelled already, the following ones already cancelled are skipped
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelSeriesRequest {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelSeriesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub reservations: ::prost::alloc::vec::Vec<Reservation>,
+}
+/// To update an occurrence and all the following ones in its series, send a UpdateSeriesRequest
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateSeriesRequest {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    /// the new note, kept if unset
+    #[prost(string, optional, tag = "2")]
+    pub note: ::core::option::Option<::prost::alloc::string::String>,
+    /// move the occurrences by this much, each of them must be free like a new reservation. A conflict
+    /// fails with the index of the occurrence from the given one. Cancelled occurrences are not moved
+    #[prost(message, optional, tag = "3")]
+    pub shift: ::core::option::Option<::prost_types::Duration>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateSeriesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub reservations: ::prost::alloc::vec::Vec<Reservation>,
+}
+/// To update a reservation, put the new values in reservation and their paths in update_mask.
+/// Paths are note, user_id, resource_id, start and end, the listed fields are changed at once
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateRequest {
     #[prost(int64, tag = "1")]
     pub id: i64,
-    #[prost(string, tag = "2")]
-    pub note: ::prost::alloc::string::String,
+    /// if set, fail with ABORTED unless the reservation is still at this version
+    #[prost(int64, optional, tag = "3")]
+    pub expected_version: ::core::option::Option<i64>,
+    #[prost(message, optional, tag = "4")]
+    pub reservation: ::core::option::Option<Reservation>,
+    #[prost(message, optional, tag = "5")]
+    pub update_mask: ::core::option::Option<::prost_types::FieldMask>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateResponse {
@@ -52,6 +147,13 @@ pub struct UpdateResponse {
 pub struct ConfirmRequest {
     #[prost(int64, tag = "1")]
     pub id: i64,
+    /// if set, fail with ABORTED unless the reservation is still at this version
+    #[prost(int64, optional, tag = "2")]
+    pub expected_version: ::core::option::Option<i64>,
+    /// a retry with the same key gets the original response, x-idempotency-key metadata is used if empty
+    /// a key is kept for each caller and operation, it can't be reused for a different request
+    #[prost(string, tag = "3")]
+    pub idempotency_key: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ConfirmResponse {
@@ -62,12 +164,322 @@ pub struct ConfirmResponse {
 pub struct CancelRequest {
     #[prost(int64, tag = "1")]
     pub id: i64,
+    /// if set, fail with ABORTED unless the reservation is still at this version
+    #[prost(int64, optional, tag = "2")]
+    pub expected_version: ::core::option::Option<i64>,
+    /// a retry with the same key gets the original response, x-idempotency-key metadata is used if empty
+    /// a key is kept for each caller and operation, it can't be reused for a different request
+    #[prost(string, tag = "3")]
+    pub idempotency_key: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CancelResponse {
     #[prost(message, optional, tag = "1")]
     pub reservation: ::core::option::Option<Reservation>,
 }
+/// To move a reservation to another time window, send a RescheduleRequest with the new start and end
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RescheduleRequest {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    /// new start time of the reservation
+    #[prost(message, optional, tag = "2")]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    /// new end time of the reservation
+    #[prost(message, optional, tag = "3")]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
+    /// if set, fail with ABORTED unless the reservation is still at this version
+    #[prost(int64, optional, tag = "4")]
+    pub expected_version: ::core::option::Option<i64>,
+    /// a retry with the same key gets the original response, x-idempotency-key metadata is used if empty
+    /// a key is kept for each caller and operation, it can't be reused for a different request
+    #[prost(string, tag = "5")]
+    pub idempotency_key: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RescheduleResponse {
+    #[prost(message, optional, tag = "1")]
+    pub reservation: ::core::option::Option<Reservation>,
+}
+/// To find the reservations a new one would conflict with, without reserving, send a CheckConflictsRequest
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckConflictsRequest {
+    /// the proposed reservation, if it has an id, it is not taken as a conflict of itself
+    #[prost(message, optional, tag = "1")]
+    pub reservation: ::core::option::Option<Reservation>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckConflictsResponse {
+    /// overlapping reservations ordered by start time, owner and note are hidden unless the caller
+    /// owns the reservation or is an admin
+    #[prost(message, repeated, tag = "1")]
+    pub conflicts: ::prost::alloc::vec::Vec<Reservation>,
+}
+/// To find the free intervals of a resource inside a window, send an AvailabilityRequest
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AvailabilityRequest {
+    #[prost(string, tag = "1")]
+    pub resource_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "3")]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
+    /// only return the free intervals lasting at least this long
+    #[prost(message, optional, tag = "4")]
+    pub min_duration: ::core::option::Option<::prost_types::Duration>,
+    /// keep the buffers of the resource free around the existing reservations and the new one
+    #[prost(bool, tag = "5")]
+    pub with_buffers: bool,
+}
+/// a free interval of a resource
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TimeSlot {
+    #[prost(message, optional, tag = "1")]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "2")]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AvailabilityResponse {
+    /// free intervals ordered by start time
+    #[prost(message, repeated, tag = "1")]
+    pub slots: ::prost::alloc::vec::Vec<TimeSlot>,
+}
+/// To block a resource for a window, e.g. for maintenance, send a BlockResourceRequest
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BlockResourceRequest {
+    /// the window to block, user_id is the operator making the block, status is ignored
+    #[prost(message, optional, tag = "1")]
+    pub block: ::core::option::Option<Reservation>,
+    #[prost(enumeration = "BlockMode", tag = "2")]
+    pub mode: i32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BlockResourceResponse {
+    /// the block, with status RESERVATION_STATUS_BLOCKED
+    #[prost(message, optional, tag = "1")]
+    pub block: ::core::option::Option<Reservation>,
+    /// reservations cancelled by the block, pending ones for BLOCK_MODE_CANCEL_PENDING, confirmed ones as
+    /// well for BLOCK_MODE_FORCE, so their users could be told
+    #[prost(message, repeated, tag = "2")]
+    pub cancelled: ::prost::alloc::vec::Vec<Reservation>,
+}
+/// To lift a block, send an UnblockResourceRequest with the id of the block
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnblockResourceRequest {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnblockResourceResponse {
+    #[prost(message, optional, tag = "1")]
+    pub block: ::core::option::Option<Reservation>,
+}
+/// A resource that could be reserved, e.g. a room, a device or a parking lot
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Resource {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+    /// kind of the resource, e.g. room, desk, parking
+    #[prost(string, tag = "3")]
+    pub kind: ::prost::alloc::string::String,
+    /// IANA timezone of the resource, e.g. America/Los_Angeles, defaults to UTC
+    #[prost(string, tag = "4")]
+    pub timezone: ::prost::alloc::string::String,
+    #[prost(map = "string, string", tag = "5")]
+    pub attributes:
+        ::std::collections::HashMap<::prost::alloc::string::String, ::prost::alloc::string::String>,
+    /// how many reservations it could take at the same time, defaults to 1
+    #[prost(int32, tag = "6")]
+    pub capacity: i32,
+    /// a retired resource can't be reserved any more
+    #[prost(bool, tag = "7")]
+    pub active: bool,
+    /// rules a reservation of the resource must follow, blocks are not restricted
+    #[prost(message, optional, tag = "8")]
+    pub policy: ::core::option::Option<BookingPolicy>,
+    /// time kept free before each reservation in whole seconds, e.g. to set up
+    #[prost(message, optional, tag = "9")]
+    pub buffer_before: ::core::option::Option<::prost_types::Duration>,
+    /// time kept free after each reservation in whole seconds, e.g. for cleaning
+    #[prost(message, optional, tag = "10")]
+    pub buffer_after: ::core::option::Option<::prost_types::Duration>,
+}
+/// Booking rules of a resource, an unset rule is not enforced. Durations are in whole seconds
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BookingPolicy {
+    /// shortest reservation allowed
+    #[prost(message, optional, tag = "1")]
+    pub min_duration: ::core::option::Option<::prost_types::Duration>,
+    /// longest reservation allowed
+    #[prost(message, optional, tag = "2")]
+    pub max_duration: ::core::option::Option<::prost_types::Duration>,
+    /// how long ahead a reservation must be made
+    #[prost(message, optional, tag = "3")]
+    pub min_lead_time: ::core::option::Option<::prost_types::Duration>,
+    /// how far ahead a reservation could be made
+    #[prost(message, optional, tag = "4")]
+    pub max_horizon: ::core::option::Option<::prost_types::Duration>,
+}
+/// To register a resource, send a CreateResourceRequest, the resource is active once created
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateResourceRequest {
+    #[prost(message, optional, tag = "1")]
+    pub resource: ::core::option::Option<Resource>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateResourceResponse {
+    #[prost(message, optional, tag = "1")]
+    pub resource: ::core::option::Option<Resource>,
+}
+/// To update name, kind, timezone, attributes and capacity of a resource, send an UpdateResourceRequest
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateResourceRequest {
+    /// id of the resource to update, and the new values of the fields in update_mask
+    #[prost(message, optional, tag = "1")]
+    pub resource: ::core::option::Option<Resource>,
+    /// paths of the fields to change: name, kind, timezone, attributes, capacity, policy, buffer_before and
+    /// buffer_after. The other fields are kept. New buffers apply to reservations made or moved afterwards,
+    /// the existing ones keep the window they were booked with
+    #[prost(message, optional, tag = "2")]
+    pub update_mask: ::core::option::Option<::prost_types::FieldMask>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateResourceResponse {
+    #[prost(message, optional, tag = "1")]
+    pub resource: ::core::option::Option<Resource>,
+}
+/// To retire a resource, send a RetireResourceRequest, its reservations are kept
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RetireResourceRequest {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RetireResourceResponse {
+    #[prost(message, optional, tag = "1")]
+    pub resource: ::core::option::Option<Resource>,
+}
+/// To list resources, send a ListResourcesRequest, all kinds are listed if kind is empty
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListResourcesRequest {
+    #[prost(string, tag = "1")]
+    pub kind: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub include_retired: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListResourcesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub resources: ::prost::alloc::vec::Vec<Resource>,
+}
+/// A pool of interchangeable resources, e.g. the 10-person rooms on floor 3
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResourcePool {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+    /// ids of the member resources, first-fit tries them in this order
+    #[prost(string, repeated, tag = "3")]
+    pub resource_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// To create a pool, send a CreatePoolRequest, all members must be registered
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreatePoolRequest {
+    #[prost(message, optional, tag = "1")]
+    pub pool: ::core::option::Option<ResourcePool>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreatePoolResponse {
+    #[prost(message, optional, tag = "1")]
+    pub pool: ::core::option::Option<ResourcePool>,
+}
+/// To rename a pool or replace its members, send an UpdatePoolRequest
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdatePoolRequest {
+    #[prost(message, optional, tag = "1")]
+    pub pool: ::core::option::Option<ResourcePool>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdatePoolResponse {
+    #[prost(message, optional, tag = "1")]
+    pub pool: ::core::option::Option<ResourcePool>,
+}
+/// To reserve any available member of a pool, send a ReserveAnyRequest
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReserveAnyRequest {
+    #[prost(string, tag = "1")]
+    pub pool_id: ::prost::alloc::string::String,
+    /// the reservation to make, resource_id is set to the member picked
+    #[prost(message, optional, tag = "2")]
+    pub reservation: ::core::option::Option<Reservation>,
+    #[prost(enumeration = "PoolStrategy", tag = "3")]
+    pub strategy: i32,
+    /// if set, hold the pending reservation for this long, it would be released if not confirmed in time
+    #[prost(message, optional, tag = "4")]
+    pub hold_ttl: ::core::option::Option<::prost_types::Duration>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReserveAnyResponse {
+    #[prost(message, optional, tag = "1")]
+    pub reservation: ::core::option::Option<Reservation>,
+}
+/// A user queueing for a window of a resource, promoted to a pending reservation once the window is freed
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WaitlistEntry {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    #[prost(string, tag = "2")]
+    pub user_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub resource_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "4")]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "5")]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(string, tag = "6")]
+    pub note: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "7")]
+    pub created_at: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// To queue for a window that is taken, send a JoinWaitlistRequest
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JoinWaitlistRequest {
+    /// the reservation wanted, id and status are ignored
+    #[prost(message, optional, tag = "1")]
+    pub reservation: ::core::option::Option<Reservation>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JoinWaitlistResponse {
+    #[prost(message, optional, tag = "1")]
+    pub entry: ::core::option::Option<WaitlistEntry>,
+}
+/// To stop queueing, send a LeaveWaitlistRequest with the entry id
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LeaveWaitlistRequest {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LeaveWaitlistResponse {
+    #[prost(message, optional, tag = "1")]
+    pub entry: ::core::option::Option<WaitlistEntry>,
+}
+/// To list the entries queueing for a resource, send a ListWaitlistRequest
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListWaitlistRequest {
+    #[prost(string, tag = "1")]
+    pub resource_id: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListWaitlistResponse {
+    /// entries in the order they would be promoted
+    #[prost(message, repeated, tag = "1")]
+    pub entries: ::prost::alloc::vec::Vec<WaitlistEntry>,
+}
 /// To get a reservation, send a GetRequest with reservation id
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetRequest {
@@ -109,6 +521,11 @@ pub struct ReservationQuery {
     #[prost(bool, tag = "6")]
     #[builder(setter(into), default)]
     pub desc: bool,
+    /// if set, query the reservations as they were at this time, reconstructed from the change log. A change counts
+    /// from the start of its transaction. Fails with OUT_OF_RANGE before the change log has every change
+    #[prost(message, optional, tag = "7")]
+    #[builder(setter(into, strip_option), default)]
+    pub as_of: ::core::option::Option<::prost_types::Timestamp>,
 }
 /// To query reservations, order by reservation id
 #[derive(derive_builder::Builder)]
@@ -139,6 +556,11 @@ pub struct ReservationFilter {
     #[prost(bool, tag = "6")]
     #[builder(setter(into), default)]
     pub desc: bool,
+    /// if set, filter the reservations as they were at this time, reconstructed from the change log. A change
+    /// counts from the start of its transaction. Fails with OUT_OF_RANGE before the change log has every change
+    #[prost(message, optional, tag = "7")]
+    #[builder(setter(into, strip_option), default)]
+    pub as_of: ::core::option::Option<::prost_types::Timestamp>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct QueryRequest {
@@ -170,7 +592,25 @@ pub struct FilterResponse {
 }
 /// Client can listen to reservation updates by sending a ListRequest
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ListenRequest {}
+pub struct ListenRequest {
+    /// consumer id for the listener, if not empty, the changes it acknowledges with ack are saved as
+    /// its read progress, and it resumes after the last acknowledged one when it reconnects
+    #[prost(string, tag = "1")]
+    pub consumer_id: ::prost::alloc::string::String,
+    /// replay the changes after this change id, it takes precedence over the saved progress
+    #[prost(int64, optional, tag = "2")]
+    pub after_change_id: ::core::option::Option<i64>,
+    /// only listen to the changes of the resources with this prefix, if empty, listen to all resources
+    #[prost(string, tag = "3")]
+    pub resource_id: ::prost::alloc::string::String,
+    /// only listen to the changes of this user, if empty, listen to all users,
+    /// only admins could listen to the changes of other users
+    #[prost(string, tag = "4")]
+    pub user_id: ::prost::alloc::string::String,
+    /// only listen to these kinds of changes, if empty, listen to all of them
+    #[prost(enumeration = "ReservationUpdateType", repeated, tag = "5")]
+    pub ops: ::prost::alloc::vec::Vec<i32>,
+}
 /// Server will send ListResponse to client  in streaming response
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListenResponse {
@@ -180,6 +620,66 @@ pub struct ListenResponse {
     /// id for updated reservation
     #[prost(message, optional, tag = "2")]
     pub reservation: ::core::option::Option<Reservation>,
+    /// id of the change, could be used as after_change_id to resume listening. Changes are sent in
+    /// the order they were committed, which is not always the order of their ids
+    #[prost(int64, tag = "3")]
+    pub change_id: i64,
+    /// when the change was made
+    #[prost(message, optional, tag = "4")]
+    pub changed_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// user who made the change, empty if it was made by the server, e.g. releasing an expired hold
+    #[prost(string, tag = "5")]
+    pub actor: ::prost::alloc::string::String,
+    /// the reservation before the change, unset for a new one. The filters of the listen request match
+    /// a change if either the reservation before or after it does
+    #[prost(message, optional, tag = "6")]
+    pub old: ::core::option::Option<Reservation>,
+}
+/// a change recorded for a reservation
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReservationChange {
+    /// id of the change, same as the change_id of the listen stream
+    #[prost(int64, tag = "1")]
+    pub change_id: i64,
+    #[prost(enumeration = "ReservationUpdateType", tag = "2")]
+    pub op: i32,
+    /// when the change was made
+    #[prost(message, optional, tag = "3")]
+    pub changed_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// the reservation before the change, empty for creation
+    #[prost(message, optional, tag = "4")]
+    pub old: ::core::option::Option<Reservation>,
+    /// the reservation after the change, empty for deletion
+    #[prost(message, optional, tag = "5")]
+    pub new: ::core::option::Option<Reservation>,
+    /// user who made the change, empty if it was made by the server, e.g. releasing an expired hold
+    #[prost(string, tag = "6")]
+    pub actor: ::prost::alloc::string::String,
+}
+/// To save the read progress of a consumer, send an AckRequest after handling the changes
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AckRequest {
+    /// consumer id used to listen
+    #[prost(string, tag = "1")]
+    pub consumer_id: ::prost::alloc::string::String,
+    /// the last change handled by the consumer
+    #[prost(int64, tag = "2")]
+    pub change_id: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AckResponse {}
+/// To get the changes of a reservation, send a GetHistoryRequest
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetHistoryRequest {
+    #[prost(int64, tag = "1")]
+    pub reservation_id: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetHistoryResponse {
+    /// changes in the order they were made. Owner and note of each side are hidden unless the caller owns
+    /// it or is an admin, the actor unless it is the caller or the caller is an admin
+    #[prost(message, repeated, tag = "1")]
+    pub changes: ::prost::alloc::vec::Vec<ReservationChange>,
 }
 /// Reservation Status for a given time period
 #[derive(
@@ -191,6 +691,8 @@ pub enum ReservationStatus {
     Pending = 1,
     Confirmed = 2,
     Blocked = 3,
+    Cancelled = 4,
+    CheckedIn = 5,
 }
 impl ReservationStatus {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -203,6 +705,78 @@ impl ReservationStatus {
             ReservationStatus::Pending => "RESERVATION_STATUS_PENDING",
             ReservationStatus::Confirmed => "RESERVATION_STATUS_CONFIRMED",
             ReservationStatus::Blocked => "RESERVATION_STATUS_BLOCKED",
+            ReservationStatus::Cancelled => "RESERVATION_STATUS_CANCELLED",
+            ReservationStatus::CheckedIn => "RESERVATION_STATUS_CHECKED_IN",
+        }
+    }
+}
+/// what to do with the reservations overlapping a block
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum BlockMode {
+    /// fail the block if it overlaps any reservation (default)
+    Reject = 0,
+    /// cancel the overlapping pending reservations, confirmed ones still fail the block
+    CancelPending = 1,
+    /// the block takes precedence, cancel every overlapping reservation, confirmed ones included.
+    /// Other blocks still fail it
+    Force = 2,
+}
+impl BlockMode {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            BlockMode::Reject => "BLOCK_MODE_REJECT",
+            BlockMode::CancelPending => "BLOCK_MODE_CANCEL_PENDING",
+            BlockMode::Force => "BLOCK_MODE_FORCE",
+        }
+    }
+}
+/// what to do if an occurrence of a recurring reservation conflicts with others
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum SeriesConflictMode {
+    /// fail the whole series (default)
+    Fail = 0,
+    /// skip the conflicting occurrences and reserve the rest
+    Skip = 1,
+}
+impl SeriesConflictMode {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            SeriesConflictMode::Fail => "SERIES_CONFLICT_MODE_FAIL",
+            SeriesConflictMode::Skip => "SERIES_CONFLICT_MODE_SKIP",
+        }
+    }
+}
+/// how to pick a member of a pool for a reservation
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum PoolStrategy {
+    /// the first available member in the order of the pool (default)
+    FirstFit = 0,
+    /// the available member with the fewest reservations not ended by the requested start
+    LeastUsed = 1,
+    /// the available member after the one reserved last time
+    RoundRobin = 2,
+}
+impl PoolStrategy {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            PoolStrategy::FirstFit => "POOL_STRATEGY_FIRST_FIT",
+            PoolStrategy::LeastUsed => "POOL_STRATEGY_LEAST_USED",
+            PoolStrategy::RoundRobin => "POOL_STRATEGY_ROUND_ROBIN",
         }
     }
 }
@@ -234,7 +808,9 @@ pub mod reservation_service_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
     use tonic::codegen::http::Uri;
     use tonic::codegen::*;
-    /// Reservation Service
+    /// Reservation Service, the caller is identified by the api token in the `authorization: Bearer <token>`
+    /// metadata, a request without a token is anonymous. Changing a reservation is allowed to its owner and
+    /// admins, operating resources, pools and blocks to admins only, others get PERMISSION_DENIED
     #[derive(Debug, Clone)]
     pub struct ReservationServiceClient<T> {
         inner: tonic::client::Grpc<T>,
@@ -314,11 +890,11 @@ pub mod reservation_service_client {
                 http::uri::PathAndQuery::from_static("/reservation.ReservationService/reserve");
             self.inner.unary(request.into_request(), path, codec).await
         }
-        /// confirm a pending reservation,if reservation is not pending, do nothing
-        pub async fn confirm(
+        /// make several reservations atomically, if any of them conflicts, none of them is made
+        pub async fn batch_reserve(
             &mut self,
-            request: impl tonic::IntoRequest<super::ConfirmRequest>,
-        ) -> Result<tonic::Response<super::ConfirmResponse>, tonic::Status> {
+            request: impl tonic::IntoRequest<super::BatchReserveRequest>,
+        ) -> Result<tonic::Response<super::BatchReserveResponse>, tonic::Status> {
             self.inner.ready().await.map_err(|e| {
                 tonic::Status::new(
                     tonic::Code::Unknown,
@@ -326,14 +902,82 @@ pub mod reservation_service_client {
                 )
             })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path =
-                http::uri::PathAndQuery::from_static("/reservation.ReservationService/confirm");
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/batch_reserve",
+            );
             self.inner.unary(request.into_request(), path, codec).await
         }
-        /// update a reservation note
-        pub async fn update(
+        /// make a recurring reservation
+        pub async fn reserve_series(
             &mut self,
-            request: impl tonic::IntoRequest<super::UpdateRequest>,
+            request: impl tonic::IntoRequest<super::ReserveSeriesRequest>,
+        ) -> Result<tonic::Response<super::ReserveSeriesResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/reserve_series",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// cancel an occurrence of a recurring reservation and all the following ones
+        pub async fn cancel_series(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CancelSeriesRequest>,
+        ) -> Result<tonic::Response<super::CancelSeriesResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/cancel_series",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// update the note or move an occurrence of a recurring reservation and all the following ones
+        pub async fn update_series(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateSeriesRequest>,
+        ) -> Result<tonic::Response<super::UpdateSeriesResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/update_series",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// confirm a pending reservation, fail with FAILED_PRECONDITION if it is not pending. An expired hold can't be confirmed, a block is never confirmed
+        pub async fn confirm(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ConfirmRequest>,
+        ) -> Result<tonic::Response<super::ConfirmResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/confirm");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// update the fields of a reservation listed in the update mask at once
+        pub async fn update(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateRequest>,
         ) -> Result<tonic::Response<super::UpdateResponse>, tonic::Status> {
             self.inner.ready().await.map_err(|e| {
                 tonic::Status::new(
@@ -346,7 +990,58 @@ pub mod reservation_service_client {
                 http::uri::PathAndQuery::from_static("/reservation.ReservationService/update");
             self.inner.unary(request.into_request(), path, codec).await
         }
-        ///cancel a reservation by id
+        /// move a reservation to another time window, fail if the new window conflicts with others
+        pub async fn reschedule(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RescheduleRequest>,
+        ) -> Result<tonic::Response<super::RescheduleResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/reschedule");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// find all the reservations a proposed one would conflict with, nothing is reserved. A resource with
+        /// capacity has none unless it is fully reserved in the window
+        pub async fn check_conflicts(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CheckConflictsRequest>,
+        ) -> Result<tonic::Response<super::CheckConflictsResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/check_conflicts",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// find the free intervals of a resource inside a window, confirmed, pending and blocked reservations are taken
+        pub async fn availability(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AvailabilityRequest>,
+        ) -> Result<tonic::Response<super::AvailabilityResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/availability",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        ///cancel a reservation by id, a block is lifted with unblock_resource instead
         pub async fn cancel(
             &mut self,
             request: impl tonic::IntoRequest<super::CancelRequest>,
@@ -362,6 +1057,209 @@ pub mod reservation_service_client {
                 http::uri::PathAndQuery::from_static("/reservation.ReservationService/cancel");
             self.inner.unary(request.into_request(), path, codec).await
         }
+        /// block a resource for a window, a blocked window can't be reserved. An overlapping confirmed reservation
+        /// fails the block unless the mode is BLOCK_MODE_FORCE
+        pub async fn block_resource(
+            &mut self,
+            request: impl tonic::IntoRequest<super::BlockResourceRequest>,
+        ) -> Result<tonic::Response<super::BlockResourceResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/block_resource",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// lift a block, the window could be reserved again
+        pub async fn unblock_resource(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UnblockResourceRequest>,
+        ) -> Result<tonic::Response<super::UnblockResourceResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/unblock_resource",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// register a resource
+        pub async fn create_resource(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateResourceRequest>,
+        ) -> Result<tonic::Response<super::CreateResourceResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/create_resource",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// update the fields of a resource listed in the update mask, the capacity can't be lowered below the
+        /// reservations the resource has at once from now on
+        pub async fn update_resource(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateResourceRequest>,
+        ) -> Result<tonic::Response<super::UpdateResourceResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/update_resource",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// retire a resource, it can't be reserved any more
+        pub async fn retire_resource(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RetireResourceRequest>,
+        ) -> Result<tonic::Response<super::RetireResourceResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/retire_resource",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// list resources, order by id
+        pub async fn list_resources(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListResourcesRequest>,
+        ) -> Result<tonic::Response<super::ListResourcesResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/list_resources",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// create a pool of resources
+        pub async fn create_pool(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreatePoolRequest>,
+        ) -> Result<tonic::Response<super::CreatePoolResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/create_pool");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// rename a pool or replace its members
+        pub async fn update_pool(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdatePoolRequest>,
+        ) -> Result<tonic::Response<super::UpdatePoolResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/update_pool");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// reserve an available member of a pool, the next member is tried if one is taken
+        pub async fn reserve_any(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReserveAnyRequest>,
+        ) -> Result<tonic::Response<super::ReserveAnyResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/reserve_any");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// queue for a window, the earliest entry is reserved when a cancellation or expiry frees it
+        pub async fn join_waitlist(
+            &mut self,
+            request: impl tonic::IntoRequest<super::JoinWaitlistRequest>,
+        ) -> Result<tonic::Response<super::JoinWaitlistResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/join_waitlist",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// leave the waitlist
+        pub async fn leave_waitlist(
+            &mut self,
+            request: impl tonic::IntoRequest<super::LeaveWaitlistRequest>,
+        ) -> Result<tonic::Response<super::LeaveWaitlistResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/leave_waitlist",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// list the entries queueing for a resource
+        pub async fn list_waitlist(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListWaitlistRequest>,
+        ) -> Result<tonic::Response<super::ListWaitlistResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/list_waitlist",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
         /// get a reservation by id
         pub async fn get(
             &mut self,
@@ -377,6 +1275,22 @@ pub mod reservation_service_client {
             let path = http::uri::PathAndQuery::from_static("/reservation.ReservationService/get");
             self.inner.unary(request.into_request(), path, codec).await
         }
+        /// get the changes of a reservation in order, a deleted reservation still has its history
+        pub async fn get_history(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetHistoryRequest>,
+        ) -> Result<tonic::Response<super::GetHistoryResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/get_history");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
         /// query reservations by resource_id, user_id, status, start time, end time
         pub async fn query(
             &mut self,
@@ -412,11 +1326,12 @@ pub mod reservation_service_client {
                 http::uri::PathAndQuery::from_static("/reservation.ReservationService/filter");
             self.inner.unary(request.into_request(), path, codec).await
         }
-        /// another system could monitor newly added/updated/cancelled/confirmed reservations
+        /// another system could monitor newly added/updated/cancelled/confirmed reservations,
+        /// the changes of other users are redacted for non admins
         pub async fn listen(
             &mut self,
             request: impl tonic::IntoRequest<super::ListenRequest>,
-        ) -> Result<tonic::Response<tonic::codec::Streaming<super::Reservation>>, tonic::Status>
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::ListenResponse>>, tonic::Status>
         {
             self.inner.ready().await.map_err(|e| {
                 tonic::Status::new(
@@ -431,6 +1346,21 @@ pub mod reservation_service_client {
                 .server_streaming(request.into_request(), path, codec)
                 .await
         }
+        /// save the read progress of a listening consumer, it resumes after the acknowledged change
+        pub async fn ack(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AckRequest>,
+        ) -> Result<tonic::Response<super::AckResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/reservation.ReservationService/ack");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -445,26 +1375,129 @@ pub mod reservation_service_server {
             &self,
             request: tonic::Request<super::ReserveRequest>,
         ) -> Result<tonic::Response<super::ReserveResponse>, tonic::Status>;
-        /// confirm a pending reservation,if reservation is not pending, do nothing
+        /// make several reservations atomically, if any of them conflicts, none of them is made
+        async fn batch_reserve(
+            &self,
+            request: tonic::Request<super::BatchReserveRequest>,
+        ) -> Result<tonic::Response<super::BatchReserveResponse>, tonic::Status>;
+        /// make a recurring reservation
+        async fn reserve_series(
+            &self,
+            request: tonic::Request<super::ReserveSeriesRequest>,
+        ) -> Result<tonic::Response<super::ReserveSeriesResponse>, tonic::Status>;
+        /// cancel an occurrence of a recurring reservation and all the following ones
+        async fn cancel_series(
+            &self,
+            request: tonic::Request<super::CancelSeriesRequest>,
+        ) -> Result<tonic::Response<super::CancelSeriesResponse>, tonic::Status>;
+        /// update the note or move an occurrence of a recurring reservation and all the following ones
+        async fn update_series(
+            &self,
+            request: tonic::Request<super::UpdateSeriesRequest>,
+        ) -> Result<tonic::Response<super::UpdateSeriesResponse>, tonic::Status>;
+        /// confirm a pending reservation, fail with FAILED_PRECONDITION if it is not pending. An expired hold can't be confirmed, a block is never confirmed
         async fn confirm(
             &self,
             request: tonic::Request<super::ConfirmRequest>,
         ) -> Result<tonic::Response<super::ConfirmResponse>, tonic::Status>;
-        /// update a reservation note
+        /// update the fields of a reservation listed in the update mask at once
         async fn update(
             &self,
             request: tonic::Request<super::UpdateRequest>,
         ) -> Result<tonic::Response<super::UpdateResponse>, tonic::Status>;
-        ///cancel a reservation by id
+        /// move a reservation to another time window, fail if the new window conflicts with others
+        async fn reschedule(
+            &self,
+            request: tonic::Request<super::RescheduleRequest>,
+        ) -> Result<tonic::Response<super::RescheduleResponse>, tonic::Status>;
+        /// find all the reservations a proposed one would conflict with, nothing is reserved. A resource with
+        /// capacity has none unless it is fully reserved in the window
+        async fn check_conflicts(
+            &self,
+            request: tonic::Request<super::CheckConflictsRequest>,
+        ) -> Result<tonic::Response<super::CheckConflictsResponse>, tonic::Status>;
+        /// find the free intervals of a resource inside a window, confirmed, pending and blocked reservations are taken
+        async fn availability(
+            &self,
+            request: tonic::Request<super::AvailabilityRequest>,
+        ) -> Result<tonic::Response<super::AvailabilityResponse>, tonic::Status>;
+        ///cancel a reservation by id, a block is lifted with unblock_resource instead
         async fn cancel(
             &self,
             request: tonic::Request<super::CancelRequest>,
         ) -> Result<tonic::Response<super::CancelResponse>, tonic::Status>;
+        /// block a resource for a window, a blocked window can't be reserved. An overlapping confirmed reservation
+        /// fails the block unless the mode is BLOCK_MODE_FORCE
+        async fn block_resource(
+            &self,
+            request: tonic::Request<super::BlockResourceRequest>,
+        ) -> Result<tonic::Response<super::BlockResourceResponse>, tonic::Status>;
+        /// lift a block, the window could be reserved again
+        async fn unblock_resource(
+            &self,
+            request: tonic::Request<super::UnblockResourceRequest>,
+        ) -> Result<tonic::Response<super::UnblockResourceResponse>, tonic::Status>;
+        /// register a resource
+        async fn create_resource(
+            &self,
+            request: tonic::Request<super::CreateResourceRequest>,
+        ) -> Result<tonic::Response<super::CreateResourceResponse>, tonic::Status>;
+        /// update the fields of a resource listed in the update mask, the capacity can't be lowered below the
+        /// reservations the resource has at once from now on
+        async fn update_resource(
+            &self,
+            request: tonic::Request<super::UpdateResourceRequest>,
+        ) -> Result<tonic::Response<super::UpdateResourceResponse>, tonic::Status>;
+        /// retire a resource, it can't be reserved any more
+        async fn retire_resource(
+            &self,
+            request: tonic::Request<super::RetireResourceRequest>,
+        ) -> Result<tonic::Response<super::RetireResourceResponse>, tonic::Status>;
+        /// list resources, order by id
+        async fn list_resources(
+            &self,
+            request: tonic::Request<super::ListResourcesRequest>,
+        ) -> Result<tonic::Response<super::ListResourcesResponse>, tonic::Status>;
+        /// create a pool of resources
+        async fn create_pool(
+            &self,
+            request: tonic::Request<super::CreatePoolRequest>,
+        ) -> Result<tonic::Response<super::CreatePoolResponse>, tonic::Status>;
+        /// rename a pool or replace its members
+        async fn update_pool(
+            &self,
+            request: tonic::Request<super::UpdatePoolRequest>,
+        ) -> Result<tonic::Response<super::UpdatePoolResponse>, tonic::Status>;
+        /// reserve an available member of a pool, the next member is tried if one is taken
+        async fn reserve_any(
+            &self,
+            request: tonic::Request<super::ReserveAnyRequest>,
+        ) -> Result<tonic::Response<super::ReserveAnyResponse>, tonic::Status>;
+        /// queue for a window, the earliest entry is reserved when a cancellation or expiry frees it
+        async fn join_waitlist(
+            &self,
+            request: tonic::Request<super::JoinWaitlistRequest>,
+        ) -> Result<tonic::Response<super::JoinWaitlistResponse>, tonic::Status>;
+        /// leave the waitlist
+        async fn leave_waitlist(
+            &self,
+            request: tonic::Request<super::LeaveWaitlistRequest>,
+        ) -> Result<tonic::Response<super::LeaveWaitlistResponse>, tonic::Status>;
+        /// list the entries queueing for a resource
+        async fn list_waitlist(
+            &self,
+            request: tonic::Request<super::ListWaitlistRequest>,
+        ) -> Result<tonic::Response<super::ListWaitlistResponse>, tonic::Status>;
         /// get a reservation by id
         async fn get(
             &self,
             request: tonic::Request<super::GetRequest>,
         ) -> Result<tonic::Response<super::GetResponse>, tonic::Status>;
+        /// get the changes of a reservation in order, a deleted reservation still has its history
+        async fn get_history(
+            &self,
+            request: tonic::Request<super::GetHistoryRequest>,
+        ) -> Result<tonic::Response<super::GetHistoryResponse>, tonic::Status>;
         ///Server streaming response type for the query method.
         type queryStream: futures_core::Stream<Item = Result<super::Reservation, tonic::Status>>
             + Send
@@ -480,16 +1513,24 @@ pub mod reservation_service_server {
             request: tonic::Request<super::FilterRequest>,
         ) -> Result<tonic::Response<super::FilterResponse>, tonic::Status>;
         ///Server streaming response type for the listen method.
-        type listenStream: futures_core::Stream<Item = Result<super::Reservation, tonic::Status>>
+        type listenStream: futures_core::Stream<Item = Result<super::ListenResponse, tonic::Status>>
             + Send
             + 'static;
-        /// another system could monitor newly added/updated/cancelled/confirmed reservations
+        /// another system could monitor newly added/updated/cancelled/confirmed reservations,
+        /// the changes of other users are redacted for non admins
         async fn listen(
             &self,
             request: tonic::Request<super::ListenRequest>,
         ) -> Result<tonic::Response<Self::listenStream>, tonic::Status>;
+        /// save the read progress of a listening consumer, it resumes after the acknowledged change
+        async fn ack(
+            &self,
+            request: tonic::Request<super::AckRequest>,
+        ) -> Result<tonic::Response<super::AckResponse>, tonic::Status>;
     }
-    /// Reservation Service
+    /// Reservation Service, the caller is identified by the api token in the `authorization: Bearer <token>`
+    /// metadata, a request without a token is anonymous. Changing a reservation is allowed to its owner and
+    /// admins, operating resources, pools and blocks to admins only, others get PERMISSION_DENIED
     #[derive(Debug)]
     pub struct ReservationServiceServer<T: ReservationService> {
         inner: _Inner<T>,
@@ -574,18 +1615,21 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/reservation.ReservationService/confirm" => {
+                "/reservation.ReservationService/batch_reserve" => {
                     #[allow(non_camel_case_types)]
-                    struct confirmSvc<T: ReservationService>(pub Arc<T>);
-                    impl<T: ReservationService> tonic::server::UnaryService<super::ConfirmRequest> for confirmSvc<T> {
-                        type Response = super::ConfirmResponse;
+                    struct batch_reserveSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::BatchReserveRequest>
+                        for batch_reserveSvc<T>
+                    {
+                        type Response = super::BatchReserveResponse;
                         type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ConfirmRequest>,
+                            request: tonic::Request<super::BatchReserveRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).confirm(request).await };
+                            let fut = async move { (*inner).batch_reserve(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -594,7 +1638,7 @@ pub mod reservation_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = confirmSvc(inner);
+                        let method = batch_reserveSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
                             accept_compression_encodings,
@@ -605,18 +1649,419 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/reservation.ReservationService/update" => {
+                "/reservation.ReservationService/reserve_series" => {
                     #[allow(non_camel_case_types)]
-                    struct updateSvc<T: ReservationService>(pub Arc<T>);
-                    impl<T: ReservationService> tonic::server::UnaryService<super::UpdateRequest> for updateSvc<T> {
+                    struct reserve_seriesSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::ReserveSeriesRequest>
+                        for reserve_seriesSvc<T>
+                    {
+                        type Response = super::ReserveSeriesResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReserveSeriesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).reserve_series(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = reserve_seriesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/cancel_series" => {
+                    #[allow(non_camel_case_types)]
+                    struct cancel_seriesSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::CancelSeriesRequest>
+                        for cancel_seriesSvc<T>
+                    {
+                        type Response = super::CancelSeriesResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CancelSeriesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).cancel_series(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = cancel_seriesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/update_series" => {
+                    #[allow(non_camel_case_types)]
+                    struct update_seriesSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::UpdateSeriesRequest>
+                        for update_seriesSvc<T>
+                    {
+                        type Response = super::UpdateSeriesResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateSeriesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).update_series(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = update_seriesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/confirm" => {
+                    #[allow(non_camel_case_types)]
+                    struct confirmSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::ConfirmRequest> for confirmSvc<T> {
+                        type Response = super::ConfirmResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ConfirmRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).confirm(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = confirmSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/update" => {
+                    #[allow(non_camel_case_types)]
+                    struct updateSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::UpdateRequest> for updateSvc<T> {
                         type Response = super::UpdateResponse;
                         type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::UpdateRequest>,
+                            request: tonic::Request<super::UpdateRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).update(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = updateSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/reschedule" => {
+                    #[allow(non_camel_case_types)]
+                    struct rescheduleSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::RescheduleRequest> for rescheduleSvc<T>
+                    {
+                        type Response = super::RescheduleResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RescheduleRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).reschedule(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = rescheduleSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/check_conflicts" => {
+                    #[allow(non_camel_case_types)]
+                    struct check_conflictsSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::CheckConflictsRequest>
+                        for check_conflictsSvc<T>
+                    {
+                        type Response = super::CheckConflictsResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CheckConflictsRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).check_conflicts(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = check_conflictsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/availability" => {
+                    #[allow(non_camel_case_types)]
+                    struct availabilitySvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::AvailabilityRequest>
+                        for availabilitySvc<T>
+                    {
+                        type Response = super::AvailabilityResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AvailabilityRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).availability(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = availabilitySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/cancel" => {
+                    #[allow(non_camel_case_types)]
+                    struct cancelSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::CancelRequest> for cancelSvc<T> {
+                        type Response = super::CancelResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CancelRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).cancel(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = cancelSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/block_resource" => {
+                    #[allow(non_camel_case_types)]
+                    struct block_resourceSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::BlockResourceRequest>
+                        for block_resourceSvc<T>
+                    {
+                        type Response = super::BlockResourceResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::BlockResourceRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).block_resource(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = block_resourceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/unblock_resource" => {
+                    #[allow(non_camel_case_types)]
+                    struct unblock_resourceSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::UnblockResourceRequest>
+                        for unblock_resourceSvc<T>
+                    {
+                        type Response = super::UnblockResourceResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UnblockResourceRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).unblock_resource(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = unblock_resourceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/create_resource" => {
+                    #[allow(non_camel_case_types)]
+                    struct create_resourceSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::CreateResourceRequest>
+                        for create_resourceSvc<T>
+                    {
+                        type Response = super::CreateResourceResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateResourceRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).create_resource(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = create_resourceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/update_resource" => {
+                    #[allow(non_camel_case_types)]
+                    struct update_resourceSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::UpdateResourceRequest>
+                        for update_resourceSvc<T>
+                    {
+                        type Response = super::UpdateResourceResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateResourceRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).update(request).await };
+                            let fut = async move { (*inner).update_resource(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -625,7 +2070,7 @@ pub mod reservation_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = updateSvc(inner);
+                        let method = update_resourceSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
                             accept_compression_encodings,
@@ -636,18 +2081,21 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/reservation.ReservationService/cancel" => {
+                "/reservation.ReservationService/retire_resource" => {
                     #[allow(non_camel_case_types)]
-                    struct cancelSvc<T: ReservationService>(pub Arc<T>);
-                    impl<T: ReservationService> tonic::server::UnaryService<super::CancelRequest> for cancelSvc<T> {
-                        type Response = super::CancelResponse;
+                    struct retire_resourceSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::RetireResourceRequest>
+                        for retire_resourceSvc<T>
+                    {
+                        type Response = super::RetireResourceResponse;
                         type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::CancelRequest>,
+                            request: tonic::Request<super::RetireResourceRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).cancel(request).await };
+                            let fut = async move { (*inner).retire_resource(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -656,7 +2104,245 @@ pub mod reservation_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = cancelSvc(inner);
+                        let method = retire_resourceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/list_resources" => {
+                    #[allow(non_camel_case_types)]
+                    struct list_resourcesSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::ListResourcesRequest>
+                        for list_resourcesSvc<T>
+                    {
+                        type Response = super::ListResourcesResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListResourcesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).list_resources(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = list_resourcesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/create_pool" => {
+                    #[allow(non_camel_case_types)]
+                    struct create_poolSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::CreatePoolRequest>
+                        for create_poolSvc<T>
+                    {
+                        type Response = super::CreatePoolResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreatePoolRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).create_pool(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = create_poolSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/update_pool" => {
+                    #[allow(non_camel_case_types)]
+                    struct update_poolSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::UpdatePoolRequest>
+                        for update_poolSvc<T>
+                    {
+                        type Response = super::UpdatePoolResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdatePoolRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).update_pool(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = update_poolSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/reserve_any" => {
+                    #[allow(non_camel_case_types)]
+                    struct reserve_anySvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::ReserveAnyRequest>
+                        for reserve_anySvc<T>
+                    {
+                        type Response = super::ReserveAnyResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReserveAnyRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).reserve_any(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = reserve_anySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/join_waitlist" => {
+                    #[allow(non_camel_case_types)]
+                    struct join_waitlistSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::JoinWaitlistRequest>
+                        for join_waitlistSvc<T>
+                    {
+                        type Response = super::JoinWaitlistResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::JoinWaitlistRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).join_waitlist(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = join_waitlistSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/leave_waitlist" => {
+                    #[allow(non_camel_case_types)]
+                    struct leave_waitlistSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::LeaveWaitlistRequest>
+                        for leave_waitlistSvc<T>
+                    {
+                        type Response = super::LeaveWaitlistResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::LeaveWaitlistRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).leave_waitlist(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = leave_waitlistSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/list_waitlist" => {
+                    #[allow(non_camel_case_types)]
+                    struct list_waitlistSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::ListWaitlistRequest>
+                        for list_waitlistSvc<T>
+                    {
+                        type Response = super::ListWaitlistResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListWaitlistRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).list_waitlist(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = list_waitlistSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
                             accept_compression_encodings,
@@ -698,6 +2384,40 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/reservation.ReservationService/get_history" => {
+                    #[allow(non_camel_case_types)]
+                    struct get_historySvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::GetHistoryRequest>
+                        for get_historySvc<T>
+                    {
+                        type Response = super::GetHistoryResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetHistoryRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).get_history(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = get_historySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/reservation.ReservationService/query" => {
                     #[allow(non_camel_case_types)]
                     struct querySvc<T: ReservationService>(pub Arc<T>);
@@ -771,7 +2491,7 @@ pub mod reservation_service_server {
                         tonic::server::ServerStreamingService<super::ListenRequest>
                         for listenSvc<T>
                     {
-                        type Response = super::Reservation;
+                        type Response = super::ListenResponse;
                         type ResponseStream = T::listenStream;
                         type Future =
                             BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
@@ -800,6 +2520,37 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/reservation.ReservationService/ack" => {
+                    #[allow(non_camel_case_types)]
+                    struct ackSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::AckRequest> for ackSvc<T> {
+                        type Response = super::AckResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AckRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).ack(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ackSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => Box::pin(async move {
                     Ok(http::Response::builder()
                         .status(200)
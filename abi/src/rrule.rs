@@ -0,0 +1,468 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday,
+};
+
+use crate::Error;
+
+/// max occurrences a recurring rule could expand to
+pub const MAX_OCCURRENCES: usize = 366;
+// occurrences of a larger interval would be years apart even for a daily rule
+const MAX_INTERVAL: u32 = 1000;
+// stop looking for occurrences if too many periods have no valid date (e.g. Feb 30)
+const MAX_EMPTY_PERIODS: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A subset of iCalendar RRULE (RFC 5545), supports FREQ, INTERVAL, COUNT, UNTIL and BYDAY.
+/// A rule must be bounded by either COUNT or UNTIL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub count: Option<usize>,
+    pub until: Option<DateTime<Utc>>,
+    pub by_day: Vec<Weekday>,
+}
+
+impl FromStr for RRule {
+    type Err = Error;
+
+    // FREQ=WEEKLY;INTERVAL=1;BYDAY=TU,TH;COUNT=10
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || Error::InvalidRRule(s.to_string());
+        let rule = s.trim();
+        let rule = rule.strip_prefix("RRULE:").unwrap_or(rule);
+
+        let mut freq = None;
+        let mut interval = 1;
+        let mut count = None;
+        let mut until = None;
+        let mut by_day = vec![];
+        for part in rule.split(';').filter(|p| !p.is_empty()) {
+            let (key, value) = part.split_once('=').ok_or_else(err)?;
+            let value = value.to_ascii_uppercase();
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match value.as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(err()),
+                    })
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .parse()
+                        .ok()
+                        .filter(|v| (1..=MAX_INTERVAL).contains(v))
+                        .ok_or_else(err)?
+                }
+                "COUNT" => {
+                    count = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|v| (1..=MAX_OCCURRENCES).contains(v))
+                            .ok_or_else(err)?,
+                    )
+                }
+                "UNTIL" => until = Some(parse_until(&value).ok_or_else(err)?),
+                "BYDAY" => {
+                    by_day = value
+                        .split(',')
+                        .map(parse_weekday)
+                        .collect::<Option<_>>()
+                        .ok_or_else(err)?
+                }
+                // week always starts on monday
+                "WKST" if value == "MO" => {}
+                _ => return Err(err()),
+            }
+        }
+
+        let freq = freq.ok_or_else(err)?;
+        // RFC 5545 doesn't allow both COUNT and UNTIL, and an endless series can't be reserved
+        if count.is_some() == until.is_some() {
+            return Err(err());
+        }
+        if !by_day.is_empty() && !matches!(freq, Frequency::Daily | Frequency::Weekly) {
+            return Err(err());
+        }
+        by_day.sort_by_key(|d: &Weekday| d.num_days_from_monday());
+        by_day.dedup();
+
+        Ok(Self {
+            freq,
+            interval,
+            count,
+            until,
+            by_day,
+        })
+    }
+}
+
+impl fmt::Display for RRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let freq = match self.freq {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+        write!(f, "FREQ={};INTERVAL={}", freq, self.interval)?;
+        if !self.by_day.is_empty() {
+            let days: Vec<_> = self.by_day.iter().map(|d| weekday_str(*d)).collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%dT%H%M%SZ"))?;
+        }
+        Ok(())
+    }
+}
+
+impl RRule {
+    /// expand the rule to the start time of each occurrence, `dtstart` is the earliest one.
+    /// The rule is expanded in the timezone of `dtstart`, so the occurrences keep its local time
+    /// and weekday across daylight saving changes.
+    pub fn occurrences<Tz: TimeZone>(
+        &self,
+        dtstart: DateTime<Tz>,
+    ) -> Result<Vec<DateTime<Utc>>, Error> {
+        let tz = dtstart.timezone();
+        let start = dtstart.naive_local();
+        let mut occurrences = vec![];
+        let mut empty_periods = 0;
+        for period in 0.. {
+            let candidates = self
+                .candidates(start, period)
+                .ok_or_else(|| Error::InvalidRRule(self.to_string()))?;
+            if candidates.is_empty() {
+                empty_periods += 1;
+                if empty_periods > MAX_EMPTY_PERIODS {
+                    break;
+                }
+                continue;
+            }
+            empty_periods = 0;
+
+            let candidates = candidates
+                .into_iter()
+                .filter(|dt| *dt >= start)
+                .filter_map(|dt| to_utc(&tz, dt));
+            for dt in candidates {
+                if matches!(self.until, Some(until) if dt > until) {
+                    return Ok(occurrences);
+                }
+                if occurrences.len() == MAX_OCCURRENCES {
+                    return Err(Error::TooManyOccurrences(MAX_OCCURRENCES));
+                }
+                occurrences.push(dt);
+                if Some(occurrences.len()) == self.count {
+                    return Ok(occurrences);
+                }
+            }
+        }
+        Ok(occurrences)
+    }
+
+    // the local occurrences in the nth period (day, week, month or year) since start,
+    // None if they are out of the supported date range
+    fn candidates(&self, start: NaiveDateTime, period: u32) -> Option<Vec<NaiveDateTime>> {
+        let step = i64::from(period).checked_mul(i64::from(self.interval))?;
+        let date = start.date();
+        let dates = match self.freq {
+            Frequency::Daily => {
+                let day = add_days(date, step)?;
+                if self.by_day.is_empty() || self.by_day.contains(&day.weekday()) {
+                    vec![day]
+                } else {
+                    vec![]
+                }
+            }
+            Frequency::Weekly => {
+                let weekday = date.weekday().num_days_from_monday() as i64;
+                let monday = add_days(date, step.checked_mul(7)? - weekday)?;
+                if self.by_day.is_empty() {
+                    vec![add_days(monday, weekday)?]
+                } else {
+                    self.by_day
+                        .iter()
+                        .map(|d| add_days(monday, d.num_days_from_monday() as i64))
+                        .collect::<Option<_>>()?
+                }
+            }
+            Frequency::Monthly => {
+                let months = (date.year() as i64 * 12 + date.month0() as i64).checked_add(step)?;
+                let year = i32::try_from(months.div_euclid(12)).ok()?;
+                let month = months.rem_euclid(12) as u32 + 1;
+                NaiveDate::from_ymd_opt(year, month, 1)?;
+                NaiveDate::from_ymd_opt(year, month, date.day())
+                    .into_iter()
+                    .collect()
+            }
+            Frequency::Yearly => {
+                let year = date.year().checked_add(i32::try_from(step).ok()?)?;
+                NaiveDate::from_ymd_opt(year, date.month(), 1)?;
+                NaiveDate::from_ymd_opt(year, date.month(), date.day())
+                    .into_iter()
+                    .collect()
+            }
+        };
+
+        Some(
+            dates
+                .into_iter()
+                .map(|d| d.and_time(start.time()))
+                .collect(),
+        )
+    }
+}
+
+// `date` moved by `days`, None if it's out of range
+fn add_days(date: NaiveDate, days: i64) -> Option<NaiveDate> {
+    // Duration::days panics if the duration doesn't fit in i64 milliseconds
+    days.checked_mul(24 * 60 * 60 * 1000)?;
+    date.checked_add_signed(Duration::days(days))
+}
+
+// a local time skipped by a daylight saving change is moved forward by the change as RFC 5545
+// says, an ambiguous one takes the earlier offset
+fn to_utc<Tz: TimeZone>(tz: &Tz, dt: NaiveDateTime) -> Option<DateTime<Utc>> {
+    let local = match tz.from_local_datetime(&dt) {
+        LocalResult::None => tz
+            .from_local_datetime(&(dt + Duration::hours(1)))
+            .earliest(),
+        local => local.earliest(),
+    };
+    local.map(|dt| dt.with_timezone(&Utc))
+}
+
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    match s {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn weekday_str(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+// UNTIL is either a UTC date-time (20230131T235959Z) or a date (20230131) which includes the whole day
+fn parse_until(s: &str) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = NaiveDateTime::parse_from_str(s, "%Y%m%dT%H%M%SZ") {
+        return Some(Utc.from_utc_datetime(&dt));
+    }
+    let date = NaiveDate::parse_from_str(s, "%Y%m%d").ok()?;
+    Some(Utc.from_utc_datetime(&date.and_hms_opt(23, 59, 59)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(rule: &str, dtstart: &str) -> Vec<String> {
+        let rule: RRule = rule.parse().unwrap();
+        rule.occurrences(dtstart.parse::<DateTime<Utc>>().unwrap())
+            .unwrap()
+            .into_iter()
+            .map(|dt| dt.to_rfc3339())
+            .collect()
+    }
+
+    #[test]
+    fn rrule_should_parse() {
+        let rule: RRule = "RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=TH,TU;COUNT=10"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            rule,
+            RRule {
+                freq: Frequency::Weekly,
+                interval: 2,
+                count: Some(10),
+                until: None,
+                by_day: vec![Weekday::Tue, Weekday::Thu],
+            }
+        );
+
+        assert_eq!(
+            rule.to_string(),
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=TU,TH;COUNT=10"
+        );
+
+        let rule: RRule = "FREQ=DAILY;UNTIL=20230131".parse().unwrap();
+        assert_eq!(
+            rule.until.unwrap().to_rfc3339(),
+            "2023-01-31T23:59:59+00:00"
+        );
+        assert_eq!(
+            rule.to_string(),
+            "FREQ=DAILY;INTERVAL=1;UNTIL=20230131T235959Z"
+        );
+    }
+
+    #[test]
+    fn invalid_rrule_should_fail() {
+        for rule in [
+            "",
+            "FREQ=HOURLY;COUNT=3",
+            "FREQ=DAILY",
+            "FREQ=DAILY;COUNT=3;UNTIL=20230131",
+            "FREQ=DAILY;COUNT=0",
+            "FREQ=DAILY;INTERVAL=0;COUNT=3",
+            "FREQ=DAILY;INTERVAL=1000000000;COUNT=2",
+            "FREQ=DAILY;COUNT=1000",
+            "FREQ=MONTHLY;BYDAY=MO;COUNT=3",
+            "FREQ=WEEKLY;BYDAY=1MO;COUNT=3",
+            "FREQ=WEEKLY;BYMONTH=1;COUNT=3",
+        ] {
+            assert_eq!(
+                rule.parse::<RRule>().unwrap_err(),
+                Error::InvalidRRule(rule.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn weekly_rrule_should_expand() {
+        // 2023-01-03 is a Tuesday
+        assert_eq!(
+            expand("FREQ=WEEKLY;COUNT=3", "2023-01-03T10:00:00Z"),
+            vec![
+                "2023-01-03T10:00:00+00:00",
+                "2023-01-10T10:00:00+00:00",
+                "2023-01-17T10:00:00+00:00",
+            ]
+        );
+        assert_eq!(
+            expand(
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TU,FR;UNTIL=20230116",
+                "2023-01-03T10:00:00Z"
+            ),
+            vec![
+                "2023-01-03T10:00:00+00:00",
+                "2023-01-06T10:00:00+00:00",
+                "2023-01-16T10:00:00+00:00",
+            ]
+        );
+    }
+
+    #[test]
+    fn daily_rrule_should_expand() {
+        assert_eq!(
+            expand("FREQ=DAILY;INTERVAL=2;COUNT=3", "2023-01-30T10:00:00Z"),
+            vec![
+                "2023-01-30T10:00:00+00:00",
+                "2023-02-01T10:00:00+00:00",
+                "2023-02-03T10:00:00+00:00",
+            ]
+        );
+        // weekdays only
+        assert_eq!(
+            expand(
+                "FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR;COUNT=3",
+                "2023-01-06T10:00:00Z"
+            ),
+            vec![
+                "2023-01-06T10:00:00+00:00",
+                "2023-01-09T10:00:00+00:00",
+                "2023-01-10T10:00:00+00:00",
+            ]
+        );
+    }
+
+    #[test]
+    fn monthly_and_yearly_rrule_should_skip_invalid_dates() {
+        assert_eq!(
+            expand("FREQ=MONTHLY;COUNT=3", "2023-01-31T10:00:00Z"),
+            vec![
+                "2023-01-31T10:00:00+00:00",
+                "2023-03-31T10:00:00+00:00",
+                "2023-05-31T10:00:00+00:00",
+            ]
+        );
+        assert_eq!(
+            expand("FREQ=YEARLY;COUNT=2", "2024-02-29T10:00:00Z"),
+            vec!["2024-02-29T10:00:00+00:00", "2028-02-29T10:00:00+00:00"]
+        );
+    }
+
+    #[test]
+    fn too_many_occurrences_should_fail() {
+        let rule: RRule = "FREQ=DAILY;UNTIL=20260101".parse().unwrap();
+        let err = rule
+            .occurrences("2023-01-01T10:00:00Z".parse::<DateTime<Utc>>().unwrap())
+            .unwrap_err();
+        assert_eq!(err, Error::TooManyOccurrences(MAX_OCCURRENCES));
+    }
+
+    #[test]
+    fn out_of_range_rrule_should_fail() {
+        let rule = RRule {
+            freq: Frequency::Daily,
+            interval: u32::MAX,
+            count: Some(2),
+            until: None,
+            by_day: vec![],
+        };
+        let err = rule
+            .occurrences("2023-01-01T10:00:00Z".parse::<DateTime<Utc>>().unwrap())
+            .unwrap_err();
+        assert_eq!(err, Error::InvalidRRule(rule.to_string()));
+    }
+
+    #[test]
+    fn rrule_should_expand_in_local_time() {
+        let tz: chrono_tz::Tz = "America/Los_Angeles".parse().unwrap();
+        // 17:30 on Friday 2023-03-10 in Los Angeles is 01:30 on Saturday in UTC
+        let dtstart = tz.with_ymd_and_hms(2023, 3, 10, 17, 30, 0).unwrap();
+        let rule: RRule = "FREQ=WEEKLY;BYDAY=FR;COUNT=2".parse().unwrap();
+        let occurrences: Vec<_> = rule
+            .occurrences(dtstart)
+            .unwrap()
+            .into_iter()
+            .map(|dt| dt.to_rfc3339())
+            .collect();
+        // still 17:30 after daylight saving starts on 2023-03-12
+        assert_eq!(
+            occurrences,
+            vec!["2023-03-11T01:30:00+00:00", "2023-03-18T00:30:00+00:00"]
+        );
+
+        // 02:30 doesn't exist on 2023-03-12, it's moved to 03:30
+        let dtstart = tz.with_ymd_and_hms(2023, 3, 11, 2, 30, 0).unwrap();
+        let rule: RRule = "FREQ=DAILY;COUNT=2".parse().unwrap();
+        assert_eq!(
+            rule.occurrences(dtstart).unwrap()[1].to_rfc3339(),
+            "2023-03-12T10:30:00+00:00"
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{
+    convert_to_duration, convert_to_timestamp, validate_range, AvailabilityRequest, Error,
+    TimeSlot, Validator,
+};
+use chrono::{DateTime, Duration, Utc};
+
+impl AvailabilityRequest {
+    pub fn new(
+        resource_id: impl Into<String>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        min_duration: Duration,
+    ) -> Self {
+        Self {
+            resource_id: resource_id.into(),
+            start: Some(convert_to_timestamp(start)),
+            end: Some(convert_to_timestamp(end)),
+            min_duration: Some(prost_types::Duration {
+                seconds: min_duration.num_seconds(),
+                nanos: 0,
+            }),
+            with_buffers: false,
+        }
+    }
+
+    /// minimal length of a free interval, zero if not given
+    pub fn min_duration(&self) -> Duration {
+        self.min_duration
+            .as_ref()
+            .map(convert_to_duration)
+            .unwrap_or_else(Duration::zero)
+    }
+}
+
+impl Validator for AvailabilityRequest {
+    fn validate(&self) -> Result<(), Error> {
+        if self.resource_id.is_empty() {
+            return Err(Error::InvalidResourceId(self.resource_id.clone()));
+        }
+        validate_range(self.start.as_ref(), self.end.as_ref())?;
+        let min_duration = self.min_duration();
+        if min_duration < Duration::zero() {
+            return Err(Error::InvalidMinDuration(min_duration.num_seconds()));
+        }
+        Ok(())
+    }
+}
+
+impl TimeSlot {
+    pub fn new(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self {
+            start: Some(convert_to_timestamp(start)),
+            end: Some(convert_to_timestamp(end)),
+        }
+    }
+}
+
+/// free intervals inside `window` lasting at least `min_duration`, given the busy intervals
+/// `[start, end)` and the units of capacity each takes. An interval is free while less than
+/// `capacity` units are taken.
+pub fn free_slots(
+    (start, end): (DateTime<Utc>, DateTime<Utc>),
+    busy: &[(DateTime<Utc>, DateTime<Utc>, i64)],
+    capacity: i64,
+    min_duration: Duration,
+) -> Vec<TimeSlot> {
+    let mut events: Vec<(DateTime<Utc>, i64)> = busy
+        .iter()
+        .filter(|(s, e, _)| *s < end && *e > start)
+        .flat_map(|&(s, e, units)| [(s.max(start), units), (e.min(end), -units)])
+        .collect();
+    // ends sort before starts at the same time as intervals are [start, end)
+    events.sort_unstable();
+
+    let mut slots = vec![];
+    let mut push = |from: DateTime<Utc>, to: DateTime<Utc>| {
+        if to > from && to - from >= min_duration {
+            slots.push(TimeSlot::new(from, to));
+        }
+    };
+    let mut used = 0;
+    let mut free_since = Some(start);
+    for (at, units) in events {
+        used += units;
+        match free_since {
+            Some(since) if used >= capacity => {
+                push(since, at);
+                free_since = None;
+            }
+            None if used < capacity => free_since = Some(at),
+            _ => {}
+        }
+    }
+    if let Some(since) = free_since {
+        push(since, end);
+    }
+    slots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn t(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    fn day() -> (DateTime<Utc>, DateTime<Utc>) {
+        (t("2023-01-10T08:00:00Z"), t("2023-01-10T18:00:00Z"))
+    }
+
+    #[test]
+    fn free_slots_should_be_gaps_between_reservations() {
+        let busy = [
+            (t("2023-01-10T10:00:00Z"), t("2023-01-10T11:00:00Z"), 1),
+            (t("2023-01-10T07:00:00Z"), t("2023-01-10T09:00:00Z"), 1),
+            (t("2023-01-10T11:00:00Z"), t("2023-01-10T12:00:00Z"), 1),
+        ];
+        let slots = free_slots(day(), &busy, 1, Duration::zero());
+        assert_eq!(
+            slots,
+            vec![
+                TimeSlot::new(t("2023-01-10T09:00:00Z"), t("2023-01-10T10:00:00Z")),
+                TimeSlot::new(t("2023-01-10T12:00:00Z"), t("2023-01-10T18:00:00Z")),
+            ]
+        );
+    }
+
+    #[test]
+    fn free_slots_should_skip_short_gaps() {
+        let busy = [
+            (t("2023-01-10T09:00:00Z"), t("2023-01-10T10:00:00Z"), 1),
+            (t("2023-01-10T10:30:00Z"), t("2023-01-10T17:00:00Z"), 1),
+        ];
+        let slots = free_slots(day(), &busy, 1, Duration::hours(1));
+        assert_eq!(
+            slots,
+            vec![
+                TimeSlot::new(t("2023-01-10T08:00:00Z"), t("2023-01-10T09:00:00Z")),
+                TimeSlot::new(t("2023-01-10T17:00:00Z"), t("2023-01-10T18:00:00Z")),
+            ]
+        );
+    }
+
+    #[test]
+    fn free_slots_should_respect_capacity() {
+        let busy = [
+            (t("2023-01-10T09:00:00Z"), t("2023-01-10T12:00:00Z"), 1),
+            (t("2023-01-10T10:00:00Z"), t("2023-01-10T11:00:00Z"), 1),
+            // a block takes the whole capacity
+            (t("2023-01-10T14:00:00Z"), t("2023-01-10T15:00:00Z"), 2),
+        ];
+        let slots = free_slots(day(), &busy, 2, Duration::zero());
+        assert_eq!(
+            slots,
+            vec![
+                TimeSlot::new(t("2023-01-10T08:00:00Z"), t("2023-01-10T10:00:00Z")),
+                TimeSlot::new(t("2023-01-10T11:00:00Z"), t("2023-01-10T14:00:00Z")),
+                TimeSlot::new(t("2023-01-10T15:00:00Z"), t("2023-01-10T18:00:00Z")),
+            ]
+        );
+    }
+
+    #[test]
+    fn availability_request_should_validate_min_duration() {
+        let (start, end) = day();
+        let request = AvailabilityRequest::new("room-1", start, end, Duration::minutes(-1));
+        assert_eq!(request.validate(), Err(Error::InvalidMinDuration(-60)));
+    }
+}
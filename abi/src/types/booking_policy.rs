@@ -0,0 +1,179 @@
+use crate::{convert_to_duration, BookingPolicy, Error, Validator};
+use chrono::{DateTime, Duration, Utc};
+
+impl BookingPolicy {
+    /// check a reservation from `start` to `end`, made at `now`, against the policy
+    pub fn check(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        let duration = end - start;
+        if let Some(min) = to_duration(&self.min_duration) {
+            if duration < min {
+                return Err(Error::DurationTooShort(
+                    duration.num_seconds(),
+                    min.num_seconds(),
+                ));
+            }
+        }
+        if let Some(max) = to_duration(&self.max_duration) {
+            if duration > max {
+                return Err(Error::DurationTooLong(
+                    duration.num_seconds(),
+                    max.num_seconds(),
+                ));
+            }
+        }
+        if let Some(lead_time) = to_duration(&self.min_lead_time) {
+            if start - now < lead_time {
+                return Err(Error::LeadTimeTooShort(lead_time.num_seconds()));
+            }
+        }
+        if let Some(horizon) = to_duration(&self.max_horizon) {
+            if start - now > horizon {
+                return Err(Error::BeyondHorizon(horizon.num_seconds()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Validator for BookingPolicy {
+    fn validate(&self) -> Result<(), Error> {
+        let rules = [
+            ("min_duration", &self.min_duration),
+            ("max_duration", &self.max_duration),
+            ("min_lead_time", &self.min_lead_time),
+            ("max_horizon", &self.max_horizon),
+        ];
+        for (name, rule) in rules {
+            if matches!(to_duration(rule), Some(d) if d < Duration::zero()) {
+                return Err(Error::InvalidPolicy(format!("{} is negative", name)));
+            }
+            if matches!(rule, Some(d) if d.nanos != 0) {
+                return Err(Error::InvalidPolicy(format!(
+                    "{} is not in whole seconds",
+                    name
+                )));
+            }
+        }
+        if let (Some(min), Some(max)) = (
+            to_duration(&self.min_duration),
+            to_duration(&self.max_duration),
+        ) {
+            if min > max {
+                return Err(Error::InvalidPolicy(
+                    "min_duration is longer than max_duration".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn to_duration(d: &Option<prost_types::Duration>) -> Option<Duration> {
+    d.as_ref().map(convert_to_duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(seconds: i64) -> Option<prost_types::Duration> {
+        Some(prost_types::Duration { seconds, nanos: 0 })
+    }
+
+    fn meeting_room_policy() -> BookingPolicy {
+        BookingPolicy {
+            min_duration: secs(15 * 60),
+            max_duration: secs(4 * 3600),
+            min_lead_time: secs(15 * 60),
+            max_horizon: secs(30 * 86400),
+        }
+    }
+
+    #[test]
+    fn empty_policy_should_allow_anything() {
+        let now = Utc::now();
+        let policy = BookingPolicy::default();
+        assert!(policy
+            .check(now - Duration::days(1), now + Duration::days(365), now)
+            .is_ok());
+    }
+
+    #[test]
+    fn policy_should_reject_each_violated_rule() {
+        let now: DateTime<Utc> = "2023-01-10T10:00:00Z".parse().unwrap();
+        let start = now + Duration::hours(1);
+        let policy = meeting_room_policy();
+        assert!(policy.check(start, start + Duration::hours(4), now).is_ok());
+
+        let err = policy
+            .check(start, start + Duration::minutes(10), now)
+            .unwrap_err();
+        assert_eq!(err, Error::DurationTooShort(600, 900));
+
+        let err = policy
+            .check(start, start + Duration::hours(5), now)
+            .unwrap_err();
+        assert_eq!(err, Error::DurationTooLong(5 * 3600, 4 * 3600));
+        assert_eq!(
+            err.to_string(),
+            "Reservation lasts 5h, longer than the maximum of 4h"
+        );
+
+        let start = now + Duration::minutes(5);
+        let err = policy
+            .check(start, start + Duration::hours(1), now)
+            .unwrap_err();
+        assert_eq!(err, Error::LeadTimeTooShort(900));
+
+        let start = now + Duration::days(31);
+        let err = policy
+            .check(start, start + Duration::hours(1), now)
+            .unwrap_err();
+        assert_eq!(err, Error::BeyondHorizon(30 * 86400));
+        assert_eq!(
+            err.to_string(),
+            "Reservation starts beyond the booking horizon of 30d"
+        );
+    }
+
+    #[test]
+    fn policy_should_validate() {
+        assert!(meeting_room_policy().validate().is_ok());
+
+        let policy = BookingPolicy {
+            min_duration: secs(3600),
+            max_duration: secs(60),
+            ..Default::default()
+        };
+        assert_eq!(
+            policy.validate().unwrap_err(),
+            Error::InvalidPolicy("min_duration is longer than max_duration".to_string())
+        );
+
+        let policy = BookingPolicy {
+            min_lead_time: secs(-1),
+            ..Default::default()
+        };
+        assert_eq!(
+            policy.validate().unwrap_err(),
+            Error::InvalidPolicy("min_lead_time is negative".to_string())
+        );
+
+        let policy = BookingPolicy {
+            max_duration: Some(prost_types::Duration {
+                seconds: 3600,
+                nanos: 500,
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            policy.validate().unwrap_err(),
+            Error::InvalidPolicy("max_duration is not in whole seconds".to_string())
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use crate::{AuthConfig, Error, ListenResponse, Reservation, ReservationChange, WaitlistEntry};
+use tonic::metadata::MetadataMap;
+
+const AUTHORIZATION_KEY: &str = "authorization";
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// who makes a request, authenticated by the api token in the `authorization` request metadata
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Caller {
+    pub user_id: String,
+    pub admin: bool,
+}
+
+impl Caller {
+    pub fn new(user_id: impl Into<String>, admin: bool) -> Self {
+        Self {
+            user_id: user_id.into(),
+            admin,
+        }
+    }
+
+    /// whether the caller could see the owner and note of the reservation
+    pub fn can_see(&self, rsvp: &Reservation) -> bool {
+        self.owns(&rsvp.user_id)
+    }
+
+    /// whether the caller is the given user or an admin
+    pub fn owns(&self, user_id: &str) -> bool {
+        self.admin || (!self.user_id.is_empty() && self.user_id == user_id)
+    }
+
+    /// fail unless the caller is an admin, for the operations on resources and pools
+    pub fn check_admin(&self) -> Result<(), Error> {
+        if self.admin {
+            Ok(())
+        } else {
+            Err(Error::PermissionDenied("admin only".to_string()))
+        }
+    }
+
+    /// fail unless the caller owns the reservation or is an admin, for changing it
+    pub fn check_owner(&self, rsvp: &Reservation) -> Result<(), Error> {
+        if self.can_see(rsvp) {
+            Ok(())
+        } else {
+            Err(Error::PermissionDenied(format!(
+                "reservation {} is not yours",
+                rsvp.id
+            )))
+        }
+    }
+}
+
+impl Caller {
+    /// the caller of the `Bearer` token in the metadata, a request without a token is anonymous
+    pub fn authenticate(metadata: &MetadataMap, auth: &AuthConfig) -> Result<Self, Error> {
+        let value = match metadata.get(AUTHORIZATION_KEY) {
+            Some(value) => value,
+            None => return Ok(Self::default()),
+        };
+        let token = value
+            .to_str()
+            .ok()
+            .and_then(|v| v.strip_prefix(BEARER_PREFIX))
+            .ok_or(Error::Unauthenticated)?;
+        auth.tokens
+            .iter()
+            .find(|t| t.token == token)
+            .map(|t| Self::new(&t.user_id, t.admin))
+            .ok_or(Error::Unauthenticated)
+    }
+}
+
+impl Reservation {
+    /// hide the owner and note if the caller can't see them
+    pub fn redact_for(mut self, caller: &Caller) -> Self {
+        if !caller.can_see(&self) {
+            self.user_id.clear();
+            self.note.clear();
+        }
+        self
+    }
+}
+
+impl WaitlistEntry {
+    /// hide the owner and note if the caller can't see them
+    pub fn redact_for(mut self, caller: &Caller) -> Self {
+        if !caller.owns(&self.user_id) {
+            self.user_id.clear();
+            self.note.clear();
+        }
+        self
+    }
+}
+
+impl ReservationChange {
+    /// hide the owner and note of both sides the caller can't see, and who made the change unless
+    /// it is the caller or the caller is an admin
+    pub fn redact_for(mut self, caller: &Caller) -> Self {
+        self.old = self.old.map(|rsvp| rsvp.redact_for(caller));
+        self.new = self.new.map(|rsvp| rsvp.redact_for(caller));
+        if !caller.owns(&self.actor) {
+            self.actor.clear();
+        }
+        self
+    }
+}
+
+impl ListenResponse {
+    /// same as the redaction of a `ReservationChange`
+    pub fn redact_for(mut self, caller: &Caller) -> Self {
+        self.reservation = self.reservation.map(|rsvp| rsvp.redact_for(caller));
+        self.old = self.old.map(|rsvp| rsvp.redact_for(caller));
+        if !caller.owns(&self.actor) {
+            self.actor.clear();
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ApiToken;
+
+    #[test]
+    fn caller_should_be_authenticated_by_token() {
+        let auth = AuthConfig {
+            tokens: vec![ApiToken {
+                token: "alice-token".to_string(),
+                user_id: "aliceid".to_string(),
+                admin: true,
+            }],
+        };
+        let mut metadata = MetadataMap::new();
+        assert_eq!(
+            Caller::authenticate(&metadata, &auth).unwrap(),
+            Caller::default()
+        );
+        // the user id and role can't be asserted by the caller
+        metadata.insert("x-user-id", "bobid".parse().unwrap());
+        metadata.insert("x-user-role", "admin".parse().unwrap());
+        assert_eq!(
+            Caller::authenticate(&metadata, &auth).unwrap(),
+            Caller::default()
+        );
+        metadata.insert(AUTHORIZATION_KEY, "Bearer alice-token".parse().unwrap());
+        assert_eq!(
+            Caller::authenticate(&metadata, &auth).unwrap(),
+            Caller::new("aliceid", true)
+        );
+        metadata.insert(AUTHORIZATION_KEY, "Bearer bob-token".parse().unwrap());
+        assert_eq!(
+            Caller::authenticate(&metadata, &auth).unwrap_err(),
+            Error::Unauthenticated
+        );
+        metadata.insert(AUTHORIZATION_KEY, "alice-token".parse().unwrap());
+        assert_eq!(
+            Caller::authenticate(&metadata, &auth).unwrap_err(),
+            Error::Unauthenticated
+        );
+    }
+
+    #[test]
+    fn reservation_should_be_redacted_for_others() {
+        let rsvp = Reservation::new_pending(
+            "aliceid",
+            "room-1",
+            "2023-01-10T10:00:00-0700".parse().unwrap(),
+            "2023-01-10T11:00:00-0700".parse().unwrap(),
+            "interview",
+        );
+        let owner = rsvp.clone().redact_for(&Caller::new("aliceid", false));
+        assert_eq!(owner, rsvp);
+        let admin = rsvp.clone().redact_for(&Caller::new("bobid", true));
+        assert_eq!(admin, rsvp);
+        let other = rsvp.clone().redact_for(&Caller::new("bobid", false));
+        assert_eq!(other.user_id, "");
+        assert_eq!(other.note, "");
+        assert_eq!(other.status, rsvp.status);
+        // an anonymous caller sees no owner
+        let anonymous = rsvp.clone().redact_for(&Caller::default());
+        assert_eq!(anonymous.user_id, "");
+    }
+
+    #[test]
+    fn caller_should_be_checked_for_admin_and_owner() {
+        let rsvp = Reservation {
+            id: 1,
+            ..Reservation::new_pending(
+                "aliceid",
+                "room-1",
+                "2023-01-10T10:00:00-0700".parse().unwrap(),
+                "2023-01-10T11:00:00-0700".parse().unwrap(),
+                "interview",
+            )
+        };
+        assert!(Caller::new("aliceid", false).check_owner(&rsvp).is_ok());
+        assert!(Caller::new("bobid", true).check_owner(&rsvp).is_ok());
+        assert_eq!(
+            Caller::new("bobid", false).check_owner(&rsvp).unwrap_err(),
+            Error::PermissionDenied("reservation 1 is not yours".to_string())
+        );
+        assert!(Caller::default().check_owner(&rsvp).is_err());
+        assert!(Caller::new("bobid", true).check_admin().is_ok());
+        assert!(Caller::new("aliceid", false).check_admin().is_err());
+    }
+
+    #[test]
+    fn change_should_be_redacted_for_others() {
+        let rsvp = Reservation::new_pending(
+            "aliceid",
+            "room-1",
+            "2023-01-10T10:00:00-0700".parse().unwrap(),
+            "2023-01-10T11:00:00-0700".parse().unwrap(),
+            "interview",
+        );
+        let change = ReservationChange {
+            old: Some(rsvp.clone()),
+            new: Some(Reservation {
+                user_id: "bobid".to_string(),
+                ..rsvp.clone()
+            }),
+            actor: "carolid".to_string(),
+            ..Default::default()
+        };
+        let admin = change.clone().redact_for(&Caller::new("davidid", true));
+        assert_eq!(admin, change);
+        // alice sees her side only, bob only his
+        let alice = change.clone().redact_for(&Caller::new("aliceid", false));
+        assert_eq!(alice.old, Some(rsvp.clone()));
+        assert_eq!(alice.new.unwrap().user_id, "");
+        assert_eq!(alice.actor, "");
+        let bob = change.clone().redact_for(&Caller::new("bobid", false));
+        assert_eq!(bob.old.unwrap().note, "");
+        assert_eq!(bob.new.unwrap().user_id, "bobid");
+        let carol = change.clone().redact_for(&Caller::new("carolid", false));
+        assert_eq!(carol.actor, "carolid");
+        assert_eq!(carol.old.unwrap().user_id, "");
+    }
+}
@@ -8,6 +8,8 @@ use crate::Error;
 pub struct Config {
     pub db: DbConfig,
     pub server: ServerConfig,
+    #[serde(default)]
+    pub auth: AuthConfig,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -28,7 +30,34 @@ fn default_pool_size() -> u32 {
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
+    /// interval in seconds to release the expired holds, must be positive
+    #[serde(default = "default_reap_interval")]
+    pub reap_interval: u64,
+    /// how long in seconds the response of a mutation is kept for its idempotency key
+    #[serde(default = "default_idempotency_ttl")]
+    pub idempotency_ttl: u64,
 }
+fn default_reap_interval() -> u64 {
+    30
+}
+fn default_idempotency_ttl() -> u64 {
+    24 * 3600
+}
+/// the api tokens a caller could authenticate with, a request without a token is anonymous
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthConfig {
+    #[serde(default)]
+    pub tokens: Vec<ApiToken>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub token: String,
+    pub user_id: String,
+    #[serde(default)]
+    pub admin: bool,
+}
+
 impl ServerConfig {
     pub fn url(&self, https: bool) -> String {
         if https {
@@ -44,6 +73,10 @@ impl Config {
         let config = fs::read_to_string(filename).map_err(|_| Error::ConfigReadError)?;
 
         let config: Config = serde_yaml::from_str(&config).map_err(|_| Error::ConfigParseError)?;
+        // the hold reaper can't tick every 0 seconds
+        if config.server.reap_interval == 0 {
+            return Err(Error::ConfigParseError);
+        }
         Ok(config)
     }
 }
@@ -85,8 +118,17 @@ mod tests {
                 server: ServerConfig {
                     host: "0.0.0.0".to_string(),
                     port: 50001,
+                    reap_interval: 30,
+                    idempotency_ttl: 86400,
                 },
+                auth: AuthConfig::default(),
             }
         )
     }
+
+    #[test]
+    fn zero_reap_interval_should_fail() {
+        let err = Config::load("fixtures/config_zero_reap_interval.yml").unwrap_err();
+        assert!(matches!(err, Error::ConfigParseError));
+    }
 }
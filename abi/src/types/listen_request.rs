@@ -0,0 +1,191 @@
+use crate::{
+    AckRequest, Error, ListenRequest, ListenResponse, Reservation, ReservationUpdateType, Validator,
+};
+
+// server_read_cursor.server_id is a varchar(64)
+const MAX_CONSUMER_ID_LEN: usize = 64;
+
+impl ListenRequest {
+    pub fn new(consumer_id: impl Into<String>, after_change_id: Option<i64>) -> Self {
+        Self {
+            consumer_id: consumer_id.into(),
+            after_change_id,
+            ..Default::default()
+        }
+    }
+
+    /// check if the change should be sent to the listener
+    pub fn matches(&self, change: &ListenResponse) -> bool {
+        if !self.ops.is_empty() && !self.ops.contains(&change.op) {
+            return false;
+        }
+        // a reservation moved out of the resources or users listened to is still sent
+        let matches = |rsvp: &Reservation| {
+            rsvp.resource_id.starts_with(&self.resource_id)
+                && (self.user_id.is_empty() || rsvp.user_id == self.user_id)
+        };
+        match (change.reservation.as_ref(), change.old.as_ref()) {
+            (None, None) => self.resource_id.is_empty() && self.user_id.is_empty(),
+            (new, old) => new.into_iter().chain(old).any(matches),
+        }
+    }
+}
+
+impl Validator for ListenRequest {
+    fn validate(&self) -> Result<(), Error> {
+        if self.consumer_id.len() > MAX_CONSUMER_ID_LEN {
+            return Err(Error::InvalidConsumerId(self.consumer_id.clone()));
+        }
+        if let Some(id) = self.after_change_id {
+            if id < 0 {
+                return Err(Error::InvalidCursor(id));
+            }
+        }
+        for op in &self.ops {
+            ReservationUpdateType::from_i32(*op).ok_or(Error::InvalidUpdateType(*op))?;
+        }
+        Ok(())
+    }
+}
+
+impl Validator for AckRequest {
+    fn validate(&self) -> Result<(), Error> {
+        if self.consumer_id.is_empty() || self.consumer_id.len() > MAX_CONSUMER_ID_LEN {
+            return Err(Error::InvalidConsumerId(self.consumer_id.clone()));
+        }
+        if self.change_id <= 0 {
+            return Err(Error::InvalidCursor(self.change_id));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listen_request_should_validate() {
+        assert!(ListenRequest::default().validate().is_ok());
+        assert!(ListenRequest::new("billing", Some(0)).validate().is_ok());
+
+        let err = ListenRequest::new("billing", Some(-1))
+            .validate()
+            .unwrap_err();
+        assert_eq!(err, Error::InvalidCursor(-1));
+
+        let consumer_id = "c".repeat(65);
+        let err = ListenRequest::new(consumer_id.clone(), None)
+            .validate()
+            .unwrap_err();
+        assert_eq!(err, Error::InvalidConsumerId(consumer_id));
+
+        let request = ListenRequest {
+            ops: vec![ReservationUpdateType::Create as i32, 10],
+            ..Default::default()
+        };
+        assert_eq!(
+            request.validate().unwrap_err(),
+            Error::InvalidUpdateType(10)
+        );
+    }
+
+    #[test]
+    fn ack_request_should_validate() {
+        let request = AckRequest {
+            consumer_id: "billing".into(),
+            change_id: 1,
+        };
+        assert!(request.validate().is_ok());
+
+        let err = AckRequest {
+            consumer_id: "".into(),
+            ..request.clone()
+        }
+        .validate()
+        .unwrap_err();
+        assert_eq!(err, Error::InvalidConsumerId("".into()));
+
+        let err = AckRequest {
+            change_id: 0,
+            ..request
+        }
+        .validate()
+        .unwrap_err();
+        assert_eq!(err, Error::InvalidCursor(0));
+    }
+
+    #[test]
+    fn listen_request_should_match_changes() {
+        let change = ListenResponse {
+            op: ReservationUpdateType::Create as i32,
+            reservation: Some(Reservation::new_pending(
+                "aliceid",
+                "building-a-room-101",
+                "2022-12-25T15:00:00-0700".parse().unwrap(),
+                "2022-12-27T12:00:00-0700".parse().unwrap(),
+                "",
+            )),
+            change_id: 1,
+            ..Default::default()
+        };
+        assert!(ListenRequest::default().matches(&change));
+
+        let mut request = ListenRequest {
+            resource_id: "building-a-".into(),
+            ..Default::default()
+        };
+        assert!(request.matches(&change));
+        request.resource_id = "building-b-".into();
+        assert!(!request.matches(&change));
+
+        let mut request = ListenRequest {
+            user_id: "aliceid".into(),
+            ..Default::default()
+        };
+        assert!(request.matches(&change));
+        request.user_id = "bobid".into();
+        assert!(!request.matches(&change));
+
+        let mut request = ListenRequest {
+            ops: vec![
+                ReservationUpdateType::Create as i32,
+                ReservationUpdateType::Delete as i32,
+            ],
+            ..Default::default()
+        };
+        assert!(request.matches(&change));
+        request.ops = vec![ReservationUpdateType::Update as i32];
+        assert!(!request.matches(&change));
+
+        // moved to another building and transferred to bob
+        let old = change.reservation.clone();
+        let mut moved = old.clone().unwrap();
+        moved.resource_id = "building-b-room-201".into();
+        moved.user_id = "bobid".into();
+        let change = ListenResponse {
+            op: ReservationUpdateType::Update as i32,
+            reservation: Some(moved),
+            old,
+            ..change
+        };
+        for (resource_id, user_id) in [
+            ("building-a-", ""),
+            ("building-b-", ""),
+            ("", "aliceid"),
+            ("", "bobid"),
+        ] {
+            let request = ListenRequest {
+                resource_id: resource_id.into(),
+                user_id: user_id.into(),
+                ..Default::default()
+            };
+            assert!(request.matches(&change));
+        }
+        let request = ListenRequest {
+            resource_id: "building-c-".into(),
+            ..Default::default()
+        };
+        assert!(!request.matches(&change));
+    }
+}
@@ -0,0 +1,35 @@
+use crate::{
+    convert_to_timestamp, ListenResponse, Reservation, ReservationUpdateType, RsvpUpdateType,
+};
+use sqlx::{postgres::PgRow, FromRow, Row};
+
+// the row is a `rsvp.reservation_changes` record joined with the reservation decoded from its `new`
+// or `old` JSONB column, as told by `side`. A change with both is read as two rows, new then old,
+// merged by `merge`
+impl FromRow<'_, PgRow> for ListenResponse {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let op: RsvpUpdateType = row.try_get("op")?;
+        let side: &str = row.try_get("side")?;
+        let reservation = Reservation::from_row(row)?;
+        let old = (side == "old").then(|| reservation.clone());
+
+        Ok(Self {
+            op: ReservationUpdateType::from(op) as i32,
+            // the reservation before a deletion if there is none after it
+            reservation: Some(reservation),
+            change_id: row.try_get("change_id")?,
+            changed_at: Some(convert_to_timestamp(row.try_get("created_at")?)),
+            actor: row
+                .try_get::<Option<String>, _>("actor")?
+                .unwrap_or_default(),
+            old,
+        })
+    }
+}
+
+impl ListenResponse {
+    /// merge the row read for the old side of the same change into the one for its new side
+    pub fn merge(&mut self, old: ListenResponse) {
+        self.old = old.old;
+    }
+}
@@ -1,17 +1,31 @@
+mod availability;
+mod booking_policy;
+mod caller;
 mod config;
+mod listen_request;
+mod listen_response;
 mod request;
 mod reservation;
+mod reservation_change;
 mod reservation_filter;
 mod reservation_query;
 mod reservation_status;
+mod resource;
+mod resource_pool;
+mod waitlist_entry;
 
-use std::ops::Bound;
+use std::{
+    fmt::{Debug, Display},
+    ops::Bound,
+};
 
 use crate::{convert_to_utc_time, Error};
 use chrono::{DateTime, Utc};
 use prost_types::Timestamp;
-use sqlx::postgres::types::PgRange;
+use sqlx::{postgres::types::PgRange, Encode, Postgres, QueryBuilder, Type};
 
+pub use availability::*;
+pub use caller::*;
 pub use config::*;
 
 pub fn validate_range(start: Option<&Timestamp>, end: Option<&Timestamp>) -> Result<(), Error> {
@@ -40,6 +54,85 @@ pub fn get_timespan(start: Option<&Timestamp>, end: Option<&Timestamp>) -> PgRan
     }
 }
 
+/// a query builder keeping the arguments it binds in order, so that they could be checked with the SQL
+pub struct BoundQuery<'a> {
+    builder: QueryBuilder<'a, Postgres>,
+    args: Vec<String>,
+}
+
+impl<'a> BoundQuery<'a> {
+    pub fn new(init: impl Into<String>) -> Self {
+        Self {
+            builder: QueryBuilder::new(init),
+            args: vec![],
+        }
+    }
+
+    pub fn push(&mut self, sql: impl Display) -> &mut Self {
+        self.builder.push(sql);
+        self
+    }
+
+    pub fn push_bind<T>(&mut self, value: T) -> &mut Self
+    where
+        T: 'a + Encode<'a, Postgres> + Send + Type<Postgres> + Debug,
+    {
+        self.args.push(format!("{:?}", value));
+        self.builder.push_bind(value);
+        self
+    }
+
+    pub fn sql(&self) -> &str {
+        self.builder.sql()
+    }
+
+    /// the bound arguments in the order of their placeholders, formatted with `Debug`
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    pub fn into_builder(self) -> QueryBuilder<'a, Postgres> {
+        self.builder
+    }
+}
+
+/// push the table to read reservations from, if `as_of` is set, the reservations are reconstructed
+/// from the latest change of each one made by then, those deleted by then are left out. A change is
+/// timed by the start of its transaction, and the log is only complete from `rsvp.change_log_start`
+pub(crate) fn push_reservation_source(builder: &mut BoundQuery<'_>, as_of: Option<&Timestamp>) {
+    match as_of {
+        None => builder.push("rsvp.reservations"),
+        Some(ts) => builder
+            .push(
+                "(SELECT r.* FROM (SELECT DISTINCT ON (reservation_id) new FROM rsvp.reservation_changes
+            WHERE created_at <= ",
+            )
+            .push_bind(convert_to_utc_time(ts))
+            .push(
+                " ORDER BY reservation_id, id DESC) c,
+            jsonb_populate_record(NULL::rsvp.reservations, c.new) r WHERE c.new IS NOT NULL) r",
+            ),
+    };
+}
+
+/// push the condition on the owner and resource, an empty one matches all
+pub(crate) fn push_owner_condition<'a>(
+    builder: &mut BoundQuery<'a>,
+    user_id: &'a str,
+    resource_id: &'a str,
+) {
+    match (user_id.is_empty(), resource_id.is_empty()) {
+        (true, true) => builder.push("TRUE"),
+        (true, false) => builder.push("resource_id = ").push_bind(resource_id),
+        (false, true) => builder.push("user_id = ").push_bind(user_id),
+        (false, false) => builder
+            .push("user_id = ")
+            .push_bind(user_id)
+            .push(" AND resource_id = ")
+            .push_bind(resource_id),
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -48,7 +141,6 @@ mod tests {
     #[test]
     fn test_i64_default() {
         let ss: i64 = Default::default();
-        println!("{}", ss);
         assert_eq!(ss, 0);
     }
     #[test]
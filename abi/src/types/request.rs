@@ -1,7 +1,11 @@
 use crate::{
-    ConfirmRequest, FilterRequest, QueryRequest, Reservation, ReservationFilter, ReservationQuery,
-    ReserveRequest, UpdateRequest,
+    convert_to_duration, convert_to_timestamp, validate_range, BatchReserveRequest, CancelRequest,
+    ConfirmRequest, Error, FilterRequest, QueryRequest, RescheduleRequest, Reservation,
+    ReservationFilter, ReservationQuery, ReserveAnyRequest, ReserveRequest, ReserveSeriesRequest,
+    UpdateRequest, Validator,
 };
+use chrono::{DateTime, Duration, Utc};
+use prost_types::{FieldMask, Timestamp};
 
 macro_rules! impl_new {
     ($name:ident,$field:ident,$type:ty) => {
@@ -16,18 +20,112 @@ macro_rules! impl_new {
     ($name:ident) => {
         impl $name {
             pub fn new(id: i64) -> Self {
-                Self { id }
+                Self {
+                    id,
+                    ..Default::default()
+                }
             }
         }
     };
 }
 
-impl_new!(ReserveRequest, reservation, Reservation);
+macro_rules! impl_hold_expires_at {
+    ($($name:ident),*) => {
+        $(
+            impl $name {
+                /// expiry time of the hold made at `now`, none if no `hold_ttl` is given
+                pub fn hold_expires_at(&self, now: DateTime<Utc>) -> Result<Option<Timestamp>, Error> {
+                    hold_expires_at(self.hold_ttl.as_ref(), now)
+                }
+            }
+        )*
+    };
+}
+
 impl_new!(ConfirmRequest);
+impl_new!(CancelRequest);
 impl_new!(QueryRequest, query, ReservationQuery);
 impl_new!(FilterRequest, filter, ReservationFilter);
+impl ReserveRequest {
+    pub fn new(reservation: Reservation) -> Self {
+        Self {
+            reservation: Some(reservation),
+            hold_ttl: None,
+            idempotency_key: String::new(),
+        }
+    }
+
+    /// hold the reservation for the given ttl, it would be released if not confirmed in time
+    pub fn with_hold_ttl(mut self, ttl: prost_types::Duration) -> Self {
+        self.hold_ttl = Some(ttl);
+        self
+    }
+}
+
+impl_hold_expires_at!(
+    ReserveRequest,
+    BatchReserveRequest,
+    ReserveSeriesRequest,
+    ReserveAnyRequest
+);
+
+fn hold_expires_at(
+    ttl: Option<&prost_types::Duration>,
+    now: DateTime<Utc>,
+) -> Result<Option<Timestamp>, Error> {
+    match ttl {
+        None => Ok(None),
+        Some(ttl) => {
+            let ttl = convert_to_duration(ttl);
+            if ttl <= Duration::zero() {
+                return Err(Error::InvalidHoldTtl(ttl.num_seconds()));
+            }
+            Ok(Some(convert_to_timestamp(now + ttl)))
+        }
+    }
+}
+
 impl UpdateRequest {
-    pub fn new(id: i64, note: String) -> Self {
-        Self { id, note }
+    /// change the fields of the `paths` to their values in `reservation`
+    pub fn new<'a>(
+        id: i64,
+        reservation: Reservation,
+        paths: impl IntoIterator<Item = &'a str>,
+    ) -> Self {
+        Self {
+            id,
+            expected_version: None,
+            reservation: Some(reservation),
+            update_mask: Some(FieldMask {
+                paths: paths.into_iter().map(|p| p.to_string()).collect(),
+            }),
+        }
+    }
+
+    /// paths of the fields to change
+    pub fn paths(&self) -> &[String] {
+        self.update_mask
+            .as_ref()
+            .map(|mask| mask.paths.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+impl RescheduleRequest {
+    pub fn new(id: i64, start: Timestamp, end: Timestamp) -> Self {
+        Self {
+            id,
+            start: Some(start),
+            end: Some(end),
+            expected_version: None,
+            idempotency_key: String::new(),
+        }
+    }
+}
+
+impl Validator for RescheduleRequest {
+    fn validate(&self) -> Result<(), Error> {
+        self.id.validate()?;
+        validate_range(self.start.as_ref(), self.end.as_ref())
     }
 }
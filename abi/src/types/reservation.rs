@@ -26,12 +26,55 @@ impl Reservation {
             end: Some(convert_to_timestamp(end.with_timezone(&Utc))),
             note: note.into(),
             status: ReservationStatus::Pending as i32,
+            series_id: 0,
+            expires_at: None,
+            version: 0,
         }
     }
 
     pub fn get_time_span(&self) -> PgRange<DateTime<Utc>> {
         get_timespan(self.start.as_ref(), self.end.as_ref())
     }
+
+    /// validate user, resource and time window, regardless of the status
+    pub fn validate_window(&self) -> Result<(), Error> {
+        if self.user_id.is_empty() {
+            return Err(Error::InvalidUserId(self.user_id.clone()));
+        }
+        if self.resource_id.is_empty() {
+            return Err(Error::InvalidResourceId(self.resource_id.clone()));
+        }
+
+        validate_range(self.start.as_ref(), self.end.as_ref())
+    }
+
+    /// copy the fields of the `paths` from `patch`, the changed reservation is validated
+    pub fn apply_update(&mut self, patch: &Reservation, paths: &[String]) -> Result<(), Error> {
+        if paths.is_empty() {
+            return Err(Error::InvalidUpdateMask(String::new()));
+        }
+        for path in paths {
+            match path.as_str() {
+                "note" => self.note = patch.note.clone(),
+                "user_id" => self.user_id = patch.user_id.clone(),
+                "resource_id" => self.resource_id = patch.resource_id.clone(),
+                "start" => self.start = patch.start.clone(),
+                "end" => self.end = patch.end.clone(),
+                _ => return Err(Error::InvalidUpdateMask(path.clone())),
+            }
+        }
+        self.validate_window()
+    }
+
+    /// fail if the reservation is not at the expected version, if one is given
+    pub fn check_version(&self, expected: Option<i64>) -> Result<(), Error> {
+        match expected {
+            Some(version) if version != self.version => {
+                Err(Error::VersionMismatch(self.id, version, self.version))
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 impl Id for Reservation {
@@ -41,15 +84,17 @@ impl Id for Reservation {
 }
 impl Validator for Reservation {
     fn validate(&self) -> Result<(), Error> {
-        if self.user_id.is_empty() {
-            return Err(Error::InvalidUserId(self.user_id.clone()));
+        self.validate_window()?;
+        // blocks are made by operators only, and only a pending reservation is a hold that could expire
+        match ReservationStatus::from_i32(self.status) {
+            Some(ReservationStatus::Pending) => Ok(()),
+            Some(ReservationStatus::Unknown | ReservationStatus::Confirmed)
+                if self.expires_at.is_none() =>
+            {
+                Ok(())
+            }
+            _ => Err(Error::InvalidStatus(self.status)),
         }
-        if self.resource_id.is_empty() {
-            return Err(Error::InvalidResourceId(self.resource_id.clone()));
-        }
-
-        validate_range(self.start.as_ref(), self.end.as_ref())?;
-        Ok(())
     }
 }
 
@@ -75,13 +120,19 @@ impl FromRow<'_, PgRow> for Reservation {
             end: Some(convert_to_timestamp(end)),
             note: row.get("note"),
             status: ReservationStatus::from(status) as i32,
+            series_id: row.get::<Option<i64>, _>("series_id").unwrap_or_default(),
+            expires_at: row
+                .get::<Option<DateTime<Utc>>, _>("expires_at")
+                .map(convert_to_timestamp),
+            // changes recorded before versioning have no version
+            version: row.get::<Option<i64>, _>("version").unwrap_or_default(),
         })
     }
 }
 
 pub struct NaiveRange<T> {
-    start: Option<T>,
-    end: Option<T>,
+    pub(crate) start: Option<T>,
+    pub(crate) end: Option<T>,
 }
 
 impl<T> From<PgRange<T>> for NaiveRange<T> {
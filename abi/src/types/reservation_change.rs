@@ -0,0 +1,83 @@
+use crate::{
+    convert_to_timestamp, Reservation, ReservationChange, ReservationUpdateType, RsvpUpdateType,
+};
+use sqlx::{postgres::PgRow, FromRow, Row};
+
+// the row is a `rsvp.reservation_changes` record joined with the reservation decoded from one of
+// its JSONB columns, `side` tells which one, so a change with both is read from two rows
+impl FromRow<'_, PgRow> for ReservationChange {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let op: RsvpUpdateType = row.try_get("op")?;
+        let side: &str = row.try_get("side")?;
+        let reservation = Reservation::from_row(row)?;
+
+        let mut change = Self {
+            change_id: row.try_get("change_id")?,
+            op: ReservationUpdateType::from(op) as i32,
+            changed_at: Some(convert_to_timestamp(row.try_get("created_at")?)),
+            old: None,
+            new: None,
+            actor: row
+                .try_get::<Option<String>, _>("actor")?
+                .unwrap_or_default(),
+        };
+        match side {
+            "old" => change.old = Some(reservation),
+            _ => change.new = Some(reservation),
+        }
+        Ok(change)
+    }
+}
+
+impl ReservationChange {
+    /// merge the rows read for the same change, the changes stay in the order of the rows
+    pub fn merge(changes: Vec<ReservationChange>) -> Vec<ReservationChange> {
+        let mut merged: Vec<ReservationChange> = Vec::with_capacity(changes.len());
+        for change in changes {
+            match merged.last_mut() {
+                Some(last) if last.change_id == change.change_id => {
+                    last.old = last.old.take().or(change.old);
+                    last.new = last.new.take().or(change.new);
+                }
+                _ => merged.push(change),
+            }
+        }
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(change_id: i64, old: Option<i64>, new: Option<i64>) -> ReservationChange {
+        let rsvp = |id| Reservation {
+            id,
+            ..Default::default()
+        };
+        ReservationChange {
+            change_id,
+            old: old.map(rsvp),
+            new: new.map(rsvp),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn merge_should_join_both_sides_of_a_change() {
+        let changes = vec![
+            change(1, None, Some(1)),
+            change(2, Some(1), None),
+            change(2, None, Some(1)),
+            change(3, Some(1), None),
+        ];
+        assert_eq!(
+            ReservationChange::merge(changes),
+            vec![
+                change(1, None, Some(1)),
+                change(2, Some(1), Some(1)),
+                change(3, Some(1), None),
+            ]
+        );
+    }
+}
@@ -1,9 +1,10 @@
 use std::collections::VecDeque;
 
+use super::{push_owner_condition, push_reservation_source, BoundQuery};
 use crate::{
     pager::{Id, PageInfo, Pager, Paginator},
     Error, FilterPager, Normalizer, ReservationFilter, ReservationFilterBuilder, ReservationStatus,
-    ToSql, Validator,
+    ToQuery, Validator,
 };
 
 impl ReservationFilterBuilder {
@@ -83,6 +84,7 @@ impl ReservationFilter {
             status: self.status,
             resource_id: self.resource_id.clone(),
             user_id: self.user_id.clone(),
+            as_of: self.as_of.clone(),
         })
     }
     fn page_info(&self) -> PageInfo {
@@ -94,35 +96,29 @@ impl ReservationFilter {
     }
 }
 
-impl ToSql for ReservationFilter {
-    fn to_sql(&self) -> String {
+impl ToQuery for ReservationFilter {
+    fn to_query(&self) -> BoundQuery<'_> {
         let middle_plus = i64::from(self.cursor.is_some());
         let limit = self.page_size + 1 + middle_plus;
 
         let status = self.get_status();
 
-        let cursor_cond = if self.desc {
-            format!("id < {}", self.get_cursor())
-        } else {
-            format!("id > {}", self.get_cursor())
-        };
-
-        let user_cursor_cond = match (self.user_id.is_empty(), self.resource_id.is_empty()) {
-            (true, true) => "TRUE".into(),
-            (true, false) => format!("resource_id = '{}'", self.resource_id),
-            (false, true) => format!("user_id = '{}'", self.user_id),
-            (false, false) => format!(
-                "user_id = '{}' AND resource_id = '{}'",
-                self.user_id, self.resource_id
-            ),
-        };
+        let mut builder = BoundQuery::new("SELECT * FROM ");
+        push_reservation_source(&mut builder, self.as_of.as_ref());
+        builder
+            .push(" WHERE status = ")
+            .push_bind(status.to_string())
+            .push("::rsvp.reservation_status AND id ")
+            .push(if self.desc { "< " } else { "> " })
+            .push_bind(self.get_cursor())
+            .push(" AND ");
+        push_owner_condition(&mut builder, &self.user_id, &self.resource_id);
 
         let direction = if self.desc { "DESC" } else { "ASC" };
-
-        format!(
-            "SELECT * FROM rsvp.reservations WHERE status = '{}'::rsvp.reservation_status AND {} AND {} ORDER BY id {} LIMIT {}",
-            status, cursor_cond, user_cursor_cond, direction, limit
-        )
+        builder
+            .push(format!(" ORDER BY id {} LIMIT ", direction))
+            .push_bind(limit);
+        builder
     }
 }
 
@@ -137,9 +133,20 @@ mod tests {
             .user_id("tyrchen")
             .build()
             .unwrap();
+        let query = filter.to_query();
+        assert_eq!(query.sql(), "SELECT * FROM rsvp.reservations WHERE status = $1::rsvp.reservation_status AND id > $2 AND user_id = $3 ORDER BY id ASC LIMIT $4");
+        assert_eq!(query.args(), ["\"pending\"", "0", "\"tyrchen\"", "11"]);
 
-        let sql = filter.to_sql();
-
-        assert_eq!(sql, "SELECT * FROM rsvp.reservations WHERE status = 'pending'::rsvp.reservation_status AND id > 0 AND user_id = 'tyrchen' ORDER BY id ASC LIMIT 11")
+        let filter = ReservationFilterBuilder::default()
+            .resource_id("room'1")
+            .desc(true)
+            .build()
+            .unwrap();
+        let query = filter.to_query();
+        assert_eq!(query.sql(), "SELECT * FROM rsvp.reservations WHERE status = $1::rsvp.reservation_status AND id < $2 AND resource_id = $3 ORDER BY id DESC LIMIT $4");
+        assert_eq!(
+            query.args(),
+            ["\"pending\"", "9223372036854775807", "\"room'1\"", "11"]
+        );
     }
 }
@@ -1,8 +1,8 @@
+use super::{push_owner_condition, push_reservation_source, BoundQuery};
 use crate::{
     convert_to_utc_time, Error, Normalizer, ReservationQuery, ReservationQueryBuilder,
-    ReservationStatus, ToSql, Validator,
+    ReservationStatus, ToQuery, Validator,
 };
-use prost_types::Timestamp;
 
 impl ReservationQueryBuilder {
     pub fn build(&self) -> Result<ReservationQuery, Error> {
@@ -39,69 +39,66 @@ impl Normalizer for ReservationQuery {
         }
     }
 }
-impl ToSql for ReservationQuery {
-    fn to_sql(&self) -> String {
+impl ToQuery for ReservationQuery {
+    fn to_query(&self) -> BoundQuery<'_> {
         let status = self.get_status();
-        let timespan = format!(
-            "tstzrange('{}', '{}')",
-            get_time_string(self.start.as_ref(), true),
-            get_time_string(self.end.as_ref(), false)
-        );
-
-        let condition = match (self.user_id.is_empty(), self.resource_id.is_empty()) {
-            (true, true) => "TRUE".into(),
-            (true, false) => format!("resource_id = '{}'", self.resource_id),
-            (false, true) => format!("user_id = '{}'", self.user_id),
-            (false, false) => format!(
-                "user_id = '{}' AND resource_id = '{}'",
-                self.user_id, self.resource_id
-            ),
-        };
+        let mut builder = BoundQuery::new("SELECT * FROM ");
+        push_reservation_source(&mut builder, self.as_of.as_ref());
+        // a missing bound is NULL, which leaves the range unbounded on that side
+        builder
+            .push(" WHERE tstzrange(")
+            .push_bind(self.start.as_ref().map(convert_to_utc_time))
+            .push(", ")
+            .push_bind(self.end.as_ref().map(convert_to_utc_time))
+            .push(") @> timespan AND status = ")
+            .push_bind(status.to_string())
+            .push("::rsvp.reservation_status\n            AND ");
+        push_owner_condition(&mut builder, &self.user_id, &self.resource_id);
 
         let direction = if self.desc { "DESC" } else { "ASC" };
-
-        format!(
-            "SELECT * FROM rsvp.reservations WHERE {} @> timespan AND status = '{}'::rsvp.reservation_status
-            AND {} ORDER BY lower(timespan) {} ",
-            timespan, status,condition , direction
-        )
-    }
-}
-
-fn get_time_string(ts: Option<&Timestamp>, start: bool) -> String {
-    match ts {
-        Some(ts) => convert_to_utc_time(ts).to_rfc3339(),
-        None => (if start { "-infinity" } else { "infinity" }).into(),
+        builder.push(format!(" ORDER BY lower(timespan) {} ", direction));
+        builder
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use prost_types::Timestamp;
+
     #[test]
     fn query_should_generate_valid_sql() {
         let query = ReservationQueryBuilder::default()
             .user_id("ssk")
             .build()
             .unwrap();
-        let sql = query.to_sql();
-
+        let query = query.to_query();
         assert_eq!(
-            sql,
-            "SELECT * FROM rsvp.reservations WHERE tstzrange('-infinity', 'infinity') @> timespan AND status = 'pending'::rsvp.reservation_status
-            AND user_id = 'ssk' ORDER BY lower(timespan) ASC "
+            query.sql(),
+            "SELECT * FROM rsvp.reservations WHERE tstzrange($1, $2) @> timespan AND status = $3::rsvp.reservation_status
+            AND user_id = $4 ORDER BY lower(timespan) ASC "
         );
+        assert_eq!(query.args(), ["None", "None", "\"pending\"", "\"ssk\""]);
 
         let query = ReservationQueryBuilder::default()
             .user_id("ssk")
             .start("2021-11-01T15:00:00-0700".parse::<Timestamp>().unwrap())
             .build()
             .unwrap();
-        let sql = query.to_sql();
+        let query = query.to_query();
         assert_eq!(
-            sql,
-            "SELECT * FROM rsvp.reservations WHERE tstzrange('2021-11-01T22:00:00+00:00', 'infinity') @> timespan AND status = 'pending'::rsvp.reservation_status
-            AND user_id = 'ssk' ORDER BY lower(timespan) ASC "
+            query.sql(),
+            "SELECT * FROM rsvp.reservations WHERE tstzrange($1, $2) @> timespan AND status = $3::rsvp.reservation_status
+            AND user_id = $4 ORDER BY lower(timespan) ASC "
+        );
+        assert_eq!(
+            query.args(),
+            [
+                "Some(2021-11-01T22:00:00Z)",
+                "None",
+                "\"pending\"",
+                "\"ssk\""
+            ]
         );
 
         let query = ReservationQueryBuilder::default()
@@ -109,11 +106,70 @@ mod tests {
             .end("2021-11-01T16:00:00-0700".parse::<Timestamp>().unwrap())
             .build()
             .unwrap();
-        let sql = query.to_sql();
+        let query = query.to_query();
+        assert_eq!(
+            query.sql(),
+            "SELECT * FROM rsvp.reservations WHERE tstzrange($1, $2) @> timespan AND status = $3::rsvp.reservation_status
+            AND user_id = $4 ORDER BY lower(timespan) ASC "
+        );
+        assert_eq!(
+            query.args(),
+            [
+                "None",
+                "Some(2021-11-01T23:00:00Z)",
+                "\"pending\"",
+                "\"ssk\""
+            ]
+        );
+
+        let query = ReservationQueryBuilder::default()
+            .user_id("ssk")
+            .as_of("2021-11-01T16:00:00-0700".parse::<Timestamp>().unwrap())
+            .build()
+            .unwrap();
+        let query = query.to_query();
+        assert_eq!(
+            query.sql(),
+            "SELECT * FROM (SELECT r.* FROM (SELECT DISTINCT ON (reservation_id) new FROM rsvp.reservation_changes
+            WHERE created_at <= $1 ORDER BY reservation_id, id DESC) c,
+            jsonb_populate_record(NULL::rsvp.reservations, c.new) r WHERE c.new IS NOT NULL) r WHERE tstzrange($2, $3) @> timespan AND status = $4::rsvp.reservation_status
+            AND user_id = $5 ORDER BY lower(timespan) ASC "
+        );
+        assert_eq!(
+            query.args(),
+            [
+                "2021-11-01T23:00:00Z",
+                "None",
+                "None",
+                "\"pending\"",
+                "\"ssk\""
+            ]
+        );
+    }
+
+    #[test]
+    fn query_should_bind_values_instead_of_formatting_them() {
+        let query = ReservationQueryBuilder::default()
+            .user_id("o'brien")
+            .resource_id("room'; DROP TABLE rsvp.reservations; --")
+            .desc(true)
+            .build()
+            .unwrap();
+        let query = query.to_query();
+        assert_eq!(
+            query.sql(),
+            "SELECT * FROM rsvp.reservations WHERE tstzrange($1, $2) @> timespan AND status = $3::rsvp.reservation_status
+            AND user_id = $4 AND resource_id = $5 ORDER BY lower(timespan) DESC "
+        );
         assert_eq!(
-            sql,
-            "SELECT * FROM rsvp.reservations WHERE tstzrange('-infinity', '2021-11-01T23:00:00+00:00') @> timespan AND status = 'pending'::rsvp.reservation_status
-            AND user_id = 'ssk' ORDER BY lower(timespan) ASC "
+            query.args(),
+            [
+                "None",
+                "None",
+                "\"pending\"",
+                "\"o'brien\"",
+                "\"room'; DROP TABLE rsvp.reservations; --\""
+            ]
         );
     }
 }
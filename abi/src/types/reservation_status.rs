@@ -1,6 +1,6 @@
 use std::fmt;
 
-use crate::ReservationStatus;
+use crate::{Error, ReservationStatus};
 
 impl fmt::Display for ReservationStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -8,7 +8,61 @@ impl fmt::Display for ReservationStatus {
             ReservationStatus::Pending => write!(f, "pending"),
             ReservationStatus::Blocked => write!(f, "blocked"),
             ReservationStatus::Confirmed => write!(f, "confirmed"),
+            ReservationStatus::Cancelled => write!(f, "cancelled"),
+            ReservationStatus::CheckedIn => write!(f, "checked_in"),
             ReservationStatus::Unknown => write!(f, "unknown"),
         }
     }
 }
+
+impl ReservationStatus {
+    /// whether a reservation could move from this status to `to`: pending to confirmed, confirmed
+    /// to checked in, and any status but cancelled to cancelled. A block is only lifted by
+    /// `unblock_resource`, so it can't move at all
+    pub fn can_transition_to(self, to: ReservationStatus) -> bool {
+        use ReservationStatus::*;
+        match (self, to) {
+            (Pending, Confirmed) | (Confirmed, CheckedIn) => true,
+            (Cancelled | Blocked, _) => false,
+            (_, Cancelled) => true,
+            _ => false,
+        }
+    }
+
+    /// fail with `InvalidTransition` if the move to `to` is not allowed
+    pub fn check_transition(self, to: ReservationStatus) -> Result<(), Error> {
+        if self.can_transition_to(to) {
+            Ok(())
+        } else {
+            Err(Error::InvalidTransition { from: self, to })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ReservationStatus::*;
+
+    #[test]
+    fn transitions_should_follow_the_state_machine() {
+        assert!(Pending.can_transition_to(Confirmed));
+        assert!(Confirmed.can_transition_to(CheckedIn));
+        for from in [Pending, Confirmed, CheckedIn] {
+            assert!(from.can_transition_to(Cancelled));
+        }
+        assert!(!Cancelled.can_transition_to(Cancelled));
+        assert!(!Blocked.can_transition_to(Cancelled));
+        assert!(!Pending.can_transition_to(CheckedIn));
+        assert!(!Confirmed.can_transition_to(Confirmed));
+        assert!(!Blocked.can_transition_to(Confirmed));
+        assert!(!Cancelled.can_transition_to(Pending));
+        assert_eq!(
+            Confirmed.check_transition(Pending),
+            Err(Error::InvalidTransition {
+                from: Confirmed,
+                to: Pending
+            })
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use crate::{BookingPolicy, Error, Normalizer, Resource, UpdateResourceRequest, Validator};
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use prost_types::FieldMask;
+use sqlx::{postgres::PgRow, types::Json, FromRow, Row};
+
+// reservations.resource_id is a varchar(64)
+const MAX_RESOURCE_ID_LEN: usize = 64;
+
+impl Resource {
+    pub fn new(id: impl Into<String>, name: impl Into<String>, kind: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            kind: kind.into(),
+            active: true,
+            ..Default::default()
+        }
+    }
+}
+
+impl Validator for Resource {
+    fn validate(&self) -> Result<(), Error> {
+        if self.id.is_empty() || self.id.len() > MAX_RESOURCE_ID_LEN {
+            return Err(Error::InvalidResourceId(self.id.clone()));
+        }
+        if !self.timezone.is_empty() && self.timezone.parse::<Tz>().is_err() {
+            return Err(Error::InvalidTimezone(self.timezone.clone()));
+        }
+        if self.capacity < 0 {
+            return Err(Error::InvalidCapacity(self.capacity));
+        }
+        if let Some(policy) = self.policy.as_ref() {
+            policy.validate()?;
+        }
+        for buffer in [&self.buffer_before, &self.buffer_after]
+            .into_iter()
+            .flatten()
+        {
+            if buffer.seconds < 0 || buffer.nanos != 0 {
+                return Err(Error::InvalidBuffer(buffer.seconds));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Normalizer for Resource {
+    fn do_normalize(&mut self) {
+        if self.timezone.is_empty() {
+            self.timezone = "UTC".to_string();
+        }
+        if self.capacity == 0 {
+            self.capacity = 1;
+        }
+    }
+}
+
+impl FromRow<'_, PgRow> for Resource {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let attributes: Json<HashMap<String, String>> = row.get("attributes");
+        Ok(Self {
+            id: row.get("id"),
+            name: row.get("name"),
+            kind: row.get("kind"),
+            timezone: row.get("timezone"),
+            attributes: attributes.0,
+            capacity: row.get("capacity"),
+            active: row.get("active"),
+            policy: Some(BookingPolicy {
+                min_duration: get_duration(row, "min_duration")?,
+                max_duration: get_duration(row, "max_duration")?,
+                min_lead_time: get_duration(row, "min_lead_time")?,
+                max_horizon: get_duration(row, "max_horizon")?,
+            }),
+            buffer_before: get_duration(row, "buffer_before")?.filter(|d| d.seconds > 0),
+            buffer_after: get_duration(row, "buffer_after")?.filter(|d| d.seconds > 0),
+        })
+    }
+}
+
+impl Resource {
+    /// copy the fields of the `paths` from `patch`, the changed resource is validated
+    pub fn apply_update(&mut self, patch: &Resource, paths: &[String]) -> Result<(), Error> {
+        if paths.is_empty() {
+            return Err(Error::InvalidUpdateMask(String::new()));
+        }
+        for path in paths {
+            match path.as_str() {
+                "name" => self.name = patch.name.clone(),
+                "kind" => self.kind = patch.kind.clone(),
+                "timezone" => self.timezone = patch.timezone.clone(),
+                "attributes" => self.attributes = patch.attributes.clone(),
+                "capacity" => self.capacity = patch.capacity,
+                "policy" => self.policy = patch.policy.clone(),
+                "buffer_before" => self.buffer_before = patch.buffer_before.clone(),
+                "buffer_after" => self.buffer_after = patch.buffer_after.clone(),
+                _ => return Err(Error::InvalidUpdateMask(path.clone())),
+            }
+        }
+        self.normalize()
+    }
+
+    /// the timezone of the resource, UTC if it isn't set
+    pub fn tz(&self) -> Tz {
+        self.timezone.parse().unwrap_or(Tz::UTC)
+    }
+
+    /// check a reservation from `start` to `end`, made at `now`, against the booking policy
+    pub fn check_policy(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        match self.policy.as_ref() {
+            Some(policy) => policy.check(start, end, now),
+            None => Ok(()),
+        }
+    }
+
+    /// the window kept for a reservation from `start` to `end`, with the buffers around it
+    pub fn padded(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> (DateTime<Utc>, DateTime<Utc>) {
+        let secs = |d: &Option<prost_types::Duration>| {
+            Duration::seconds(d.as_ref().map(|d| d.seconds).unwrap_or_default())
+        };
+        (
+            start - secs(&self.buffer_before),
+            end + secs(&self.buffer_after),
+        )
+    }
+
+    /// seconds of the buffers before and after a reservation
+    pub fn buffer_secs(&self) -> (i64, i64) {
+        let secs =
+            |d: &Option<prost_types::Duration>| d.as_ref().map(|d| d.seconds).unwrap_or_default();
+        (secs(&self.buffer_before), secs(&self.buffer_after))
+    }
+
+    /// seconds of the booking policy rules, in the order of the resources table columns
+    pub fn policy_secs(&self) -> [Option<i64>; 4] {
+        let policy = self.policy.clone().unwrap_or_default();
+        [
+            policy.min_duration,
+            policy.max_duration,
+            policy.min_lead_time,
+            policy.max_horizon,
+        ]
+        .map(|d| d.map(|d| d.seconds))
+    }
+}
+
+impl UpdateResourceRequest {
+    /// change the fields of the `paths` to their values in `resource`
+    pub fn new<'a>(resource: Resource, paths: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            resource: Some(resource),
+            update_mask: Some(FieldMask {
+                paths: paths.into_iter().map(|p| p.to_string()).collect(),
+            }),
+        }
+    }
+
+    /// paths of the fields to change
+    pub fn paths(&self) -> &[String] {
+        self.update_mask
+            .as_ref()
+            .map(|mask| mask.paths.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+// the rules are stored as seconds
+fn get_duration(row: &PgRow, column: &str) -> Result<Option<prost_types::Duration>, sqlx::Error> {
+    let secs: Option<i64> = row.try_get(column)?;
+    Ok(secs.map(|seconds| prost_types::Duration { seconds, nanos: 0 }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resource_should_normalize() {
+        let mut resource = Resource::new("room-1", "Room 1", "room");
+        resource.normalize().unwrap();
+        assert_eq!(resource.timezone, "UTC");
+        assert_eq!(resource.capacity, 1);
+
+        resource.timezone = "America/Los_Angeles".to_string();
+        resource.capacity = 30;
+        resource.normalize().unwrap();
+        assert_eq!(resource.timezone, "America/Los_Angeles");
+        assert_eq!(resource.capacity, 30);
+    }
+
+    #[test]
+    fn resource_should_validate() {
+        let resource = Resource::new("", "Room 1", "room");
+        assert_eq!(
+            resource.validate().unwrap_err(),
+            Error::InvalidResourceId("".to_string())
+        );
+
+        let mut resource = Resource::new("room-1", "Room 1", "room");
+        resource.timezone = "Mars/Olympus_Mons".to_string();
+        assert_eq!(
+            resource.validate().unwrap_err(),
+            Error::InvalidTimezone("Mars/Olympus_Mons".to_string())
+        );
+
+        resource.timezone = "".to_string();
+        resource.capacity = -1;
+        assert_eq!(resource.validate().unwrap_err(), Error::InvalidCapacity(-1));
+
+        resource.capacity = 1;
+        resource.buffer_after = Some(prost_types::Duration {
+            seconds: 600,
+            nanos: 1,
+        });
+        assert_eq!(resource.validate().unwrap_err(), Error::InvalidBuffer(600));
+    }
+}
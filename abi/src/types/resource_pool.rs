@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+
+use crate::{Error, PoolStrategy, Reservation, ReserveAnyRequest, ResourcePool, Validator};
+
+// resource_pools.id is a varchar(64)
+const MAX_POOL_ID_LEN: usize = 64;
+
+impl ResourcePool {
+    pub fn new(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        resource_ids: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            resource_ids: resource_ids.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl Validator for ResourcePool {
+    fn validate(&self) -> Result<(), Error> {
+        if self.id.is_empty() || self.id.len() > MAX_POOL_ID_LEN {
+            return Err(Error::InvalidPool(format!("invalid id {:?}", self.id)));
+        }
+        let mut seen = HashSet::new();
+        for id in &self.resource_ids {
+            if !seen.insert(id) {
+                return Err(Error::InvalidPool(format!("{} is listed twice", id)));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ReserveAnyRequest {
+    pub fn new(pool_id: impl Into<String>, rsvp: Reservation, strategy: PoolStrategy) -> Self {
+        Self {
+            pool_id: pool_id.into(),
+            reservation: Some(rsvp),
+            strategy: strategy as i32,
+            hold_ttl: None,
+        }
+    }
+}
+
+impl PoolStrategy {
+    /// order the active members of a pool to try, given in pool order with their number of
+    /// reservations not ended by the requested start, and the member reserved last time
+    pub fn order(&self, members: Vec<(String, i64)>, last: Option<&str>) -> Vec<String> {
+        let mut members = members;
+        match self {
+            PoolStrategy::FirstFit => {}
+            // sort is stable, so members used as much are kept in pool order
+            PoolStrategy::LeastUsed => members.sort_by_key(|(_, used)| *used),
+            PoolStrategy::RoundRobin => {
+                if let Some(i) = last.and_then(|last| members.iter().position(|(id, _)| id == last))
+                {
+                    members.rotate_left(i + 1);
+                }
+            }
+        }
+        members.into_iter().map(|(id, _)| id).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn members() -> Vec<(String, i64)> {
+        vec![
+            ("room-1".to_string(), 3),
+            ("room-2".to_string(), 1),
+            ("room-3".to_string(), 1),
+        ]
+    }
+
+    #[test]
+    fn pool_strategy_should_order_members() {
+        assert_eq!(
+            PoolStrategy::FirstFit.order(members(), Some("room-1")),
+            vec!["room-1", "room-2", "room-3"]
+        );
+        assert_eq!(
+            PoolStrategy::LeastUsed.order(members(), None),
+            vec!["room-2", "room-3", "room-1"]
+        );
+        assert_eq!(
+            PoolStrategy::RoundRobin.order(members(), Some("room-2")),
+            vec!["room-3", "room-1", "room-2"]
+        );
+        // the last member may have left the pool
+        assert_eq!(
+            PoolStrategy::RoundRobin.order(members(), Some("room-9")),
+            vec!["room-1", "room-2", "room-3"]
+        );
+    }
+
+    #[test]
+    fn pool_with_duplicated_members_should_be_rejected() {
+        let pool = ResourcePool::new("floor-3", "Floor 3", ["room-1", "room-2", "room-1"]);
+        assert_eq!(
+            pool.validate(),
+            Err(Error::InvalidPool("room-1 is listed twice".to_string()))
+        );
+    }
+}
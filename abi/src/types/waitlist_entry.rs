@@ -0,0 +1,41 @@
+use super::reservation::NaiveRange;
+use crate::{convert_to_timestamp, Reservation, ReservationStatus, WaitlistEntry};
+use chrono::{DateTime, Utc};
+use sqlx::{
+    postgres::{types::PgRange, PgRow},
+    FromRow, Row,
+};
+
+impl WaitlistEntry {
+    /// the pending reservation the entry is promoted to
+    pub fn to_reservation(&self) -> Reservation {
+        Reservation {
+            id: 0,
+            user_id: self.user_id.clone(),
+            resource_id: self.resource_id.clone(),
+            start: self.start.clone(),
+            end: self.end.clone(),
+            note: self.note.clone(),
+            status: ReservationStatus::Pending as i32,
+            series_id: 0,
+            expires_at: None,
+            version: 0,
+        }
+    }
+}
+
+impl FromRow<'_, PgRow> for WaitlistEntry {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let range: PgRange<DateTime<Utc>> = row.get("timespan");
+        let range: NaiveRange<DateTime<Utc>> = range.into();
+        Ok(Self {
+            id: row.get("id"),
+            user_id: row.get("user_id"),
+            resource_id: row.get("resource_id"),
+            start: range.start.map(convert_to_timestamp),
+            end: range.end.map(convert_to_timestamp),
+            note: row.get("note"),
+            created_at: Some(convert_to_timestamp(row.get("created_at"))),
+        })
+    }
+}
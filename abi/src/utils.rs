@@ -1,4 +1,4 @@
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
 use prost_types::Timestamp;
 
 pub fn convert_to_utc_time(ts: &Timestamp) -> DateTime<Utc> {
@@ -12,3 +12,28 @@ pub fn convert_to_timestamp(dt: DateTime<Utc>) -> Timestamp {
         nanos: dt.timestamp_subsec_nanos() as i32,
     }
 }
+
+pub fn convert_to_duration(d: &prost_types::Duration) -> Duration {
+    Duration::seconds(d.seconds) + Duration::nanoseconds(d.nanos as i64)
+}
+
+/// format seconds as a readable duration, e.g. 1d2h, 4h, 15m
+pub fn format_secs(secs: i64) -> String {
+    let units = [("d", 86400), ("h", 3600), ("m", 60), ("s", 1)];
+    let mut left = secs.abs();
+    let mut s = if secs < 0 {
+        "-".to_string()
+    } else {
+        String::new()
+    };
+    for (name, size) in units {
+        if left >= size {
+            s.push_str(&format!("{}{}", left / size, name));
+            left %= size;
+        }
+    }
+    if s.is_empty() || s == "-" {
+        s = "0s".to_string();
+    }
+    s
+}
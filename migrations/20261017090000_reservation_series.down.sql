@@ -0,0 +1,2 @@
+ALTER TABLE rsvp.reservations DROP COLUMN series_id;
+DROP TABLE rsvp.reservation_series CASCADE;
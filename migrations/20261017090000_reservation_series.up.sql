@@ -0,0 +1,13 @@
+-- recurring reservations, each occurrence is a row in rsvp.reservations with the series id
+CREATE TABLE rsvp.reservation_series (
+    id bigserial NOT NULL,
+    user_id varchar(64) NOT NULL,
+    resource_id varchar(64) NOT NULL,
+    rrule text NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT reservation_series_pkey PRIMARY KEY (id)
+);
+
+ALTER TABLE rsvp.reservations ADD COLUMN series_id bigint REFERENCES rsvp.reservation_series (id);
+
+CREATE INDEX reservation_series_id_idx ON rsvp.reservations (series_id);
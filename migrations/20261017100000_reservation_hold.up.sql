@@ -0,0 +1,4 @@
+-- a pending reservation with expires_at is a hold, it is released by the reaper once expired
+ALTER TABLE rsvp.reservations ADD COLUMN expires_at timestamptz;
+
+CREATE INDEX reservations_expires_at_idx ON rsvp.reservations (expires_at) WHERE status = 'pending';
@@ -0,0 +1,10 @@
+-- postgres can't drop an enum value, so recreate the type without it
+ALTER TYPE rsvp.reservation_status RENAME TO reservation_status_old;
+CREATE TYPE rsvp.reservation_status AS ENUM ('unknown', 'pending', 'confirmed', 'blocked');
+ALTER TABLE rsvp.reservations ALTER COLUMN status DROP DEFAULT;
+DROP INDEX rsvp.reservations_expires_at_idx;
+ALTER TABLE rsvp.reservations
+    ALTER COLUMN status TYPE rsvp.reservation_status USING status::text::rsvp.reservation_status;
+ALTER TABLE rsvp.reservations ALTER COLUMN status SET DEFAULT 'pending';
+CREATE INDEX reservations_expires_at_idx ON rsvp.reservations (expires_at) WHERE status = 'pending';
+DROP TYPE rsvp.reservation_status_old;
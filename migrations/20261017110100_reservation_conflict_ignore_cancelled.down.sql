@@ -0,0 +1,5 @@
+-- cancelled reservations might overlap with others, they were hard deleted before
+DELETE FROM rsvp.reservations WHERE status = 'cancelled';
+ALTER TABLE rsvp.reservations DROP CONSTRAINT reservations_conflict;
+ALTER TABLE rsvp.reservations ADD CONSTRAINT reservations_conflict
+    EXCLUDE USING gist (resource_id WITH =, timespan WITH &&);
@@ -0,0 +1,4 @@
+-- cancelled reservations are kept for reporting, but they don't occupy the resource any more
+ALTER TABLE rsvp.reservations DROP CONSTRAINT reservations_conflict;
+ALTER TABLE rsvp.reservations ADD CONSTRAINT reservations_conflict
+    EXCLUDE USING gist (resource_id WITH =, timespan WITH &&) WHERE (status <> 'cancelled');
@@ -0,0 +1,7 @@
+-- shared reservations might overlap with each other
+DELETE FROM rsvp.reservations WHERE shared AND status <> 'cancelled';
+ALTER TABLE rsvp.reservations DROP CONSTRAINT reservations_conflict;
+ALTER TABLE rsvp.reservations ADD CONSTRAINT reservations_conflict
+    EXCLUDE USING gist (resource_id WITH =, timespan WITH &&) WHERE (status <> 'cancelled');
+ALTER TABLE rsvp.reservations DROP COLUMN shared;
+DROP TABLE rsvp.resources;
@@ -0,0 +1,15 @@
+-- resources taking more than one reservation at a time, e.g. parking spots or seats of a room
+CREATE TABLE rsvp.resources (
+    id varchar(64) NOT NULL,
+    capacity int NOT NULL DEFAULT 1,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT resources_pkey PRIMARY KEY (id),
+    CONSTRAINT resources_capacity_check CHECK (capacity > 0)
+);
+
+-- shared reservations are for a resource with capacity, they are checked by the reserve logic
+ALTER TABLE rsvp.reservations ADD COLUMN shared boolean NOT NULL DEFAULT false;
+
+ALTER TABLE rsvp.reservations DROP CONSTRAINT reservations_conflict;
+ALTER TABLE rsvp.reservations ADD CONSTRAINT reservations_conflict
+    EXCLUDE USING gist (resource_id WITH =, timespan WITH &&) WHERE (status <> 'cancelled' AND NOT shared);
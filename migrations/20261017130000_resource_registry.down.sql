@@ -0,0 +1,8 @@
+ALTER TABLE rsvp.reservations DROP CONSTRAINT reservations_resource_id_fkey;
+DROP INDEX rsvp.resources_kind_idx;
+ALTER TABLE rsvp.resources
+    DROP COLUMN name,
+    DROP COLUMN kind,
+    DROP COLUMN timezone,
+    DROP COLUMN attributes,
+    DROP COLUMN active;
@@ -0,0 +1,15 @@
+-- every reservation is for a registered resource
+ALTER TABLE rsvp.resources
+    ADD COLUMN name varchar(128) NOT NULL DEFAULT '',
+    ADD COLUMN kind varchar(64) NOT NULL DEFAULT '',
+    ADD COLUMN timezone varchar(64) NOT NULL DEFAULT 'UTC',
+    ADD COLUMN attributes jsonb NOT NULL DEFAULT '{}',
+    ADD COLUMN active boolean NOT NULL DEFAULT true;
+
+CREATE INDEX resources_kind_idx ON rsvp.resources (kind);
+
+-- register the resources already reserved, so the foreign key holds
+INSERT INTO rsvp.resources (id) SELECT DISTINCT resource_id FROM rsvp.reservations ON CONFLICT DO NOTHING;
+
+ALTER TABLE rsvp.reservations ADD CONSTRAINT reservations_resource_id_fkey
+    FOREIGN KEY (resource_id) REFERENCES rsvp.resources (id);
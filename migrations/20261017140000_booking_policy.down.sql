@@ -0,0 +1,5 @@
+ALTER TABLE rsvp.resources
+    DROP COLUMN min_duration,
+    DROP COLUMN max_duration,
+    DROP COLUMN min_lead_time,
+    DROP COLUMN max_horizon;
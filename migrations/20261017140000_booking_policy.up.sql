@@ -0,0 +1,6 @@
+-- booking policy of a resource, in seconds, a NULL rule is not enforced
+ALTER TABLE rsvp.resources
+    ADD COLUMN min_duration bigint,
+    ADD COLUMN max_duration bigint,
+    ADD COLUMN min_lead_time bigint,
+    ADD COLUMN max_horizon bigint;
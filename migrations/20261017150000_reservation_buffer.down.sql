@@ -0,0 +1,8 @@
+ALTER TABLE rsvp.reservations DROP CONSTRAINT reservations_conflict;
+ALTER TABLE rsvp.reservations ADD CONSTRAINT reservations_conflict
+    EXCLUDE USING gist (resource_id WITH =, timespan WITH &&) WHERE (status <> 'cancelled' AND NOT shared);
+ALTER TABLE rsvp.reservations DROP COLUMN padded_timespan;
+
+ALTER TABLE rsvp.resources
+    DROP COLUMN buffer_before,
+    DROP COLUMN buffer_after;
@@ -0,0 +1,13 @@
+-- time kept free around each reservation of a resource, in seconds
+ALTER TABLE rsvp.resources
+    ADD COLUMN buffer_before bigint NOT NULL DEFAULT 0,
+    ADD COLUMN buffer_after bigint NOT NULL DEFAULT 0;
+
+-- the timespan with the buffers of the resource when it was reserved, this is what conflicts
+ALTER TABLE rsvp.reservations ADD COLUMN padded_timespan tstzrange;
+UPDATE rsvp.reservations SET padded_timespan = timespan;
+ALTER TABLE rsvp.reservations ALTER COLUMN padded_timespan SET NOT NULL;
+
+ALTER TABLE rsvp.reservations DROP CONSTRAINT reservations_conflict;
+ALTER TABLE rsvp.reservations ADD CONSTRAINT reservations_conflict
+    EXCLUDE USING gist (resource_id WITH =, padded_timespan WITH &&) WHERE (status <> 'cancelled' AND NOT shared);
@@ -0,0 +1,2 @@
+DROP TABLE rsvp.pool_members;
+DROP TABLE rsvp.resource_pools;
@@ -0,0 +1,16 @@
+-- pools of interchangeable resources, a reservation could be made for any member of a pool
+CREATE TABLE rsvp.resource_pools (
+    id varchar(64) NOT NULL,
+    name varchar(128) NOT NULL DEFAULT '',
+    -- member reserved last time, round-robin starts after it
+    last_resource_id varchar(64),
+    created_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT resource_pools_pkey PRIMARY KEY (id)
+);
+
+CREATE TABLE rsvp.pool_members (
+    pool_id varchar(64) NOT NULL REFERENCES rsvp.resource_pools (id) ON DELETE CASCADE,
+    resource_id varchar(64) NOT NULL REFERENCES rsvp.resources (id),
+    position int NOT NULL,
+    CONSTRAINT pool_members_pkey PRIMARY KEY (pool_id, resource_id)
+);
@@ -0,0 +1,12 @@
+-- users queueing for a taken window, the earliest entry is reserved once the window is freed
+CREATE TABLE rsvp.waitlist (
+    id bigserial NOT NULL,
+    user_id varchar(64) NOT NULL,
+    resource_id varchar(64) NOT NULL REFERENCES rsvp.resources (id),
+    timespan tstzrange NOT NULL,
+    note text NOT NULL DEFAULT '',
+    created_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT waitlist_pkey PRIMARY KEY (id)
+);
+
+CREATE INDEX waitlist_resource_id_idx ON rsvp.waitlist USING gist (resource_id, timespan);
@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS reservation_version_trigger ON rsvp.reservations;
+DROP FUNCTION IF EXISTS rsvp.reservation_version_trigger;
+ALTER TABLE rsvp.reservations DROP COLUMN version;
@@ -0,0 +1,13 @@
+-- version of a reservation, bumped on every change, so a stale update could be detected
+ALTER TABLE rsvp.reservations ADD COLUMN version bigint NOT NULL DEFAULT 1;
+
+CREATE OR REPLACE FUNCTION rsvp.reservation_version_trigger() RETURNS trigger AS $$
+BEGIN
+    NEW.version := OLD.version + 1;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER reservation_version_trigger
+    BEFORE UPDATE ON rsvp.reservations
+    FOR EACH ROW EXECUTE PROCEDURE rsvp.reservation_version_trigger();
@@ -0,0 +1,11 @@
+-- responses of the mutations made with an idempotency key, replayed on a retry with the same key
+CREATE TABLE rsvp.idempotency_keys (
+    key varchar(128) NOT NULL,
+    operation varchar(32) NOT NULL,
+    -- encoded response, NULL while the mutation is in progress
+    response bytea,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT idempotency_keys_pkey PRIMARY KEY (key)
+);
+
+CREATE INDEX idempotency_keys_created_at_idx ON rsvp.idempotency_keys (created_at);
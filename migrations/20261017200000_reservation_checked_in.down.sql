@@ -0,0 +1,14 @@
+-- postgres can't drop an enum value, so recreate the type without it
+UPDATE rsvp.reservations SET status = 'confirmed' WHERE status = 'checked_in';
+ALTER TYPE rsvp.reservation_status RENAME TO reservation_status_old;
+CREATE TYPE rsvp.reservation_status AS ENUM ('unknown', 'pending', 'confirmed', 'blocked', 'cancelled');
+ALTER TABLE rsvp.reservations DROP CONSTRAINT reservations_conflict;
+ALTER TABLE rsvp.reservations ALTER COLUMN status DROP DEFAULT;
+DROP INDEX rsvp.reservations_expires_at_idx;
+ALTER TABLE rsvp.reservations
+    ALTER COLUMN status TYPE rsvp.reservation_status USING status::text::rsvp.reservation_status;
+ALTER TABLE rsvp.reservations ALTER COLUMN status SET DEFAULT 'pending';
+CREATE INDEX reservations_expires_at_idx ON rsvp.reservations (expires_at) WHERE status = 'pending';
+ALTER TABLE rsvp.reservations ADD CONSTRAINT reservations_conflict
+    EXCLUDE USING gist (resource_id WITH =, padded_timespan WITH &&) WHERE (status <> 'cancelled' AND NOT shared);
+DROP TYPE rsvp.reservation_status_old;
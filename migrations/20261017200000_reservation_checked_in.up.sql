@@ -0,0 +1,2 @@
+-- a confirmed reservation is checked in when the user shows up
+ALTER TYPE rsvp.reservation_status ADD VALUE 'checked_in';
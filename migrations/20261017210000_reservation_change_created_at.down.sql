@@ -0,0 +1 @@
+ALTER TABLE rsvp.reservation_changes DROP COLUMN created_at;
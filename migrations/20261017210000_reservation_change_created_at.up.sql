@@ -0,0 +1,2 @@
+-- when a change was made, the changes recorded before get the time of this migration
+ALTER TABLE rsvp.reservation_changes ADD COLUMN created_at timestamptz NOT NULL DEFAULT now();
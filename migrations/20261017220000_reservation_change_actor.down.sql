@@ -0,0 +1,17 @@
+CREATE OR REPLACE FUNCTION rsvp.reservation_trigger() RETURNS trigger AS $$
+BEGIN
+    IF (TG_OP = 'INSERT') THEN
+        INSERT INTO rsvp.reservation_changes (reservation_id,old,new,op) VALUES (NEW.id,NULL,to_jsonb(NEW), 'create');
+    ELSIF (TG_OP = 'UPDATE') THEN
+        IF (OLD.status != NEW.status) THEN
+            INSERT INTO rsvp.reservation_changes (reservation_id,old,new,op) VALUES (NEW.id,to_jsonb(OLD),to_jsonb(NEW), 'update');
+        END IF;
+    ELSIF (TG_OP = 'DELETE') THEN
+        INSERT INTO rsvp.reservation_changes (reservation_id,old,new,op) VALUES (OLD.id,to_jsonb(OLD),NULL, 'delete');
+    END IF;
+    NOTIFY reservation_update;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+ALTER TABLE rsvp.reservation_changes DROP COLUMN actor;
@@ -0,0 +1,26 @@
+-- who made a change, taken from the `rsvp.actor` setting of the transaction
+ALTER TABLE rsvp.reservation_changes ADD COLUMN actor varchar(64);
+
+-- record every change of a reservation, an update is recorded if any column but the version changes
+CREATE OR REPLACE FUNCTION rsvp.reservation_trigger() RETURNS trigger AS $$
+DECLARE
+    actor varchar(64) := NULLIF(current_setting('rsvp.actor', true), '');
+BEGIN
+    IF (TG_OP = 'INSERT') THEN
+        INSERT INTO rsvp.reservation_changes (reservation_id, old, new, op, actor)
+            VALUES (NEW.id, NULL, to_jsonb(NEW), 'create', actor);
+    ELSIF (TG_OP = 'UPDATE') THEN
+        -- the version is bumped on every update, so it doesn't count as a change
+        IF (to_jsonb(OLD) - 'version' IS DISTINCT FROM to_jsonb(NEW) - 'version') THEN
+            INSERT INTO rsvp.reservation_changes (reservation_id, old, new, op, actor)
+                VALUES (NEW.id, to_jsonb(OLD), to_jsonb(NEW), 'update', actor);
+        END IF;
+    ELSIF (TG_OP = 'DELETE') THEN
+        INSERT INTO rsvp.reservation_changes (reservation_id, old, new, op, actor)
+            VALUES (OLD.id, to_jsonb(OLD), NULL, 'delete', actor);
+    END IF;
+    -- notify a channel called reservation_change
+    NOTIFY reservation_update;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,5 @@
+-- the transaction that made a change, change ids are taken before commit so they could become
+-- visible out of order, listeners read the changes in the order of their transactions instead
+ALTER TABLE rsvp.reservation_changes ADD COLUMN xid xid8 NOT NULL DEFAULT pg_current_xact_id();
+
+CREATE INDEX reservation_changes_xid_id_idx ON rsvp.reservation_changes (xid, id);
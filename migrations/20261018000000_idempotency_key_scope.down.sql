@@ -0,0 +1,9 @@
+-- the same key could be used by several callers and operations, which the old primary key doesn't allow
+DELETE FROM rsvp.idempotency_keys;
+ALTER TABLE rsvp.idempotency_keys
+    DROP CONSTRAINT idempotency_keys_pkey,
+    ADD CONSTRAINT idempotency_keys_pkey PRIMARY KEY (key);
+ALTER TABLE rsvp.idempotency_keys
+    DROP COLUMN caller,
+    DROP COLUMN request_hash,
+    DROP COLUMN claimed_until;
@@ -0,0 +1,13 @@
+-- a key is scoped by the caller and the operation, and bound to the hash of the request it was
+-- claimed for. A claim without a response is leased, a retry takes it over once the lease is over
+ALTER TABLE rsvp.idempotency_keys
+    ADD COLUMN caller text NOT NULL DEFAULT '',
+    ADD COLUMN request_hash bytea NOT NULL DEFAULT '',
+    ADD COLUMN claimed_until timestamptz NOT NULL DEFAULT now();
+ALTER TABLE rsvp.idempotency_keys
+    ALTER COLUMN request_hash DROP DEFAULT,
+    ALTER COLUMN claimed_until DROP DEFAULT;
+
+ALTER TABLE rsvp.idempotency_keys
+    DROP CONSTRAINT idempotency_keys_pkey,
+    ADD CONSTRAINT idempotency_keys_pkey PRIMARY KEY (caller, operation, key);
@@ -0,0 +1,3 @@
+DROP INDEX rsvp.reservation_changes_created_at_idx;
+DROP INDEX rsvp.reservation_changes_reservation_id_id_idx;
+DROP TABLE rsvp.change_log_start;
@@ -0,0 +1,10 @@
+-- before this migration, changes were logged without their time and some updates were not logged at
+-- all, so reservations are only reconstructed as of this time on
+CREATE TABLE rsvp.change_log_start (
+    started_at timestamptz NOT NULL
+);
+INSERT INTO rsvp.change_log_start (started_at) VALUES (now());
+
+-- the latest change of each reservation made by a given time
+CREATE INDEX reservation_changes_reservation_id_id_idx ON rsvp.reservation_changes (reservation_id, id);
+CREATE INDEX reservation_changes_created_at_idx ON rsvp.reservation_changes (created_at);
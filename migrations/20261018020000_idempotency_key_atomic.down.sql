@@ -0,0 +1,4 @@
+ALTER TABLE rsvp.idempotency_keys
+    ALTER COLUMN response DROP NOT NULL,
+    ADD COLUMN claimed_until timestamptz NOT NULL DEFAULT now();
+ALTER TABLE rsvp.idempotency_keys ALTER COLUMN claimed_until DROP DEFAULT;
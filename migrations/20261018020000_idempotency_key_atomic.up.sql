@@ -0,0 +1,5 @@
+-- a key is saved with its response in the transaction of the mutation, so it is never left claimed
+DELETE FROM rsvp.idempotency_keys WHERE response IS NULL;
+ALTER TABLE rsvp.idempotency_keys
+    DROP COLUMN claimed_until,
+    ALTER COLUMN response SET NOT NULL;
@@ -0,0 +1 @@
+ALTER TABLE rsvp.reservation_changes ALTER COLUMN created_at SET DEFAULT now();
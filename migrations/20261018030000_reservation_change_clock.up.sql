@@ -0,0 +1,3 @@
+-- stamp a change when it is recorded rather than when its transaction started, a transaction that
+-- started earlier may record its change of a reservation after another one committed
+ALTER TABLE rsvp.reservation_changes ALTER COLUMN created_at SET DEFAULT clock_timestamp();
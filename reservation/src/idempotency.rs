@@ -0,0 +1,188 @@
+use crate::{Idempotency, IdempotencyKey, ReservationManager};
+use async_trait::async_trait;
+use prost::Message;
+
+const MAX_KEY_LEN: usize = 128;
+
+#[async_trait]
+impl Idempotency for ReservationManager {
+    async fn saved_reservation(
+        &self,
+        key: &IdempotencyKey,
+        ttl: chrono::Duration,
+    ) -> Result<Option<abi::Reservation>, abi::Error> {
+        if key.key.is_empty() || key.key.len() > MAX_KEY_LEN {
+            return Err(abi::Error::InvalidIdempotencyKey(key.key.clone()));
+        }
+        sqlx::query("DELETE FROM rsvp.idempotency_keys WHERE created_at <= now() - $1::interval")
+            .bind(ttl)
+            .execute(&self.pool)
+            .await?;
+        let saved: Option<(bool, Vec<u8>)> = sqlx::query_as(
+            r#"
+            SELECT request_hash = sha256($4), response FROM rsvp.idempotency_keys
+            WHERE caller = $1 AND operation = $2 AND key = $3
+            "#,
+        )
+        .bind(&key.caller)
+        .bind(&key.operation)
+        .bind(&key.key)
+        .bind(&key.request)
+        .fetch_optional(&self.pool)
+        .await?;
+        match saved {
+            None => Ok(None),
+            Some((false, _)) => Err(abi::Error::IdempotencyKeyReused(key.key.clone())),
+            Some((true, response)) => abi::Reservation::decode(response.as_slice())
+                .map(Some)
+                .map_err(|_| abi::Error::Unknown),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ResourceRegistry, Rsvp};
+    use sqlx_db_tester::TestDb;
+
+    #[tokio::test]
+    async fn saved_reservation_should_replay_the_mutation() {
+        let tdb = get_db();
+        let manager = get_manager(&tdb).await;
+        let ttl = chrono::Duration::hours(1);
+        let k1 = key("aliceid", "reserve", "k1", &[1]);
+        assert_eq!(manager.saved_reservation(&k1, ttl).await.unwrap(), None);
+
+        let rsvp = manager
+            .with_idempotency_key(k1.clone())
+            .reserve(make_reservation())
+            .await
+            .unwrap();
+        let saved = manager.saved_reservation(&k1, ttl).await.unwrap();
+        assert_eq!(saved, Some(rsvp));
+
+        // the key was used for another request
+        let other = key("aliceid", "reserve", "k1", &[2]);
+        let err = manager.saved_reservation(&other, ttl).await.unwrap_err();
+        assert_eq!(err, abi::Error::IdempotencyKeyReused("k1".to_string()));
+
+        // keys of other operations and callers are apart
+        let cancel = key("aliceid", "cancel", "k1", &[1]);
+        assert_eq!(manager.saved_reservation(&cancel, ttl).await.unwrap(), None);
+        let bob = key("bobid", "reserve", "k1", &[1]);
+        assert_eq!(manager.saved_reservation(&bob, ttl).await.unwrap(), None);
+
+        // an expired key could be used again
+        let saved = manager
+            .saved_reservation(&other, chrono::Duration::zero())
+            .await
+            .unwrap();
+        assert_eq!(saved, None);
+
+        let err = manager
+            .saved_reservation(&key("aliceid", "reserve", "", &[1]), ttl)
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::InvalidIdempotencyKey("".to_string()));
+    }
+
+    #[tokio::test]
+    async fn failed_mutation_should_not_save_key() {
+        let tdb = get_db();
+        let manager = get_manager(&tdb).await;
+        let ttl = chrono::Duration::hours(1);
+        manager.reserve(make_reservation()).await.unwrap();
+
+        let k1 = key("bobid", "reserve", "k1", &[1]);
+        let err = manager
+            .with_idempotency_key(k1.clone())
+            .reserve(make_reservation())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, abi::Error::ConflictReservation(_)));
+        assert_eq!(manager.saved_reservation(&k1, ttl).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn mutation_with_saved_key_should_be_rolled_back() {
+        let tdb = get_db();
+        let manager = get_manager(&tdb).await;
+        let room = abi::Resource::new("meeting-room-2", "Meeting room 2", "room");
+        manager.create_resource(room).await.unwrap();
+        let k1 = key("aliceid", "reserve", "k1", &[1]);
+        let keyed = manager.with_idempotency_key(k1);
+        keyed.reserve(make_reservation()).await.unwrap();
+
+        // a change that doesn't conflict by itself is not made twice with the key either
+        let mut rsvp = make_reservation();
+        rsvp.resource_id = "meeting-room-2".to_string();
+        let err = keyed.reserve(rsvp.clone()).await.unwrap_err();
+        assert_eq!(err, abi::Error::IdempotencyKeyInProgress("k1".to_string()));
+        let conflicts = manager.check_conflicts(&rsvp).await.unwrap();
+        assert!(conflicts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn concurrent_mutations_with_same_key_should_be_done_once() {
+        let tdb = get_db();
+        let manager = get_manager(&tdb).await;
+        let k1 = key("aliceid", "cancel", "k1", &[1]);
+        let rsvp = manager.reserve(make_reservation()).await.unwrap();
+        let tasks: Vec<_> = (0..4)
+            .map(|_| {
+                let manager = manager.with_idempotency_key(k1.clone());
+                tokio::spawn(async move { manager.cancel(rsvp.id, None).await })
+            })
+            .collect();
+        let mut cancelled = vec![];
+        for task in tasks {
+            if let Ok(rsvp) = task.await.unwrap() {
+                cancelled.push(rsvp);
+            }
+        }
+        assert_eq!(cancelled.len(), 1);
+        let saved = manager
+            .saved_reservation(&k1, chrono::Duration::hours(1))
+            .await
+            .unwrap();
+        assert_eq!(saved, cancelled.pop());
+    }
+
+    /// a manager with the room of the reservations registered
+    async fn get_manager(tdb: &TestDb) -> ReservationManager {
+        let manager = ReservationManager::new(tdb.get_pool().await);
+        let room = abi::Resource::new("meeting-room-1", "Meeting room 1", "room");
+        manager.create_resource(room).await.unwrap();
+        manager
+    }
+
+    fn make_reservation() -> abi::Reservation {
+        abi::Reservation::new_pending(
+            "aliceid",
+            "meeting-room-1",
+            "2023-01-10T10:00:00-0700".parse().unwrap(),
+            "2023-01-10T11:00:00-0700".parse().unwrap(),
+            "weekly sync",
+        )
+    }
+
+    fn key(caller: &str, operation: &str, key: &str, request: &[u8]) -> IdempotencyKey {
+        IdempotencyKey {
+            caller: caller.to_string(),
+            operation: operation.to_string(),
+            key: key.to_string(),
+            request: request.to_vec(),
+        }
+    }
+
+    fn get_db() -> TestDb {
+        TestDb::new(
+            "localhost",
+            15432,
+            "postgres",
+            "7cOPpA7dnc",
+            "../migrations",
+        )
+    }
+}
@@ -1,29 +1,198 @@
+mod idempotency;
 mod manager;
+mod resource;
+mod waitlist;
 use abi::{Error, FilterPager, ReservationId};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 use tokio::sync::mpsc;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ReservationManager {
     pool: PgPool,
+    /// user making the changes, recorded in the change log, empty for the server itself
+    actor: String,
+    /// key saved with the reservation made by the mutation, in the same transaction
+    idempotency_key: Option<IdempotencyKey>,
+}
+
+/// an idempotency key of a caller for an operation, bound to the request it is used for
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdempotencyKey {
+    pub caller: String,
+    pub operation: String,
+    pub key: String,
+    /// the encoded request, only its hash is kept
+    pub request: Vec<u8>,
+}
+
+#[async_trait]
+pub trait ResourceRegistry {
+    /// register a resource, it is active once created
+    async fn create_resource(&self, resource: abi::Resource) -> Result<abi::Resource, Error>;
+    /// update the fields of a resource listed in `paths` to their values in `patch`. Fail if the
+    /// capacity is lowered below the reservations it has at once from now on. Changed buffers apply
+    /// to reservations made or moved afterwards, the existing ones keep their padded window
+    async fn update_resource(
+        &self,
+        patch: abi::Resource,
+        paths: &[String],
+    ) -> Result<abi::Resource, Error>;
+    /// retire a resource, it can't be reserved any more but its reservations are kept
+    async fn retire_resource(&self, id: &str) -> Result<abi::Resource, Error>;
+    /// list resources of the given kind, all kinds if it is empty
+    async fn list_resources(
+        &self,
+        kind: &str,
+        include_retired: bool,
+    ) -> Result<Vec<abi::Resource>, Error>;
+    /// create a pool of registered resources
+    async fn create_pool(&self, pool: abi::ResourcePool) -> Result<abi::ResourcePool, Error>;
+    /// rename a pool and replace its members
+    async fn update_pool(&self, pool: abi::ResourcePool) -> Result<abi::ResourcePool, Error>;
+}
+
+#[async_trait]
+pub trait Waitlist {
+    /// queue for the window of the given reservation, the earliest entry overlapping a window freed
+    /// by a cancellation or expiry is reserved as pending
+    async fn join_waitlist(&self, rsvp: abi::Reservation) -> Result<abi::WaitlistEntry, Error>;
+    /// leave the waitlist
+    async fn leave_waitlist(&self, id: i64) -> Result<abi::WaitlistEntry, Error>;
+    /// entries queueing for a resource, in the order they would be promoted
+    async fn list_waitlist(&self, resource_id: &str) -> Result<Vec<abi::WaitlistEntry>, Error>;
+}
+
+#[async_trait]
+pub trait Idempotency {
+    /// the reservation saved with a key by the mutation done with it, none if the key is not used.
+    /// Expired keys are dropped first, fail if the key was used for another request. The key is
+    /// saved by a mutation of the manager `with_idempotency_key`
+    async fn saved_reservation(
+        &self,
+        key: &IdempotencyKey,
+        ttl: chrono::Duration,
+    ) -> Result<Option<abi::Reservation>, Error>;
 }
 
 #[async_trait]
 pub trait Rsvp {
-    /// make a reservation
+    /// make a reservation, the resource must be registered and active
     async fn reserve(&self, mut rsvp: abi::Reservation) -> Result<abi::Reservation, Error>;
+    /// reserve an available member of a pool, tried in the order of the strategy. A member
+    /// taken, retired meanwhile or whose booking policy rejects the window is skipped, other
+    /// errors are returned right away. If the policy of every member rejects it, the last
+    /// rejection is returned
+    async fn reserve_any(
+        &self,
+        pool_id: &str,
+        rsvp: abi::Reservation,
+        strategy: abi::PoolStrategy,
+    ) -> Result<abi::Reservation, Error>;
+    /// make several reservations in one transaction, either all or none of them are made
+    async fn reserve_many(
+        &self,
+        rsvps: Vec<abi::Reservation>,
+    ) -> Result<Vec<abi::Reservation>, Error>;
 
-    /// change a reservation status (if current status is `pending`, then change to `confirmed`)
-    async fn change_status(&self, id: ReservationId) -> Result<abi::Reservation, Error>;
-    /// update note for a reservation
-    async fn update_note(&self, id: ReservationId, note: String)
-        -> Result<abi::Reservation, Error>;
+    /// make a recurring reservation, the rule is expanded from the start of the given reservation
+    /// in the timezone of its resource
+    async fn reserve_series(
+        &self,
+        rsvp: abi::Reservation,
+        rrule: abi::RRule,
+        mode: abi::SeriesConflictMode,
+    ) -> Result<abi::ReserveSeriesResponse, Error>;
+    /// cancel an occurrence and all the following ones in its series, the occurrence must not be
+    /// cancelled already. The following ones already cancelled are skipped
+    async fn cancel_series(&self, id: ReservationId) -> Result<Vec<abi::Reservation>, Error>;
+    /// update an occurrence and all the following ones in its series: set the note if given, and
+    /// move the occurrences not cancelled by `shift`. Each moved occurrence must be free like a new
+    /// reservation, a conflict fails with the index of the occurrence from the given one
+    async fn update_series(
+        &self,
+        id: ReservationId,
+        note: Option<String>,
+        shift: chrono::Duration,
+    ) -> Result<Vec<abi::Reservation>, Error>;
+
+    /// confirm a pending reservation, an expired hold can't be confirmed. Like the other single
+    /// reservation changes, it fails with `VersionMismatch` if an expected version is given and
+    /// the reservation is not at it
+    async fn change_status(
+        &self,
+        id: ReservationId,
+        expected_version: Option<i64>,
+    ) -> Result<abi::Reservation, Error>;
+    /// move a reservation to another status, fail with `InvalidTransition` if the state machine
+    /// doesn't allow it. The waitlist is promoted when a reservation is cancelled
+    async fn transition(
+        &self,
+        id: ReservationId,
+        to: abi::ReservationStatus,
+        expected_version: Option<i64>,
+    ) -> Result<abi::Reservation, Error>;
+    /// change the fields of the `paths` (note, user_id, resource_id, start, end) to their values in
+    /// `patch` in one update, a new resource or time window must be free like for a new reservation.
+    /// The waitlist of the window it leaves is promoted
+    async fn update(
+        &self,
+        id: ReservationId,
+        patch: abi::Reservation,
+        paths: &[String],
+        expected_version: Option<i64>,
+    ) -> Result<abi::Reservation, Error>;
+
+    /// move a reservation to a new time window, the waitlist of the window it leaves is promoted
+    async fn reschedule(
+        &self,
+        id: ReservationId,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        expected_version: Option<i64>,
+    ) -> Result<abi::Reservation, Error>;
 
-    /// delete a reservation
+    /// free intervals of a resource inside the window lasting at least `min_duration`, the
+    /// buffers of the resource are kept free around reservations if `with_buffers` is set
+    async fn availability(
+        &self,
+        resource_id: &str,
+        window: (DateTime<Utc>, DateTime<Utc>),
+        min_duration: chrono::Duration,
+        with_buffers: bool,
+    ) -> Result<Vec<abi::TimeSlot>, Error>;
+
+    /// reservations overlapping the window of the given one, as reserve would check, without
+    /// reserving anything. A resource with capacity has none unless it is fully reserved in the
+    /// window. The given reservation itself is left out if it has an id
+    async fn check_conflicts(
+        &self,
+        rsvp: &abi::Reservation,
+    ) -> Result<Vec<abi::Reservation>, Error>;
+
+    /// cancel a reservation, it is kept with `cancelled` status and no longer occupies the resource
+    async fn cancel(
+        &self,
+        id: ReservationId,
+        expected_version: Option<i64>,
+    ) -> Result<abi::Reservation, Error>;
+    /// block a resource for the window of the given reservation, pending reservations overlapping
+    /// it are cancelled if mode is `CancelPending`, confirmed ones as well if it is `Force`.
+    /// Otherwise an overlapping confirmed reservation fails the block
+    async fn block_resource(
+        &self,
+        block: abi::Reservation,
+        mode: abi::BlockMode,
+    ) -> Result<abi::BlockResourceResponse, Error>;
+    /// lift a block, fail with `NotFound` if the id is not a block
+    async fn unblock_resource(&self, id: ReservationId) -> Result<abi::Reservation, Error>;
+    /// delete a reservation permanently
     async fn delete(&self, id: ReservationId) -> Result<abi::Reservation, Error>;
     ///get a reservation by id
     async fn get(&self, id: ReservationId) -> Result<abi::Reservation, Error>;
+    /// changes of a reservation in the order they were made, fail with `NotFound` if it has none
+    async fn history(&self, id: ReservationId) -> Result<Vec<abi::ReservationChange>, Error>;
     /// query reservations
     async fn query(
         &self,
@@ -34,4 +203,13 @@ pub trait Rsvp {
         &self,
         filer: abi::ReservationFilter,
     ) -> Result<(FilterPager, Vec<abi::Reservation>), Error>;
+    /// release the pending reservations whose hold has expired by cancelling them
+    async fn release_expired_holds(&self) -> Result<Vec<abi::Reservation>, Error>;
+    /// listen to reservation changes, replay the missed ones first if the request asks to resume
+    async fn listen(
+        &self,
+        request: abi::ListenRequest,
+    ) -> Result<mpsc::Receiver<Result<abi::ListenResponse, Error>>, Error>;
+    /// save the read progress of a consumer, it resumes after the change when it listens again
+    async fn ack(&self, request: abi::AckRequest) -> Result<(), Error>;
 }
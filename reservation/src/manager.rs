@@ -1,84 +1,576 @@
-use crate::{ReservationManager, Rsvp};
-use abi::{DbConfig, FilterPager, Normalizer, ReservationId, ToSql, Validator};
+use std::{ops::Bound, time::Duration};
+
+use crate::{
+    waitlist::{promote_waitlist, promote_waitlist_in},
+    IdempotencyKey, ReservationManager, Rsvp,
+};
+use abi::{
+    convert_to_timestamp, convert_to_utc_time, DbConfig, FilterPager, Normalizer, ReservationId,
+    ToQuery, Validator,
+};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use futures::StreamExt;
-use sqlx::{postgres::PgPoolOptions, Either, PgPool, Row};
-use tokio::sync::mpsc;
+use prost::Message;
+use sqlx::{
+    postgres::{types::PgRange, PgListener, PgPoolOptions},
+    Connection, Either, FromRow, PgConnection, PgPool, Postgres, Row, Transaction,
+};
+use tokio::{sync::mpsc, task::JoinHandle};
 use tracing::{error, info, warn};
 
 #[async_trait]
 impl Rsvp for ReservationManager {
-    async fn reserve(&self, mut rsvp: abi::Reservation) -> Result<abi::Reservation, abi::Error> {
+    async fn reserve(&self, rsvp: abi::Reservation) -> Result<abi::Reservation, abi::Error> {
         rsvp.validate()?;
-        if rsvp.start.is_none() || rsvp.end.is_none() {
-            return Err(abi::Error::InvalidTime);
+        // the resource row stays locked until commit, so concurrent reserves are serialized
+        let mut tx = self.begin().await?;
+        let rsvp = insert_reservation(&mut tx, &rsvp, None).await?;
+        self.commit(tx, &rsvp).await?;
+        Ok(rsvp)
+    }
+
+    async fn reserve_any(
+        &self,
+        pool_id: &str,
+        mut rsvp: abi::Reservation,
+        strategy: abi::PoolStrategy,
+    ) -> Result<abi::Reservation, abi::Error> {
+        let last: Option<Option<String>> =
+            sqlx::query_scalar("SELECT last_resource_id FROM rsvp.resource_pools WHERE id = $1")
+                .bind(pool_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        let last = last.ok_or_else(|| abi::Error::PoolNotFound(pool_id.to_string()))?;
+        let members: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT m.resource_id, (
+                SELECT count(*) FROM rsvp.reservations r
+                WHERE r.resource_id = m.resource_id AND r.status <> 'cancelled'
+                    AND upper(r.timespan) > $2
+            )
+            FROM rsvp.pool_members m JOIN rsvp.resources res ON res.id = m.resource_id
+            WHERE m.pool_id = $1 AND res.active
+            ORDER BY m.position
+            "#,
+        )
+        .bind(pool_id)
+        .bind(rsvp.start.as_ref().map(convert_to_utc_time))
+        .fetch_all(&self.pool)
+        .await?;
+
+        // the booking policy of a member rejecting the window is only reported if all of them do
+        let mut rejected = None;
+        let mut taken = false;
+        for resource_id in strategy.order(members, last.as_deref()) {
+            rsvp.resource_id = resource_id;
+            rsvp.validate()?;
+            // each member is tried in its own transaction, a failed insert aborts the transaction
+            let mut tx = self.begin().await?;
+            match insert_reservation(&mut tx, &rsvp, None).await {
+                Ok(reserved) => rsvp = reserved,
+                Err(
+                    abi::Error::ConflictReservation(_)
+                    | abi::Error::CapacityExceeded(..)
+                    | abi::Error::ResourceRetired(_),
+                ) => {
+                    taken = true;
+                    continue;
+                }
+                Err(
+                    e @ (abi::Error::DurationTooShort(..)
+                    | abi::Error::DurationTooLong(..)
+                    | abi::Error::LeadTimeTooShort(_)
+                    | abi::Error::BeyondHorizon(_)),
+                ) => {
+                    rejected = Some(e);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            }
+            sqlx::query("UPDATE rsvp.resource_pools SET last_resource_id = $2 WHERE id = $1")
+                .bind(pool_id)
+                .bind(&rsvp.resource_id)
+                .execute(&mut tx)
+                .await?;
+            tx.commit().await?;
+            return Ok(rsvp);
+        }
+        match rejected {
+            Some(e) if !taken => Err(e),
+            _ => Err(abi::Error::NoAvailableResource(pool_id.to_string())),
         }
+    }
+
+    async fn reserve_many(
+        &self,
+        mut rsvps: Vec<abi::Reservation>,
+    ) -> Result<Vec<abi::Reservation>, abi::Error> {
+        for rsvp in &rsvps {
+            rsvp.validate()?;
+        }
+        // the transaction is rolled back on drop if any of the reservations fails
+        let mut tx = self.begin().await?;
+        for (i, rsvp) in rsvps.iter_mut().enumerate() {
+            *rsvp = insert_reservation(&mut tx, rsvp, None)
+                .await
+                .map_err(|e| e.in_batch(i))?;
+        }
+        tx.commit().await?;
+        Ok(rsvps)
+    }
 
-        let timespan = rsvp.get_time_span();
+    async fn reserve_series(
+        &self,
+        rsvp: abi::Reservation,
+        rrule: abi::RRule,
+        mode: abi::SeriesConflictMode,
+    ) -> Result<abi::ReserveSeriesResponse, abi::Error> {
+        rsvp.validate()?;
+        let start = convert_to_utc_time(rsvp.start.as_ref().unwrap());
+        let duration = convert_to_utc_time(rsvp.end.as_ref().unwrap()) - start;
 
-        let status = abi::ReservationStatus::from_i32(rsvp.status)
-            .unwrap_or(abi::ReservationStatus::Pending);
-        info!("timespan: {:?}", timespan);
-        // generate a insert sql for the reservation
-        let id = sqlx::query(
+        let mut tx = self.begin().await?;
+        // the rule is expanded in the timezone of the resource
+        let resource = lock_resource(&mut tx, &rsvp.resource_id).await?;
+        let occurrences = rrule.occurrences(start.with_timezone(&resource.tz()))?;
+        let series_id: i64 = sqlx::query_scalar(
             r#"
-            INSERT INTO rsvp.reservations (resource_id, user_id, timespan, note, status)
-            VALUES ($1, $2, $3, $4, $5::rsvp.reservation_status)
+            INSERT INTO rsvp.reservation_series (user_id, resource_id, rrule)
+            VALUES ($1, $2, $3)
             RETURNING id
             "#,
         )
-        .bind(rsvp.resource_id.clone())
-        .bind(rsvp.user_id.clone())
-        .bind(timespan)
-        .bind(rsvp.note.clone())
-        .bind(status.to_string())
-        .fetch_one(&self.pool)
-        .await?
-        .get(0);
-        rsvp.id = id;
-        Ok(rsvp)
+        .bind(&rsvp.user_id)
+        .bind(&rsvp.resource_id)
+        .bind(rrule.to_string())
+        .fetch_one(&mut tx)
+        .await?;
+
+        let mut reservations = vec![];
+        let mut skipped = vec![];
+        for (i, start) in occurrences.into_iter().enumerate() {
+            let occurrence = abi::Reservation {
+                start: Some(convert_to_timestamp(start)),
+                end: Some(convert_to_timestamp(start + duration)),
+                series_id,
+                ..rsvp.clone()
+            };
+            // each occurrence is inserted in a savepoint, so a conflicting one could be skipped
+            let mut sp = Connection::begin(&mut *tx).await?;
+            match insert_reservation(&mut sp, &occurrence, Some(series_id)).await {
+                Ok(reserved) => {
+                    sp.commit().await?;
+                    reservations.push(reserved);
+                }
+                Err(abi::Error::ConflictReservation(_) | abi::Error::CapacityExceeded(..))
+                    if mode == abi::SeriesConflictMode::Skip =>
+                {
+                    sp.rollback().await?;
+                    skipped.push(occurrence);
+                }
+                Err(e) => return Err(e.in_batch(i)),
+            }
+        }
+        tx.commit().await?;
+
+        Ok(abi::ReserveSeriesResponse {
+            series_id,
+            reservations,
+            skipped,
+        })
+    }
+
+    async fn cancel_series(&self, id: ReservationId) -> Result<Vec<abi::Reservation>, abi::Error> {
+        id.validate()?;
+        let mut tx = self.begin().await?;
+        let rsvp = lock_reservation(&mut tx, id, None).await?;
+        rsvp.status()
+            .check_transition(abi::ReservationStatus::Cancelled)?;
+        // the following occurrences already cancelled are left as they are
+        let rsvps: Vec<abi::Reservation> = sqlx::query_as(
+            r#"
+            UPDATE rsvp.reservations r
+            SET status = 'cancelled', expires_at = NULL
+            FROM rsvp.reservations t
+            WHERE t.id = $1 AND (r.id = t.id OR
+                (r.series_id = t.series_id AND lower(r.timespan) >= lower(t.timespan)))
+                AND r.status NOT IN ('cancelled', 'blocked')
+            RETURNING r.*
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&mut tx)
+        .await?;
+        promote_waitlist(&mut tx, &rsvps).await?;
+        tx.commit().await?;
+        Ok(sort_by_start(rsvps))
+    }
+
+    async fn update_series(
+        &self,
+        id: ReservationId,
+        note: Option<String>,
+        shift: chrono::Duration,
+    ) -> Result<Vec<abi::Reservation>, abi::Error> {
+        id.validate()?;
+        let mut tx = self.begin().await?;
+        let rsvps: Vec<abi::Reservation> = sqlx::query_as(
+            r#"
+            UPDATE rsvp.reservations r
+            SET note = COALESCE($2, r.note)
+            FROM rsvp.reservations t
+            WHERE t.id = $1 AND (r.id = t.id OR
+                (r.series_id = t.series_id AND lower(r.timespan) >= lower(t.timespan)))
+            RETURNING r.*
+            "#,
+        )
+        .bind(id)
+        .bind(note)
+        .fetch_all(&mut tx)
+        .await?;
+        if rsvps.is_empty() {
+            return Err(abi::Error::NotFound);
+        }
+        let mut rsvps = sort_by_start(rsvps);
+        if shift.is_zero() {
+            tx.commit().await?;
+            return Ok(rsvps);
+        }
+        // moving later, the last occurrence goes first, so each one is moved to where the next
+        // one has left, and the other way round moving earlier
+        let mut order: Vec<usize> = (0..rsvps.len()).collect();
+        if shift > chrono::Duration::zero() {
+            order.reverse();
+        }
+        for i in order {
+            let rsvp = &rsvps[i];
+            if rsvp.status == abi::ReservationStatus::Cancelled as i32 {
+                continue;
+            }
+            let window = (
+                convert_to_utc_time(rsvp.start.as_ref().unwrap()) + shift,
+                convert_to_utc_time(rsvp.end.as_ref().unwrap()) + shift,
+            );
+            let resource = lock_resource(&mut tx, &rsvp.resource_id).await?;
+            let padded_timespan = check_window(&mut tx, rsvp, &resource, window, rsvp.id)
+                .await
+                .map_err(|e| e.in_batch(i))?;
+            let old_padded = padded_timespan_of(&mut tx, rsvp.id).await?;
+            rsvps[i] = sqlx::query_as(
+                r#"
+                UPDATE rsvp.reservations
+                SET timespan = $2, padded_timespan = $3, shared = $4
+                WHERE id = $1
+                RETURNING *
+                "#,
+            )
+            .bind(rsvp.id)
+            .bind(to_range(window))
+            .bind(padded_timespan)
+            .bind(resource.capacity > 1)
+            .fetch_one(&mut tx)
+            .await?;
+            promote_waitlist_in(&mut tx, &resource.id, &old_padded).await?;
+        }
+        tx.commit().await?;
+        Ok(rsvps)
     }
 
-    async fn change_status(&self, id: ReservationId) -> Result<abi::Reservation, abi::Error> {
-        // if current status is `pending`, then change to `confirmed` otherwise do nothing
+    async fn change_status(
+        &self,
+        id: ReservationId,
+        expected_version: Option<i64>,
+    ) -> Result<abi::Reservation, abi::Error> {
+        self.transition(id, abi::ReservationStatus::Confirmed, expected_version)
+            .await
+    }
+    async fn transition(
+        &self,
+        id: ReservationId,
+        to: abi::ReservationStatus,
+        expected_version: Option<i64>,
+    ) -> Result<abi::Reservation, abi::Error> {
         id.validate()?;
 
+        let mut tx = self.begin().await?;
+        let rsvp = lock_reservation(&mut tx, id, expected_version).await?;
+        rsvp.status().check_transition(to)?;
+        // only a pending reservation has a hold, an expired one could still be cancelled
+        let rsvp: Option<abi::Reservation> = sqlx::query_as(
+            r#"
+            UPDATE rsvp.reservations
+            SET status = $2::rsvp.reservation_status, expires_at = NULL
+            WHERE id = $1 AND ($2 = 'cancelled' OR expires_at IS NULL OR expires_at > now())
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(to.to_string())
+        .fetch_optional(&mut tx)
+        .await?;
+        // the reservation exists but is skipped above, so it is an expired hold
+        let rsvp = rsvp.ok_or(abi::Error::HoldExpired(id))?;
+        if to == abi::ReservationStatus::Cancelled {
+            promote_waitlist(&mut tx, std::slice::from_ref(&rsvp)).await?;
+        }
+        self.commit(tx, &rsvp).await?;
+        Ok(rsvp)
+    }
+    async fn update(
+        &self,
+        id: ReservationId,
+        patch: abi::Reservation,
+        paths: &[String],
+        expected_version: Option<i64>,
+    ) -> Result<abi::Reservation, abi::Error> {
+        id.validate()?;
+        let mut tx = self.begin().await?;
+        let old = lock_reservation(&mut tx, id, expected_version).await?;
+        let mut rsvp = old.clone();
+        rsvp.apply_update(&patch, paths)?;
+        // the window is checked again only if it is moved, the padded timespan is kept otherwise
+        let moved =
+            rsvp.resource_id != old.resource_id || rsvp.start != old.start || rsvp.end != old.end;
+        let (padded_timespan, shared) = if moved {
+            let resource = lock_resource(&mut tx, &rsvp.resource_id).await?;
+            let window = (
+                convert_to_utc_time(rsvp.start.as_ref().unwrap()),
+                convert_to_utc_time(rsvp.end.as_ref().unwrap()),
+            );
+            let padded_timespan = check_window(&mut tx, &rsvp, &resource, window, id).await?;
+            (Some(padded_timespan), Some(resource.capacity > 1))
+        } else {
+            (None, None)
+        };
+        let old_padded = padded_timespan_of(&mut tx, id).await?;
         let rsvp: abi::Reservation = sqlx::query_as(
             r#"
             UPDATE rsvp.reservations
-            SET status = CASE WHEN status = 'pending' THEN 'confirmed' ELSE status END
+            SET note = $2, user_id = $3, resource_id = $4, timespan = $5,
+                padded_timespan = COALESCE($6, padded_timespan), shared = COALESCE($7, shared)
             WHERE id = $1
             RETURNING *
             "#,
         )
         .bind(id)
-        .fetch_one(&self.pool)
+        .bind(&rsvp.note)
+        .bind(&rsvp.user_id)
+        .bind(&rsvp.resource_id)
+        .bind(rsvp.get_time_span())
+        .bind(padded_timespan)
+        .bind(shared)
+        .fetch_one(&mut tx)
         .await?;
+        if moved {
+            promote_waitlist_in(&mut tx, &old.resource_id, &old_padded).await?;
+        }
+        tx.commit().await?;
         Ok(rsvp)
     }
-    async fn update_note(
+    async fn reschedule(
         &self,
         id: ReservationId,
-        note: String,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        expected_version: Option<i64>,
     ) -> Result<abi::Reservation, abi::Error> {
         id.validate()?;
+        if start >= end {
+            return Err(abi::Error::InvalidTime);
+        }
+        let mut tx = self.begin().await?;
+        let rsvp = lock_reservation(&mut tx, id, expected_version).await?;
+        let resource = lock_resource(&mut tx, &rsvp.resource_id).await?;
+        let padded_timespan = check_window(&mut tx, &rsvp, &resource, (start, end), id).await?;
+        let old_padded = padded_timespan_of(&mut tx, id).await?;
         let rsvp: abi::Reservation = sqlx::query_as(
             r#"
             UPDATE rsvp.reservations
-            SET note = $2
+            SET timespan = $2, padded_timespan = $3, shared = $4
             WHERE id = $1
             RETURNING *
             "#,
         )
         .bind(id)
-        .bind(note)
-        .fetch_one(&self.pool)
+        .bind(to_range((start, end)))
+        .bind(padded_timespan)
+        .bind(resource.capacity > 1)
+        .fetch_one(&mut tx)
+        .await?;
+        promote_waitlist_in(&mut tx, &rsvp.resource_id, &old_padded).await?;
+        self.commit(tx, &rsvp).await?;
+        Ok(rsvp)
+    }
+    async fn availability(
+        &self,
+        resource_id: &str,
+        (start, end): (DateTime<Utc>, DateTime<Utc>),
+        min_duration: chrono::Duration,
+        with_buffers: bool,
+    ) -> Result<Vec<abi::TimeSlot>, abi::Error> {
+        let resource: Option<abi::Resource> =
+            sqlx::query_as("SELECT * FROM rsvp.resources WHERE id = $1")
+                .bind(resource_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        let resource = active_resource(resource_id, resource)?;
+        // a new reservation takes a slot only if its padded window doesn't overlap the padded
+        // windows of others, so others are widened by the buffers of the new one
+        let (before, after) = if with_buffers {
+            let (before, after) = resource.buffer_secs();
+            (
+                chrono::Duration::seconds(before),
+                chrono::Duration::seconds(after),
+            )
+        } else {
+            (chrono::Duration::zero(), chrono::Duration::zero())
+        };
+        let rows: Vec<(DateTime<Utc>, DateTime<Utc>, i64)> = sqlx::query_as(
+            r#"
+            SELECT lower(span) - $3::interval, upper(span) + $4::interval, units
+            FROM (
+                SELECT CASE WHEN $5 THEN padded_timespan ELSE timespan END AS span,
+                    CASE WHEN status = 'blocked' THEN $6::int8 ELSE 1 END AS units
+                FROM rsvp.reservations
+                WHERE resource_id = $1 AND status <> 'cancelled'
+            ) r
+            WHERE span && $2
+            "#,
+        )
+        .bind(resource_id)
+        .bind(to_range((start - before - after, end + before + after)))
+        .bind(after)
+        .bind(before)
+        .bind(with_buffers)
+        .bind(resource.capacity as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(abi::free_slots(
+            (start, end),
+            &rows,
+            resource.capacity as i64,
+            min_duration,
+        ))
+    }
+
+    async fn check_conflicts(
+        &self,
+        rsvp: &abi::Reservation,
+    ) -> Result<Vec<abi::Reservation>, abi::Error> {
+        rsvp.validate_window()?;
+        let mut conn = self.pool.acquire().await?;
+        let resource: Option<abi::Resource> =
+            sqlx::query_as("SELECT * FROM rsvp.resources WHERE id = $1")
+                .bind(&rsvp.resource_id)
+                .fetch_optional(&mut conn)
+                .await?;
+        let resource = active_resource(&rsvp.resource_id, resource)?;
+        let start = convert_to_utc_time(rsvp.start.as_ref().unwrap());
+        let end = convert_to_utc_time(rsvp.end.as_ref().unwrap());
+        // same overlap as reserve, with the buffers of the resource
+        let padded = resource.padded(start, end);
+        // a shared resource has no conflicts until the capacity is used up, as in reserve
+        if resource.capacity > 1 {
+            let timespan = to_range(padded);
+            match check_capacity(&mut conn, rsvp, &timespan, resource.capacity, rsvp.id).await {
+                Ok(()) => return Ok(vec![]),
+                Err(abi::Error::CapacityExceeded(..)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let overlapping = find_overlapping(&mut conn, &rsvp.resource_id, padded, rsvp.id).await?;
+        Ok(overlapping.into_iter().map(|(rsvp, _)| rsvp).collect())
+    }
+
+    async fn cancel(
+        &self,
+        id: ReservationId,
+        expected_version: Option<i64>,
+    ) -> Result<abi::Reservation, abi::Error> {
+        self.transition(id, abi::ReservationStatus::Cancelled, expected_version)
+            .await
+    }
+    async fn block_resource(
+        &self,
+        mut block: abi::Reservation,
+        mode: abi::BlockMode,
+    ) -> Result<abi::BlockResourceResponse, abi::Error> {
+        block.validate_window()?;
+        block.status = abi::ReservationStatus::Blocked as i32;
+        block.expires_at = None;
+
+        let mut tx = self.begin().await?;
+        // whatever is cancelled within the padded window no longer conflicts with the block
+        let resource = lock_resource(&mut tx, &block.resource_id).await?;
+        let padded = to_range(resource.padded(
+            convert_to_utc_time(block.start.as_ref().unwrap()),
+            convert_to_utc_time(block.end.as_ref().unwrap()),
+        ));
+        let cancelled = match mode {
+            abi::BlockMode::Reject => vec![],
+            abi::BlockMode::CancelPending => {
+                sqlx::query_as(
+                    r#"
+                    UPDATE rsvp.reservations
+                    SET status = 'cancelled', expires_at = NULL
+                    WHERE resource_id = $1 AND status = 'pending' AND padded_timespan && $2
+                    RETURNING *
+                    "#,
+                )
+                .bind(&block.resource_id)
+                .bind(&padded)
+                .fetch_all(&mut tx)
+                .await?
+            }
+            abi::BlockMode::Force => {
+                sqlx::query_as(
+                    r#"
+                    UPDATE rsvp.reservations
+                    SET status = 'cancelled', expires_at = NULL
+                    WHERE resource_id = $1 AND status NOT IN ('cancelled', 'blocked')
+                        AND padded_timespan && $2
+                    RETURNING *
+                    "#,
+                )
+                .bind(&block.resource_id)
+                .bind(&padded)
+                .fetch_all(&mut tx)
+                .await?
+            }
+        };
+        // other blocks, and confirmed reservations unless forced, still conflict with the block
+        let block = insert_reservation(&mut tx, &block, None).await?;
+        tx.commit().await?;
+        Ok(abi::BlockResourceResponse {
+            block: Some(block),
+            cancelled: sort_by_start(cancelled),
+        })
+    }
+
+    async fn unblock_resource(&self, id: ReservationId) -> Result<abi::Reservation, abi::Error> {
+        id.validate()?;
+        let mut tx = self.begin().await?;
+        let rsvp: abi::Reservation = sqlx::query_as(
+            r#"
+            UPDATE rsvp.reservations
+            SET status = 'cancelled'
+            WHERE id = $1 AND status = 'blocked'
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&mut tx)
         .await?;
+        promote_waitlist(&mut tx, std::slice::from_ref(&rsvp)).await?;
+        tx.commit().await?;
         Ok(rsvp)
     }
+
     /// 删除并返回old row
     async fn delete(&self, id: ReservationId) -> Result<abi::Reservation, abi::Error> {
         id.validate()?;
+        let mut tx = self.begin().await?;
         let rsvp: abi::Reservation = sqlx::query_as(
             r#"
             DELETE FROM rsvp.reservations
@@ -86,8 +578,9 @@ impl Rsvp for ReservationManager {
             "#,
         )
         .bind(id)
-        .fetch_one(&self.pool)
+        .fetch_one(&mut tx)
         .await?;
+        tx.commit().await?;
         Ok(rsvp)
     }
     async fn get(&self, id: ReservationId) -> Result<abi::Reservation, abi::Error> {
@@ -104,6 +597,28 @@ impl Rsvp for ReservationManager {
         Ok(rsvp)
     }
 
+    async fn history(&self, id: ReservationId) -> Result<Vec<abi::ReservationChange>, abi::Error> {
+        id.validate()?;
+        // one row per JSONB column that is set, the old one first
+        let changes: Vec<abi::ReservationChange> = sqlx::query_as(
+            r#"
+            SELECT c.id::int8 AS change_id, c.op, c.created_at, c.actor, s.side, r.*
+            FROM rsvp.reservation_changes c
+            CROSS JOIN LATERAL (VALUES ('old', c.old), ('new', c.new)) s(side, data)
+            CROSS JOIN LATERAL jsonb_populate_record(NULL::rsvp.reservations, s.data) r
+            WHERE c.reservation_id = $1 AND s.data IS NOT NULL
+            ORDER BY c.id, s.side DESC
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+        if changes.is_empty() {
+            return Err(abi::Error::NotFound);
+        }
+        Ok(abi::ReservationChange::merge(changes))
+    }
+
     async fn query(
         &self,
         query: abi::ReservationQuery,
@@ -112,8 +627,13 @@ impl Rsvp for ReservationManager {
         let (tx, rx) = mpsc::channel(128);
 
         tokio::spawn(async move {
-            let sql = query.to_sql();
-            let mut rsvps = sqlx::query_as(&sql).fetch_many(&pool);
+            if let Err(e) = check_as_of(&pool, query.as_of.as_ref().map(convert_to_utc_time)).await
+            {
+                let _ = tx.send(Err(e)).await;
+                return;
+            }
+            let mut builder = query.to_query().into_builder();
+            let mut rsvps = builder.build_query_as().fetch_many(&pool);
 
             while let Some(ret) = rsvps.next().await {
                 match ret {
@@ -144,18 +664,519 @@ impl Rsvp for ReservationManager {
         mut filter: abi::ReservationFilter,
     ) -> Result<(FilterPager, Vec<abi::Reservation>), abi::Error> {
         filter.normalize()?;
+        check_as_of(&self.pool, filter.as_of.as_ref().map(convert_to_utc_time)).await?;
 
-        let sql = filter.to_sql();
-        let rsvps: Vec<abi::Reservation> = sqlx::query_as(&sql).fetch_all(&self.pool).await?;
+        let rsvps: Vec<abi::Reservation> = filter
+            .to_query()
+            .into_builder()
+            .build_query_as()
+            .fetch_all(&self.pool)
+            .await?;
         let mut rsvps = rsvps.into_iter().collect();
 
         let pager = filter.get_pager(&mut rsvps);
         Ok((pager, rsvps.into_iter().collect()))
     }
+
+    async fn release_expired_holds(&self) -> Result<Vec<abi::Reservation>, abi::Error> {
+        // the status change is recorded by the reservation trigger, so listeners get a change event
+        let mut tx = self.begin().await?;
+        let rsvps = sqlx::query_as(
+            r#"
+            UPDATE rsvp.reservations
+            SET status = 'cancelled'
+            WHERE status = 'pending' AND expires_at <= now()
+            RETURNING *
+            "#,
+        )
+        .fetch_all(&mut tx)
+        .await?;
+        promote_waitlist(&mut tx, &rsvps).await?;
+        tx.commit().await?;
+        Ok(rsvps)
+    }
+
+    async fn listen(
+        &self,
+        request: abi::ListenRequest,
+    ) -> Result<mpsc::Receiver<Result<abi::ListenResponse, abi::Error>>, abi::Error> {
+        request.validate()?;
+        // start listening before reading the last change, so no change is lost in between
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener.listen(RESERVATION_UPDATE_CHANNEL).await?;
+        let mut last = start_position(&self.pool, &request).await?;
+
+        let pool = self.pool.clone();
+        let (tx, rx) = mpsc::channel(128);
+
+        tokio::spawn(async move {
+            // replay the missed changes first, then send the new ones on every notification. The
+            // changes held back by a running transaction are polled for, its commit may not notify
+            while send_changes(&pool, &tx, &request, &mut last).await {
+                if let Ok(Err(e)) =
+                    tokio::time::timeout(CHANGE_POLL_INTERVAL, listener.recv()).await
+                {
+                    warn!("Failed to receive reservation notification: {}", e);
+                    let _ = tx.send(Err(e.into())).await;
+                    break;
+                }
+            }
+        });
+        Ok(rx)
+    }
+
+    async fn ack(&self, request: abi::AckRequest) -> Result<(), abi::Error> {
+        request.validate()?;
+        let found: Option<i64> =
+            sqlx::query_scalar("SELECT id::int8 FROM rsvp.reservation_changes WHERE id = $1")
+                .bind(request.change_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        if found.is_none() {
+            return Err(abi::Error::NotFound);
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO rsvp.server_read_cursor (server_id, last_change_id)
+            VALUES ($1, $2)
+            ON CONFLICT (server_id) DO UPDATE SET last_change_id = EXCLUDED.last_change_id
+            "#,
+        )
+        .bind(&request.consumer_id)
+        .bind(request.change_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+/// insert a validated reservation, optionally as an occurrence of a series, return its id
+pub(crate) async fn insert_reservation(
+    conn: &mut PgConnection,
+    rsvp: &abi::Reservation,
+    series_id: Option<i64>,
+) -> Result<abi::Reservation, abi::Error> {
+    let timespan = rsvp.get_time_span();
+    let start = convert_to_utc_time(rsvp.start.as_ref().unwrap());
+    let end = convert_to_utc_time(rsvp.end.as_ref().unwrap());
+    let resource = lock_resource(conn, &rsvp.resource_id).await?;
+    let padded_timespan = check_window(conn, rsvp, &resource, (start, end), rsvp.id).await?;
+    let shared = resource.capacity > 1;
+
+    let status =
+        abi::ReservationStatus::from_i32(rsvp.status).unwrap_or(abi::ReservationStatus::Pending);
+    info!("timespan: {:?}", timespan);
+    // generate a insert sql for the reservation
+    let rsvp = sqlx::query_as(
+        r#"
+        INSERT INTO rsvp.reservations (resource_id, user_id, timespan, note, status, series_id, expires_at, shared, padded_timespan)
+        VALUES ($1, $2, $3, $4, $5::rsvp.reservation_status, $6, $7, $8, $9)
+        RETURNING *
+        "#,
+    )
+    .bind(rsvp.resource_id.clone())
+    .bind(rsvp.user_id.clone())
+    .bind(timespan)
+    .bind(rsvp.note.clone())
+    .bind(status.to_string())
+    .bind(series_id)
+    .bind(rsvp.expires_at.as_ref().map(convert_to_utc_time))
+    .bind(shared)
+    .bind(padded_timespan)
+    .fetch_one(conn)
+    .await?;
+    Ok(rsvp)
+}
+
+/// lock the reservation row until the transaction ends, fail if it is not at the expected version
+async fn lock_reservation(
+    conn: &mut PgConnection,
+    id: ReservationId,
+    expected_version: Option<i64>,
+) -> Result<abi::Reservation, abi::Error> {
+    let rsvp: abi::Reservation =
+        sqlx::query_as("SELECT * FROM rsvp.reservations WHERE id = $1 FOR UPDATE")
+            .bind(id)
+            .fetch_one(conn)
+            .await?;
+    rsvp.check_version(expected_version)?;
+    Ok(rsvp)
+}
+
+/// lock the resource row until the transaction ends, fail if the resource is unknown or retired
+async fn lock_resource(
+    conn: &mut PgConnection,
+    resource_id: &str,
+) -> Result<abi::Resource, abi::Error> {
+    let resource: Option<abi::Resource> =
+        sqlx::query_as("SELECT * FROM rsvp.resources WHERE id = $1 FOR UPDATE")
+            .bind(resource_id)
+            .fetch_optional(conn)
+            .await?;
+    active_resource(resource_id, resource)
+}
+
+/// fail if the resource is unknown or retired
+pub(crate) fn active_resource(
+    resource_id: &str,
+    resource: Option<abi::Resource>,
+) -> Result<abi::Resource, abi::Error> {
+    match resource {
+        None => Err(abi::Error::ResourceNotFound(resource_id.to_string())),
+        Some(resource) if !resource.active => {
+            Err(abi::Error::ResourceRetired(resource_id.to_string()))
+        }
+        Some(resource) => Ok(resource),
+    }
+}
+
+/// check the reservation could take the `window` of the locked resource, the reservation with
+/// `exclude_id` is not counted. Return the window padded with the buffers of the resource.
+async fn check_window(
+    conn: &mut PgConnection,
+    rsvp: &abi::Reservation,
+    resource: &abi::Resource,
+    window: (DateTime<Utc>, DateTime<Utc>),
+    exclude_id: i64,
+) -> Result<PgRange<DateTime<Utc>>, abi::Error> {
+    // blocks are made by operators, they are not restricted by the booking policy
+    if rsvp.status != abi::ReservationStatus::Blocked as i32 {
+        resource.check_policy(window.0, window.1, Utc::now())?;
+    }
+    let padded = resource.padded(window.0, window.1);
+    let padded_timespan = to_range(padded);
+    // reservations for a resource with capacity are shared, they are checked against the
+    // capacity instead of the reservations_conflict constraint
+    if resource.capacity > 1 {
+        check_capacity(conn, rsvp, &padded_timespan, resource.capacity, exclude_id).await?;
+    } else {
+        check_conflict(conn, &rsvp.resource_id, window, padded, exclude_id).await?;
+    }
+    Ok(padded_timespan)
+}
+
+/// fail if the padded window overlaps another reservation's. The reservations_conflict constraint
+/// rejects it as well, but its message only has the padded windows, not the booked ones, and it
+/// does not cover reservations shared while the resource had a larger capacity.
+async fn check_conflict(
+    conn: &mut PgConnection,
+    resource_id: &str,
+    window: (DateTime<Utc>, DateTime<Utc>),
+    padded: (DateTime<Utc>, DateTime<Utc>),
+    exclude_id: i64,
+) -> Result<(), abi::Error> {
+    let overlapping = find_overlapping(conn, resource_id, padded, exclude_id).await?;
+    match overlapping.into_iter().next() {
+        None => Ok(()),
+        Some((old, old_padded)) => Err(abi::Error::ConflictReservation(
+            abi::ReservationConflictInfo::Parsed(Box::new(abi::ReservationConflict {
+                new: abi::ReservationWindow::new(resource_id, window, padded),
+                old: abi::ReservationWindow::new(
+                    resource_id,
+                    (
+                        convert_to_utc_time(old.start.as_ref().unwrap()),
+                        convert_to_utc_time(old.end.as_ref().unwrap()),
+                    ),
+                    old_padded,
+                ),
+            })),
+        )),
+    }
+}
+
+/// reservations whose padded window overlaps the given one, with their padded windows, ordered
+/// by start. Cancelled reservations and the one with `exclude_id` are left out.
+async fn find_overlapping(
+    conn: &mut PgConnection,
+    resource_id: &str,
+    padded: (DateTime<Utc>, DateTime<Utc>),
+    exclude_id: i64,
+) -> Result<Vec<(abi::Reservation, (DateTime<Utc>, DateTime<Utc>))>, abi::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT *, lower(padded_timespan) AS padded_start, upper(padded_timespan) AS padded_end
+        FROM rsvp.reservations
+        WHERE resource_id = $1 AND padded_timespan && $2 AND status <> 'cancelled' AND id <> $3
+        ORDER BY lower(padded_timespan), id
+        "#,
+    )
+    .bind(resource_id)
+    .bind(to_range(padded))
+    .bind(exclude_id)
+    .fetch_all(conn)
+    .await?;
+    rows.iter()
+        .map(|row| {
+            let padded = (row.get("padded_start"), row.get("padded_end"));
+            Ok((abi::Reservation::from_row(row)?, padded))
+        })
+        .collect()
+}
+
+/// check the reservation fits in the capacity left in the given padded timespan, the reservation
+/// with `exclude_id` is not counted. A block takes the whole capacity.
+async fn check_capacity(
+    conn: &mut PgConnection,
+    rsvp: &abi::Reservation,
+    timespan: &PgRange<DateTime<Utc>>,
+    capacity: i32,
+    exclude_id: i64,
+) -> Result<(), abi::Error> {
+    let peak = peak_usage(conn, &rsvp.resource_id, timespan, capacity, exclude_id).await?;
+    let left = (capacity as i64 - peak).max(0) as i32;
+    let units = if rsvp.status == abi::ReservationStatus::Blocked as i32 {
+        capacity
+    } else {
+        1
+    };
+    if left < units {
+        return Err(abi::Error::CapacityExceeded(
+            rsvp.resource_id.clone(),
+            capacity,
+            left,
+        ));
+    }
+    Ok(())
+}
+
+/// the most units of the resource used at once within the padded timespan, a block takes the
+/// whole `capacity`. The reservation with `exclude_id` is not counted
+pub(crate) async fn peak_usage(
+    conn: &mut PgConnection,
+    resource_id: &str,
+    timespan: &PgRange<DateTime<Utc>>,
+    capacity: i32,
+    exclude_id: i64,
+) -> Result<i64, abi::Error> {
+    // ends sort before starts at the same time as ranges are [)
+    let peak = sqlx::query_scalar(
+        r#"
+        WITH usage AS (
+            SELECT padded_timespan * $2 AS span,
+                CASE WHEN status = 'blocked' THEN $3::int8 ELSE 1 END AS units
+            FROM rsvp.reservations
+            WHERE resource_id = $1 AND status <> 'cancelled' AND padded_timespan && $2 AND id <> $4
+        ), edges AS (
+            SELECT lower(span) AS at, units AS delta FROM usage
+            UNION ALL
+            SELECT upper(span) AS at, -units AS delta FROM usage
+        )
+        SELECT COALESCE(max(used), 0)::int8 FROM (
+            SELECT sum(delta) OVER (ORDER BY at, delta) AS used FROM edges
+        ) t
+        "#,
+    )
+    .bind(resource_id)
+    .bind(timespan)
+    .bind(capacity as i64)
+    .bind(exclude_id)
+    .fetch_one(conn)
+    .await?;
+    Ok(peak)
+}
+
+/// fail if the reservations are asked for as of a time before the change log has every change
+async fn check_as_of(pool: &PgPool, as_of: Option<DateTime<Utc>>) -> Result<(), abi::Error> {
+    let as_of = match as_of {
+        Some(as_of) => as_of,
+        None => return Ok(()),
+    };
+    let started_at: DateTime<Utc> =
+        sqlx::query_scalar("SELECT started_at FROM rsvp.change_log_start")
+            .fetch_one(pool)
+            .await?;
+    if as_of < started_at {
+        return Err(abi::Error::AsOfBeforeChangeLog(started_at.to_rfc3339()));
+    }
+    Ok(())
+}
+
+/// the padded window a reservation takes now
+async fn padded_timespan_of(
+    conn: &mut PgConnection,
+    id: ReservationId,
+) -> Result<PgRange<DateTime<Utc>>, abi::Error> {
+    let padded = sqlx::query_scalar("SELECT padded_timespan FROM rsvp.reservations WHERE id = $1")
+        .bind(id)
+        .fetch_one(conn)
+        .await?;
+    Ok(padded)
+}
+
+fn to_range((start, end): (DateTime<Utc>, DateTime<Utc>)) -> PgRange<DateTime<Utc>> {
+    PgRange {
+        start: Bound::Included(start),
+        end: Bound::Excluded(end),
+    }
+}
+
+fn sort_by_start(mut rsvps: Vec<abi::Reservation>) -> Vec<abi::Reservation> {
+    rsvps.sort_by_key(|r| r.start.as_ref().map(|ts| (ts.seconds, ts.nanos)));
+    rsvps
+}
+
+/// bytes of the user id kept as the actor, the `actor` column of the change log holds 64
+/// characters and a character takes at least a byte in any database encoding
+const MAX_ACTOR_LEN: usize = 64;
+const RESERVATION_UPDATE_CHANNEL: &str = "reservation_update";
+const CHANGE_BATCH_SIZE: i64 = 128;
+const CHANGE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// where a change is in commit order: the id of the transaction that made it, then its own id.
+/// Change ids are taken before commit, so a change could become visible after one with a larger
+/// id. Only the changes of transactions older than any running one are read, the changes seen
+/// later are made by newer transactions and come after them.
+type ChangePosition = (i64, i64);
+
+/// the position to listen after: the requested change, the change last acknowledged by the
+/// consumer, or the latest change if neither exists
+async fn start_position(
+    pool: &PgPool,
+    request: &abi::ListenRequest,
+) -> Result<ChangePosition, abi::Error> {
+    let mut change_id = request.after_change_id;
+    if change_id.is_none() && !request.consumer_id.is_empty() {
+        change_id = sqlx::query_scalar(
+            "SELECT last_change_id FROM rsvp.server_read_cursor WHERE server_id = $1",
+        )
+        .bind(&request.consumer_id)
+        .fetch_optional(pool)
+        .await?;
+    }
+    let position = match change_id {
+        // nothing is before the first change
+        Some(0) => None,
+        Some(id) => {
+            let position = sqlx::query_as(
+                "SELECT xid::text::int8, id::int8 FROM rsvp.reservation_changes WHERE id = $1",
+            )
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+            Some(position.ok_or(abi::Error::InvalidCursor(id))?)
+        }
+        None => {
+            sqlx::query_as(
+                r#"
+                SELECT xid::text::int8, id::int8 FROM rsvp.reservation_changes
+                WHERE xid < pg_snapshot_xmin(pg_current_snapshot())
+                ORDER BY xid DESC, id DESC
+                LIMIT 1
+                "#,
+            )
+            .fetch_optional(pool)
+            .await?
+        }
+    };
+    Ok(position.unwrap_or((0, 0)))
+}
+
+/// send the changes after `last` matching the request until the listener catches up,
+/// return false if the listener is closed or the changes could not be fetched
+async fn send_changes(
+    pool: &PgPool,
+    tx: &mpsc::Sender<Result<abi::ListenResponse, abi::Error>>,
+    request: &abi::ListenRequest,
+    last: &mut ChangePosition,
+) -> bool {
+    loop {
+        if tx.is_closed() {
+            info!("Listener closed, stop sending reservation changes");
+            return false;
+        }
+        let changes = match fetch_changes(pool, *last).await {
+            Ok(changes) => changes,
+            Err(e) => {
+                warn!("Failed to fetch reservation changes: {}", e);
+                let _ = tx.send(Err(e)).await;
+                return false;
+            }
+        };
+        let caught_up = (changes.len() as i64) < CHANGE_BATCH_SIZE;
+        for (position, change) in changes {
+            // the filtered out changes are still counted as read
+            if request.matches(&change) && tx.send(Ok(change)).await.is_err() {
+                info!("Listener closed, stop sending reservation changes");
+                return false;
+            }
+            *last = position;
+        }
+        if caught_up {
+            return true;
+        }
+    }
+}
+
+/// fetch a batch of the committed reservation changes after the given position, in commit order
+async fn fetch_changes(
+    pool: &PgPool,
+    after: ChangePosition,
+) -> Result<Vec<(ChangePosition, abi::ListenResponse)>, abi::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT c.xid::text::int8 AS xid, c.id::int8 AS change_id, c.op, c.created_at, c.actor,
+            s.side, r.*
+        FROM (
+            SELECT * FROM rsvp.reservation_changes
+            WHERE (xid, id) > ($1::text::xid8, $2)
+                AND xid < pg_snapshot_xmin(pg_current_snapshot())
+            ORDER BY xid, id
+            LIMIT $3
+        ) c,
+            LATERAL (VALUES ('new', c.new), ('old', c.old)) s(side, record),
+            jsonb_populate_record(NULL::rsvp.reservations, s.record) r
+        WHERE s.record IS NOT NULL
+        ORDER BY c.xid, c.id, s.side
+        "#,
+    )
+    .bind(after.0)
+    .bind(after.1)
+    .bind(CHANGE_BATCH_SIZE)
+    .fetch_all(pool)
+    .await?;
+    let mut changes: Vec<(ChangePosition, abi::ListenResponse)> = Vec::with_capacity(rows.len());
+    for row in &rows {
+        let change = abi::ListenResponse::from_row(row)?;
+        match changes.last_mut() {
+            Some((_, last)) if last.change_id == change.change_id => last.merge(change),
+            _ => changes.push(((row.try_get("xid")?, change.change_id), change)),
+        }
+    }
+    Ok(changes)
 }
+
 impl ReservationManager {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            actor: String::new(),
+            idempotency_key: None,
+        }
+    }
+    /// a manager sharing the pool whose changes are recorded as made by the given user, the user id
+    /// is cut to the length the change log keeps
+    pub fn with_actor(&self, actor: impl Into<String>) -> Self {
+        let mut actor = actor.into();
+        if actor.len() > MAX_ACTOR_LEN {
+            let mut end = MAX_ACTOR_LEN;
+            while !actor.is_char_boundary(end) {
+                end -= 1;
+            }
+            actor.truncate(end);
+        }
+        Self {
+            actor,
+            ..self.clone()
+        }
+    }
+    /// a manager saving the key with the reservation made by `reserve`, `change_status`,
+    /// `reschedule` or `cancel`, in the transaction of the change. A retry with the key gets the
+    /// saved reservation from `saved_reservation` instead of making the change again
+    pub fn with_idempotency_key(&self, key: IdempotencyKey) -> Self {
+        Self {
+            idempotency_key: Some(key),
+            ..self.clone()
+        }
     }
     pub async fn from_config(config: &DbConfig) -> Result<Self, abi::Error> {
         let pool = PgPoolOptions::default()
@@ -164,25 +1185,88 @@ impl ReservationManager {
             .await?;
         Ok(Self::new(pool))
     }
+
+    /// begin a transaction, the changes made in it are recorded as made by the actor
+    async fn begin(&self) -> Result<Transaction<'static, Postgres>, abi::Error> {
+        let mut tx = self.pool.begin().await?;
+        if !self.actor.is_empty() {
+            // local to the transaction, so the pooled connection doesn't keep it
+            sqlx::query("SELECT set_config('rsvp.actor', $1, true)")
+                .bind(&self.actor)
+                .execute(&mut tx)
+                .await?;
+        }
+        Ok(tx)
+    }
+
+    /// commit a transaction, saving the idempotency key with the reservation in it if any. The
+    /// transaction is rolled back if a request with the same key has saved it meanwhile
+    async fn commit(
+        &self,
+        mut tx: Transaction<'static, Postgres>,
+        rsvp: &abi::Reservation,
+    ) -> Result<(), abi::Error> {
+        if let Some(key) = &self.idempotency_key {
+            // waits for a transaction saving the same key to end
+            let saved = sqlx::query(
+                r#"
+                INSERT INTO rsvp.idempotency_keys (caller, operation, key, request_hash, response)
+                VALUES ($1, $2, $3, sha256($4), $5)
+                ON CONFLICT (caller, operation, key) DO NOTHING
+                "#,
+            )
+            .bind(&key.caller)
+            .bind(&key.operation)
+            .bind(&key.key)
+            .bind(&key.request)
+            .bind(rsvp.encode_to_vec())
+            .execute(&mut tx)
+            .await?;
+            if saved.rows_affected() == 0 {
+                return Err(abi::Error::IdempotencyKeyInProgress(key.key.clone()));
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// spawn a background task releasing the expired holds every `interval`
+    pub fn spawn_hold_reaper(&self, interval: Duration) -> JoinHandle<()> {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match manager.release_expired_holds().await {
+                    Ok(rsvps) if !rsvps.is_empty() => {
+                        info!("released {} expired holds", rsvps.len())
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!("failed to release expired holds: {:?}", e),
+                }
+            }
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use abi::{
-        Reservation, ReservationConflict, ReservationConflictInfo, ReservationFilterBuilder,
-        ReservationQueryBuilder, ReservationWindow,
+        BlockMode, ListenRequest, Reservation, ReservationConflict, ReservationConflictInfo,
+        ReservationFilterBuilder, ReservationQueryBuilder, ReservationUpdateType,
+        ReservationWindow, SeriesConflictMode,
     };
     use prost_types::Timestamp;
     use sqlx_db_tester::TestDb;
 
     use super::*;
+    use crate::ResourceRegistry;
     #[tokio::test]
     async fn reserve_should_work_for_valid_window() {
         let tdb = get_db();
-        let pool = tdb.get_pool().await;
+        let pool = get_pool(&tdb).await;
         let (rsvp, _) = make_ssk_reservation(pool).await;
-        println!("{:?}", rsvp);
         assert_eq!(rsvp.resource_id, "ocean-view-room-713");
         assert_eq!(rsvp.user_id, "sskid");
         assert_eq!(rsvp.id, 1);
@@ -191,7 +1275,7 @@ mod tests {
     #[tokio::test]
     async fn reserve_conflict_reservation_should_reject() {
         let tdb = get_db();
-        let pool = tdb.get_pool().await;
+        let pool = get_pool(&tdb).await;
         let (rsvp, manager) = make_ssk_reservation(pool).await;
         let rsvp2 = abi::Reservation::new_pending(
             "aliceid",
@@ -200,84 +1284,1204 @@ mod tests {
             "2022-12-27T12:00:00-0700".parse().unwrap(),
             "I'll arrive at 3pm. Please help to upgrade to execuitive room if possible.",
         );
-        println!("rsvp: {:?}", rsvp);
+        assert_eq!(rsvp.status, abi::ReservationStatus::Pending as i32);
         let err = manager.reserve(rsvp2).await.unwrap_err();
-        println!("err {:?}", err);
-        let info = ReservationConflictInfo::Parsed(ReservationConflict {
-            new: ReservationWindow {
-                rid: "ocean-view-room-713".to_string(),
-                start: "2022-12-25T15:00:00-0700".parse().unwrap(),
-                end: "2022-12-27T12:00:00-0700".parse().unwrap(),
-            },
-            old: ReservationWindow {
-                rid: "ocean-view-room-713".to_string(),
-                start: "2022-12-25T15:00:00-0700".parse().unwrap(),
-                end: "2022-12-28T12:00:00-0700".parse().unwrap(),
-            },
-        });
-        println!("info {:?}", info);
+        let info = ReservationConflictInfo::Parsed(Box::new(ReservationConflict {
+            new: window(
+                "ocean-view-room-713",
+                "2022-12-25T15:00:00-0700",
+                "2022-12-27T12:00:00-0700",
+            ),
+            old: window(
+                "ocean-view-room-713",
+                "2022-12-25T15:00:00-0700",
+                "2022-12-28T12:00:00-0700",
+            ),
+        }));
         assert_eq!(err, abi::Error::ConflictReservation(info));
     }
 
     #[tokio::test]
-    async fn change_status_should_work() {
+    async fn reserve_many_should_work() {
         let tdb = get_db();
-        let pool = tdb.get_pool().await;
-        let (rsvp, manager) = make_alice_reservation(pool).await;
-        println!("rsvp: {:?}", rsvp);
-        let id = rsvp.id;
-        let rsvp = manager.change_status(id).await.unwrap();
+        let pool = get_pool(&tdb).await;
+        let manager = ReservationManager::new(pool);
+        let rsvps = manager
+            .reserve_many(vec![
+                make_meeting_reservation("meeting-room-1"),
+                make_meeting_reservation("projector-1"),
+                make_meeting_reservation("parking-spot-1"),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(
+            rsvps.iter().map(|r| r.id).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(manager.get(2).await.unwrap(), rsvps[1]);
+    }
+
+    #[tokio::test]
+    async fn reserve_many_conflict_reservation_should_rollback() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let (rsvp, manager) = make_reservation(
+            pool,
+            "sskid",
+            "projector-1",
+            "2023-01-10T08:00:00-0700",
+            "2023-01-10T12:00:00-0700",
+            "",
+        )
+        .await;
+        let err = manager
+            .reserve_many(vec![
+                make_meeting_reservation("meeting-room-1"),
+                make_meeting_reservation("projector-1"),
+                make_meeting_reservation("parking-spot-1"),
+            ])
+            .await
+            .unwrap_err();
+        let info = ReservationConflictInfo::Parsed(Box::new(ReservationConflict {
+            new: window(
+                "projector-1",
+                "2023-01-10T10:00:00-0700",
+                "2023-01-10T11:00:00-0700",
+            ),
+            old: window(
+                "projector-1",
+                "2023-01-10T08:00:00-0700",
+                "2023-01-10T12:00:00-0700",
+            ),
+        }));
+        assert_eq!(err, abi::Error::ConflictBatchReservation(1, info));
+
+        // nothing in the batch should be reserved
+        let filter = ReservationFilterBuilder::default()
+            .user_id("aliceid")
+            .build()
+            .unwrap();
+        let (_, rsvps) = manager.filter(filter).await.unwrap();
+        assert!(rsvps.is_empty());
+        assert_eq!(manager.get(rsvp.id).await.unwrap(), rsvp);
+    }
+
+    #[tokio::test]
+    async fn reserve_many_conflict_in_batch_should_reject() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let manager = ReservationManager::new(pool);
+        let err = manager
+            .reserve_many(vec![
+                make_meeting_reservation("meeting-room-1"),
+                make_meeting_reservation("meeting-room-1"),
+            ])
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            abi::Error::ConflictBatchReservation(1, ReservationConflictInfo::Parsed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn reserve_series_should_work() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let manager = ReservationManager::new(pool);
+        let rrule = "FREQ=WEEKLY;BYDAY=TU;COUNT=3".parse().unwrap();
+        let ret = manager
+            .reserve_series(
+                make_meeting_reservation("meeting-room-1"),
+                rrule,
+                SeriesConflictMode::Fail,
+            )
+            .await
+            .unwrap();
+        assert_eq!(ret.series_id, 1);
+        assert!(ret.skipped.is_empty());
+        let starts: Vec<_> = ret
+            .reservations
+            .iter()
+            .map(|r| r.start.clone().unwrap())
+            .collect();
+        assert_eq!(
+            starts,
+            vec![
+                "2023-01-10T10:00:00-0700".parse::<Timestamp>().unwrap(),
+                "2023-01-17T10:00:00-0700".parse::<Timestamp>().unwrap(),
+                "2023-01-24T10:00:00-0700".parse::<Timestamp>().unwrap(),
+            ]
+        );
+        let r = manager.get(ret.reservations[2].id).await.unwrap();
+        assert_eq!(r, ret.reservations[2]);
+        assert_eq!(r.series_id, 1);
+        assert_eq!(r.end.unwrap(), "2023-01-24T11:00:00-0700".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn reserve_series_should_expand_in_resource_timezone() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        let resource = abi::Resource {
+            timezone: "America/Denver".to_string(),
+            ..abi::Resource::new("meeting-room-1", "meeting-room-1", "test")
+        };
+        manager
+            .update_resource(resource, &paths(["timezone"]))
+            .await
+            .unwrap();
+        let rsvp = Reservation::new_pending(
+            "aliceid",
+            "meeting-room-1",
+            "2023-03-10T10:00:00-0700".parse().unwrap(),
+            "2023-03-10T11:00:00-0700".parse().unwrap(),
+            "",
+        );
+        let rrule = "FREQ=DAILY;COUNT=3".parse().unwrap();
+        let ret = manager
+            .reserve_series(rsvp, rrule, SeriesConflictMode::Fail)
+            .await
+            .unwrap();
+        // daylight saving starts on 2023-03-12, the meeting stays at 10:00 in Denver
+        let starts: Vec<_> = ret
+            .reservations
+            .iter()
+            .map(|r| r.start.clone().unwrap())
+            .collect();
+        assert_eq!(
+            starts,
+            vec![
+                "2023-03-10T10:00:00-0700".parse::<Timestamp>().unwrap(),
+                "2023-03-11T10:00:00-0700".parse::<Timestamp>().unwrap(),
+                "2023-03-12T10:00:00-0600".parse::<Timestamp>().unwrap(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn reserve_series_conflict_should_skip_or_fail() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let (_, manager) = make_reservation(
+            pool,
+            "sskid",
+            "meeting-room-1",
+            "2023-01-17T08:00:00-0700",
+            "2023-01-17T12:00:00-0700",
+            "",
+        )
+        .await;
+        let rrule: abi::RRule = "FREQ=WEEKLY;COUNT=3".parse().unwrap();
+
+        let err = manager
+            .reserve_series(
+                make_meeting_reservation("meeting-room-1"),
+                rrule.clone(),
+                SeriesConflictMode::Fail,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, abi::Error::ConflictBatchReservation(1, _)));
+
+        let ret = manager
+            .reserve_series(
+                make_meeting_reservation("meeting-room-1"),
+                rrule,
+                SeriesConflictMode::Skip,
+            )
+            .await
+            .unwrap();
+        assert_eq!(ret.reservations.len(), 2);
+        assert_eq!(ret.skipped.len(), 1);
+        assert_eq!(
+            ret.skipped[0].start,
+            Some("2023-01-17T10:00:00-0700".parse().unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn cancel_and_update_series_should_apply_to_following_occurrences() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let manager = ReservationManager::new(pool);
+        let rrule = "FREQ=DAILY;COUNT=4".parse().unwrap();
+        let ret = manager
+            .reserve_series(
+                make_meeting_reservation("meeting-room-1"),
+                rrule,
+                SeriesConflictMode::Fail,
+            )
+            .await
+            .unwrap();
+        let ids: Vec<_> = ret.reservations.iter().map(|r| r.id).collect();
+
+        let rsvps = manager
+            .update_series(
+                ids[1],
+                Some("new note".to_string()),
+                chrono::Duration::zero(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(rsvps.iter().map(|r| r.id).collect::<Vec<_>>(), &ids[1..]);
+        assert!(rsvps.iter().all(|r| r.note == "new note"));
+        assert_eq!(manager.get(ids[0]).await.unwrap().note, "weekly sync");
+
+        // an occurrence cancelled on its own is not cancelled again
+        let last = manager.cancel(ids[3], None).await.unwrap();
+        let rsvps = manager.cancel_series(ids[2]).await.unwrap();
+        assert_eq!(rsvps.iter().map(|r| r.id).collect::<Vec<_>>(), &ids[2..3]);
+        assert!(rsvps
+            .iter()
+            .all(|r| r.status == abi::ReservationStatus::Cancelled as i32));
+        assert_eq!(manager.get(ids[3]).await.unwrap(), last);
+        let err = manager.cancel_series(ids[2]).await.unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::InvalidTransition {
+                from: abi::ReservationStatus::Cancelled,
+                to: abi::ReservationStatus::Cancelled
+            }
+        );
+        assert_eq!(
+            manager.get(ids[1]).await.unwrap().status,
+            abi::ReservationStatus::Pending as i32
+        );
+
+        let err = manager.cancel_series(100).await.unwrap_err();
+        assert_eq!(err, abi::Error::NotFound);
+    }
+
+    #[tokio::test]
+    async fn update_series_should_shift_following_occurrences() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let manager = ReservationManager::new(pool.clone());
+        let rrule = "FREQ=DAILY;COUNT=4".parse().unwrap();
+        let ret = manager
+            .reserve_series(
+                make_meeting_reservation("meeting-room-1"),
+                rrule,
+                SeriesConflictMode::Fail,
+            )
+            .await
+            .unwrap();
+        let ids: Vec<_> = ret.reservations.iter().map(|r| r.id).collect();
+
+        // moving by less than the length overlaps the old slot of the same occurrence
+        let rsvps = manager
+            .update_series(ids[1], None, chrono::Duration::minutes(30))
+            .await
+            .unwrap();
+        assert_eq!(rsvps.iter().map(|r| r.id).collect::<Vec<_>>(), &ids[1..]);
+        assert!(rsvps.iter().all(|r| r.note == "weekly sync"));
+        let start = convert_to_utc_time(ret.reservations[1].start.as_ref().unwrap());
+        assert_eq!(
+            rsvps[0].start,
+            Some(convert_to_timestamp(start + chrono::Duration::minutes(30)))
+        );
+        assert_eq!(
+            manager.get(ids[0]).await.unwrap().start,
+            ret.reservations[0].start
+        );
+
+        make_reservation(
+            pool,
+            "bobid",
+            "meeting-room-1",
+            "2023-01-13T13:00:00-0700",
+            "2023-01-13T14:00:00-0700",
+            "taken",
+        )
+        .await;
+        let err = manager
+            .update_series(ids[1], None, chrono::Duration::hours(2))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, abi::Error::ConflictBatchReservation(2, _)));
+        assert_eq!(manager.get(ids[1]).await.unwrap().start, rsvps[0].start);
+    }
+
+    #[tokio::test]
+    async fn change_status_should_work() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let (rsvp, manager) = make_alice_reservation(pool).await;
+        assert_eq!(rsvp.status, abi::ReservationStatus::Pending as i32);
+        let id = rsvp.id;
+        let rsvp = manager.change_status(id, None).await.unwrap();
         assert_eq!(rsvp.status, abi::ReservationStatus::Confirmed as i32);
     }
 
     #[tokio::test]
-    async fn reserve_change_status_not_pending_shhou_do_nothing() {
+    async fn change_status_should_confirm_live_hold() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        let mut rsvp = make_meeting_reservation("hold-room-1");
+        rsvp.expires_at = Some(convert_to_timestamp(
+            Utc::now() + chrono::Duration::minutes(10),
+        ));
+        let rsvp = manager.reserve(rsvp).await.unwrap();
+        assert!(rsvp.expires_at.is_some());
+
+        let rsvp = manager.change_status(rsvp.id, None).await.unwrap();
+        assert_eq!(rsvp.status, abi::ReservationStatus::Confirmed as i32);
+        assert_eq!(rsvp.expires_at, None);
+    }
+
+    #[tokio::test]
+    async fn change_status_should_reject_expired_hold() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        let mut rsvp = make_meeting_reservation("hold-room-1");
+        rsvp.expires_at = Some(convert_to_timestamp(
+            Utc::now() - chrono::Duration::seconds(1),
+        ));
+        let rsvp = manager.reserve(rsvp).await.unwrap();
+
+        let err = manager.change_status(rsvp.id, None).await.unwrap_err();
+        assert_eq!(err, abi::Error::HoldExpired(rsvp.id));
+        let err = manager.change_status(rsvp.id + 1, None).await.unwrap_err();
+        assert_eq!(err, abi::Error::NotFound);
+    }
+
+    #[tokio::test]
+    async fn release_expired_holds_should_only_release_expired_pending() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        let mut expired = make_meeting_reservation("hold-room-1");
+        expired.expires_at = Some(convert_to_timestamp(
+            Utc::now() - chrono::Duration::seconds(1),
+        ));
+        let expired = manager.reserve(expired).await.unwrap();
+        let mut live = make_meeting_reservation("hold-room-2");
+        live.expires_at = Some(convert_to_timestamp(
+            Utc::now() + chrono::Duration::minutes(10),
+        ));
+        let live = manager.reserve(live).await.unwrap();
+        let forever = manager
+            .reserve(make_meeting_reservation("hold-room-3"))
+            .await
+            .unwrap();
+
+        let released = manager.release_expired_holds().await.unwrap();
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].id, expired.id);
+        assert_eq!(
+            manager.get(expired.id).await.unwrap().status,
+            abi::ReservationStatus::Cancelled as i32
+        );
+        manager.get(live.id).await.unwrap();
+        manager.get(forever.id).await.unwrap();
+
+        // the released window is free again
+        manager
+            .reserve(make_meeting_reservation("hold-room-1"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn hold_reaper_should_release_expired_holds() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        let mut rsvp = make_meeting_reservation("hold-room-1");
+        rsvp.expires_at = Some(convert_to_timestamp(
+            Utc::now() - chrono::Duration::seconds(1),
+        ));
+        let rsvp = manager.reserve(rsvp).await.unwrap();
+
+        let reaper = manager.spawn_hold_reaper(Duration::from_millis(50));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        reaper.abort();
+        assert_eq!(
+            manager.get(rsvp.id).await.unwrap().status,
+            abi::ReservationStatus::Cancelled as i32
+        );
+    }
+
+    #[tokio::test]
+    async fn reserve_change_status_not_pending_should_reject() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let (rsvp, manager) = make_alice_reservation(pool).await;
+        assert_eq!(rsvp.status, abi::ReservationStatus::Pending as i32);
+        let rsvp = manager.change_status(rsvp.id, None).await.unwrap();
+        // change status again should fail instead of doing nothing
+        let err = manager.change_status(rsvp.id, None).await.unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::InvalidTransition {
+                from: abi::ReservationStatus::Confirmed,
+                to: abi::ReservationStatus::Confirmed
+            }
+        );
+        assert_eq!(manager.get(rsvp.id).await.unwrap(), rsvp);
+    }
+
+    #[tokio::test]
+    async fn transition_should_follow_the_state_machine() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let (rsvp, manager) = make_alice_reservation(pool).await;
+        let err = manager
+            .transition(rsvp.id, abi::ReservationStatus::CheckedIn, None)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::InvalidTransition {
+                from: abi::ReservationStatus::Pending,
+                to: abi::ReservationStatus::CheckedIn
+            }
+        );
+
+        manager.change_status(rsvp.id, None).await.unwrap();
+        let rsvp = manager
+            .transition(rsvp.id, abi::ReservationStatus::CheckedIn, None)
+            .await
+            .unwrap();
+        assert_eq!(rsvp.status, abi::ReservationStatus::CheckedIn as i32);
+
+        let rsvp = manager.cancel(rsvp.id, None).await.unwrap();
+        assert_eq!(rsvp.status, abi::ReservationStatus::Cancelled as i32);
+        let err = manager.cancel(rsvp.id, None).await.unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::InvalidTransition {
+                from: abi::ReservationStatus::Cancelled,
+                to: abi::ReservationStatus::Cancelled
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn update_note_should_work() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let (rsvp, manager) = make_alice_reservation(pool.clone()).await;
+        assert_ne!(rsvp.note, "new note");
+        let r = manager
+            .update(rsvp.id, note("new note"), &paths(["note"]), None)
+            .await
+            .unwrap();
+        assert_eq!(r.note, "new note");
+    }
+
+    #[tokio::test]
+    async fn update_should_change_fields_at_once() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let (rsvp, manager) = make_alice_reservation(pool.clone()).await;
+        let (other, _) = make_reservation(
+            pool.clone(),
+            "bobid",
+            "ocean-view-room-713",
+            "2023-01-25T15:00:00-0700",
+            "2023-01-28T12:00:00-0700",
+            "",
+        )
+        .await;
+        // transfer to bob, move to another resource and another window
+        let mut patch = other.clone();
+        patch.start = Some(convert_to_timestamp(
+            "2023-02-25T15:00:00-0700".parse::<DateTime<Utc>>().unwrap(),
+        ));
+        patch.end = Some(convert_to_timestamp(
+            "2023-02-26T12:00:00-0700".parse::<DateTime<Utc>>().unwrap(),
+        ));
+        patch.note = "moved".to_string();
+        let all = paths(["note", "user_id", "resource_id", "start", "end"]);
+        let r = manager
+            .update(rsvp.id, patch.clone(), &all, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            (r.user_id.as_str(), r.resource_id.as_str(), r.note.as_str()),
+            ("bobid", "ocean-view-room-713", "moved")
+        );
+        assert_eq!((&r.start, &r.end), (&patch.start, &patch.end));
+        assert_eq!(r.version, rsvp.version + 1);
+
+        // the new window must be free
+        let err = manager
+            .update(rsvp.id, other.clone(), &paths(["start", "end"]), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, abi::Error::ConflictReservation(_)));
+
+        let err = manager
+            .update(rsvp.id, note(""), &paths(["user_id"]), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::InvalidUserId("".to_string()));
+        let err = manager
+            .update(rsvp.id, note(""), &paths(["status"]), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::InvalidUpdateMask("status".to_string()));
+        let err = manager
+            .update(rsvp.id, note(""), &[], None)
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::InvalidUpdateMask("".to_string()));
+        assert_eq!(manager.get(rsvp.id).await.unwrap(), r);
+    }
+
+    #[tokio::test]
+    async fn stale_version_should_be_rejected() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let (rsvp, manager) = make_alice_reservation(pool.clone()).await;
+        assert_eq!(rsvp.version, 1);
+        // two agents read version 1, the first one wins
+        let r = manager
+            .update(rsvp.id, note("agent 1"), &paths(["note"]), Some(1))
+            .await
+            .unwrap();
+        assert_eq!(r.version, 2);
+        let err = manager
+            .update(rsvp.id, note("agent 2"), &paths(["note"]), Some(1))
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::VersionMismatch(rsvp.id, 1, 2));
+        let err = manager.change_status(rsvp.id, Some(1)).await.unwrap_err();
+        assert_eq!(err, abi::Error::VersionMismatch(rsvp.id, 1, 2));
+        let err = manager.cancel(rsvp.id, Some(1)).await.unwrap_err();
+        assert_eq!(err, abi::Error::VersionMismatch(rsvp.id, 1, 2));
+
+        let r = manager.change_status(rsvp.id, Some(2)).await.unwrap();
+        assert_eq!(r.version, 3);
+        assert_eq!(manager.get(rsvp.id).await.unwrap(), r);
+        let err = manager
+            .change_status(rsvp.id + 1, Some(1))
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::NotFound);
+    }
+    #[tokio::test]
+    async fn reschedule_should_work() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let (rsvp, manager) = make_alice_reservation(pool.clone()).await;
+        let start = "2023-03-25T15:00:00-0700".parse().unwrap();
+        let end = "2023-04-25T12:00:00-0700".parse().unwrap();
+        let r = manager.reschedule(rsvp.id, start, end, None).await.unwrap();
+        assert_eq!(r.start, Some(convert_to_timestamp(start)));
+        assert_eq!(r.end, Some(convert_to_timestamp(end)));
+        assert_eq!(r.note, rsvp.note);
+    }
+
+    #[tokio::test]
+    async fn reschedule_conflict_reservation_should_reject() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        make_ssk_reservation(pool.clone()).await;
+        let (rsvp, manager) = make_reservation(
+            pool,
+            "aliceid",
+            "ocean-view-room-713",
+            "2023-01-25T15:00:00-0700",
+            "2023-01-28T12:00:00-0700",
+            "",
+        )
+        .await;
+        let err = manager
+            .reschedule(
+                rsvp.id,
+                "2022-12-26T15:00:00-0700".parse().unwrap(),
+                "2022-12-29T12:00:00-0700".parse().unwrap(),
+                None,
+            )
+            .await
+            .unwrap_err();
+        let info = ReservationConflictInfo::Parsed(Box::new(ReservationConflict {
+            new: window(
+                "ocean-view-room-713",
+                "2022-12-26T15:00:00-0700",
+                "2022-12-29T12:00:00-0700",
+            ),
+            old: window(
+                "ocean-view-room-713",
+                "2022-12-25T15:00:00-0700",
+                "2022-12-28T12:00:00-0700",
+            ),
+        }));
+        assert_eq!(err, abi::Error::ConflictReservation(info));
+
+        // the reservation is kept in its original window
+        let r = manager.get(rsvp.id).await.unwrap();
+        assert_eq!(r, rsvp);
+    }
+
+    #[tokio::test]
+    async fn reschedule_with_invalid_window_should_reject() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let (rsvp, manager) = make_alice_reservation(pool).await;
+        let start = "2023-03-25T15:00:00-0700".parse().unwrap();
+        let err = manager
+            .reschedule(rsvp.id, start, start, None)
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::InvalidTime);
+    }
+
+    #[tokio::test]
+    async fn delete_reservation_should_work() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let (rsvp, manager) = make_alice_reservation(pool.clone()).await;
+        let r = manager.delete(rsvp.id).await.unwrap();
+        assert_eq!(r, rsvp);
+        let err = manager.get(rsvp.id).await.unwrap_err();
+        assert_eq!(err, abi::Error::NotFound);
+    }
+    #[tokio::test]
+    async fn cancel_reservation_should_keep_it_and_free_the_window() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let (rsvp, manager) = make_alice_reservation(pool.clone()).await;
+        let cancelled = manager.cancel(rsvp.id, None).await.unwrap();
+        assert_eq!(cancelled.status, abi::ReservationStatus::Cancelled as i32);
+        assert_eq!(manager.get(rsvp.id).await.unwrap(), cancelled);
+
+        // the same window could be reserved again
+        let (rsvp, _) = make_alice_reservation(pool.clone()).await;
+        assert_ne!(rsvp.id, cancelled.id);
+
+        let query = ReservationQueryBuilder::default()
+            .user_id("aliceid")
+            .status(abi::ReservationStatus::Cancelled as i32)
+            .build()
+            .unwrap();
+        let mut rx = manager.query(query).await;
+        assert_eq!(rx.recv().await, Some(Ok(cancelled)));
+        assert_eq!(rx.recv().await, None);
+    }
+    #[tokio::test]
+    async fn block_resource_should_cancel_pending_but_not_confirmed() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let manager = ReservationManager::new(pool.clone());
+        let pending = manager
+            .reserve(make_meeting_reservation("meeting-room-1"))
+            .await
+            .unwrap();
+        let mut block = make_meeting_reservation("meeting-room-1");
+        block.user_id = "operator".to_string();
+
+        let err = manager
+            .block_resource(block.clone(), BlockMode::Reject)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, abi::Error::ConflictReservation(_)));
+
+        let ret = manager
+            .block_resource(block.clone(), BlockMode::CancelPending)
+            .await
+            .unwrap();
+        assert_eq!(ret.cancelled.len(), 1);
+        assert_eq!(ret.cancelled[0].id, pending.id);
+        assert_eq!(
+            ret.cancelled[0].status,
+            abi::ReservationStatus::Cancelled as i32
+        );
+        let block_id = ret.block.unwrap().id;
+
+        // a block is never confirmed
+        let err = manager.change_status(block_id, None).await.unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::InvalidTransition {
+                from: abi::ReservationStatus::Blocked,
+                to: abi::ReservationStatus::Confirmed
+            }
+        );
+
+        // reservations can't be made as blocks
+        let mut rsvp = make_meeting_reservation("meeting-room-2");
+        rsvp.status = abi::ReservationStatus::Blocked as i32;
+        let err = manager.reserve(rsvp).await.unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::InvalidStatus(abi::ReservationStatus::Blocked as i32)
+        );
+
+        // a confirmed reservation still fails the block
+        let confirmed = manager
+            .reserve(make_meeting_reservation("meeting-room-2"))
+            .await
+            .unwrap();
+        manager.change_status(confirmed.id, None).await.unwrap();
+        block.resource_id = "meeting-room-2".to_string();
+        let err = manager
+            .block_resource(block.clone(), BlockMode::CancelPending)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, abi::Error::ConflictReservation(_)));
+
+        // unless the block is forced, then it's cancelled and reported
+        let ret = manager
+            .block_resource(block.clone(), BlockMode::Force)
+            .await
+            .unwrap();
+        assert_eq!(ret.cancelled.len(), 1);
+        assert_eq!(ret.cancelled[0].id, confirmed.id);
+        // other blocks still fail it
+        let err = manager
+            .block_resource(block, BlockMode::Force)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, abi::Error::ConflictReservation(_)));
+    }
+
+    #[tokio::test]
+    async fn block_resource_should_cancel_pending_in_buffers() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        let room = abi::Resource {
+            buffer_after: Some(prost_types::Duration {
+                seconds: 30 * 60,
+                nanos: 0,
+            }),
+            ..abi::Resource::new("meeting-room-2", "Meeting room 2", "room")
+        };
+        manager
+            .update_resource(room, &paths(["buffer_after"]))
+            .await
+            .unwrap();
+        let pending = manager
+            .reserve(make_meeting_reservation("meeting-room-2"))
+            .await
+            .unwrap();
+
+        // the block right after the pending reservation falls into its buffer
+        let mut block = make_meeting_reservation("meeting-room-2");
+        block.user_id = "operator".to_string();
+        block.start = Some(convert_to_timestamp(
+            "2023-01-10T11:00:00-0700".parse().unwrap(),
+        ));
+        block.end = Some(convert_to_timestamp(
+            "2023-01-10T12:00:00-0700".parse().unwrap(),
+        ));
+        let ret = manager
+            .block_resource(block, BlockMode::CancelPending)
+            .await
+            .unwrap();
+        assert_eq!(ret.cancelled.len(), 1);
+        assert_eq!(ret.cancelled[0].id, pending.id);
+        assert!(ret.block.is_some());
+    }
+
+    #[tokio::test]
+    async fn unblock_resource_should_free_the_window() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let manager = ReservationManager::new(pool.clone());
+        let ret = manager
+            .block_resource(
+                make_meeting_reservation("meeting-room-1"),
+                BlockMode::Reject,
+            )
+            .await
+            .unwrap();
+        let block = ret.block.unwrap();
+
+        let err = manager.unblock_resource(block.id + 1).await.unwrap_err();
+        assert_eq!(err, abi::Error::NotFound);
+
+        // a block is not cancelled like a reservation
+        let err = manager.cancel(block.id, None).await.unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::InvalidTransition {
+                from: abi::ReservationStatus::Blocked,
+                to: abi::ReservationStatus::Cancelled
+            }
+        );
+
+        let unblocked = manager.unblock_resource(block.id).await.unwrap();
+        assert_eq!(unblocked.status, abi::ReservationStatus::Cancelled as i32);
+        manager
+            .reserve(make_meeting_reservation("meeting-room-1"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn reserve_should_admit_up_to_capacity() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        set_capacity(&manager, "training-room", 2).await;
+        let mut rsvp = make_meeting_reservation("training-room");
+        manager.reserve(rsvp.clone()).await.unwrap();
+        rsvp.user_id = "bobid".to_string();
+        manager.reserve(rsvp.clone()).await.unwrap();
+
+        rsvp.user_id = "carolid".to_string();
+        let err = manager.reserve(rsvp.clone()).await.unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::CapacityExceeded("training-room".to_string(), 2, 0)
+        );
+
+        // a block takes the whole capacity, so it fails while any unit is used
+        let err = manager
+            .block_resource(rsvp.clone(), BlockMode::Reject)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::CapacityExceeded("training-room".to_string(), 2, 0)
+        );
+    }
+
+    #[tokio::test]
+    async fn reserve_series_should_report_full_occurrence_in_batch() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        set_capacity(&manager, "training-room", 2).await;
+        let pool = manager.pool.clone();
+        for uid in ["bobid", "carolid"] {
+            make_reservation(
+                pool.clone(),
+                uid,
+                "training-room",
+                "2023-01-11T10:00:00-0700",
+                "2023-01-11T11:00:00-0700",
+                "taken",
+            )
+            .await;
+        }
+        let err = manager
+            .reserve_series(
+                make_meeting_reservation("training-room"),
+                "FREQ=DAILY;COUNT=3".parse().unwrap(),
+                SeriesConflictMode::Fail,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::ConflictBatchReservation(
+                1,
+                ReservationConflictInfo::CapacityExceeded("training-room".to_string(), 2, 0)
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn reserve_should_count_peak_overlap_for_capacity() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        set_capacity(&manager, "parking-lot", 2).await;
+        let pool = manager.pool.clone();
+        // 10:00-11:00 and 11:00-12:00 don't overlap each other
+        make_reservation(
+            pool.clone(),
+            "aliceid",
+            "parking-lot",
+            "2023-01-10T10:00:00-0700",
+            "2023-01-10T11:00:00-0700",
+            "",
+        )
+        .await;
+        make_reservation(
+            pool.clone(),
+            "bobid",
+            "parking-lot",
+            "2023-01-10T11:00:00-0700",
+            "2023-01-10T12:00:00-0700",
+            "",
+        )
+        .await;
+        let (rsvp, _) = make_reservation(
+            pool.clone(),
+            "carolid",
+            "parking-lot",
+            "2023-01-10T09:00:00-0700",
+            "2023-01-10T13:00:00-0700",
+            "",
+        )
+        .await;
+        let rsvp = abi::Reservation {
+            id: 0,
+            user_id: "daveid".to_string(),
+            ..rsvp
+        };
+        let err = manager.reserve(rsvp).await.unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::CapacityExceeded("parking-lot".to_string(), 2, 0)
+        );
+    }
+
+    #[tokio::test]
+    async fn concurrent_reserves_should_not_exceed_capacity() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        set_capacity(&manager, "parking-lot", 3).await;
+        let tasks: Vec<_> = (0..10)
+            .map(|i| {
+                let manager = manager.clone();
+                let mut rsvp = make_meeting_reservation("parking-lot");
+                rsvp.user_id = format!("user{}", i);
+                tokio::spawn(async move { manager.reserve(rsvp).await })
+            })
+            .collect();
+        let mut reserved = 0;
+        for task in tasks {
+            match task.await.unwrap() {
+                Ok(_) => reserved += 1,
+                Err(e) => assert!(matches!(e, abi::Error::CapacityExceeded(..))),
+            }
+        }
+        assert_eq!(reserved, 3);
+    }
+
+    #[tokio::test]
+    async fn reschedule_should_respect_capacity() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        set_capacity(&manager, "training-room", 2).await;
+        let mut rsvp = make_meeting_reservation("training-room");
+        manager.reserve(rsvp.clone()).await.unwrap();
+        let moved = manager.reserve(rsvp.clone()).await.unwrap();
+        // rescheduling onto its own window doesn't count itself
+        let start = convert_to_utc_time(moved.start.as_ref().unwrap());
+        let end = convert_to_utc_time(moved.end.as_ref().unwrap());
+        manager
+            .reschedule(moved.id, start, end + chrono::Duration::minutes(30), None)
+            .await
+            .unwrap();
+
+        rsvp.start = Some(convert_to_timestamp(end));
+        rsvp.end = Some(convert_to_timestamp(end + chrono::Duration::hours(1)));
+        let other = manager.reserve(rsvp).await.unwrap();
+        let err = manager
+            .reschedule(other.id, start, end + chrono::Duration::hours(1), None)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::CapacityExceeded("training-room".to_string(), 2, 0)
+        );
+    }
+
+    #[tokio::test]
+    async fn reserve_and_reschedule_should_enforce_booking_policy() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        let secs = |seconds| Some(prost_types::Duration { seconds, nanos: 0 });
+        let room = abi::Resource {
+            policy: Some(abi::BookingPolicy {
+                max_duration: secs(4 * 3600),
+                min_lead_time: secs(15 * 60),
+                ..Default::default()
+            }),
+            ..abi::Resource::new("meeting-room-1", "Meeting room 1", "room")
+        };
+        manager
+            .update_resource(room, &paths(["policy"]))
+            .await
+            .unwrap();
+
+        let start = Utc::now() + chrono::Duration::days(1);
+        let make_rsvp = |start: DateTime<Utc>, end: DateTime<Utc>| abi::Reservation {
+            start: Some(convert_to_timestamp(start)),
+            end: Some(convert_to_timestamp(end)),
+            ..make_meeting_reservation("meeting-room-1")
+        };
+        let rsvp = manager
+            .reserve(make_rsvp(start, start + chrono::Duration::hours(4)))
+            .await
+            .unwrap();
+
+        let err = manager
+            .reserve(make_rsvp(
+                start + chrono::Duration::days(1),
+                start + chrono::Duration::days(1) + chrono::Duration::hours(5),
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::DurationTooLong(5 * 3600, 4 * 3600));
+
+        let soon = Utc::now() + chrono::Duration::minutes(5);
+        let err = manager
+            .reserve(make_rsvp(soon, soon + chrono::Duration::hours(1)))
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::LeadTimeTooShort(15 * 60));
+
+        let err = manager
+            .reschedule(rsvp.id, start, start + chrono::Duration::hours(5), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::DurationTooLong(5 * 3600, 4 * 3600));
+
+        // blocks are not restricted by the booking policy
+        manager
+            .block_resource(
+                make_rsvp(soon, soon + chrono::Duration::hours(8)),
+                BlockMode::Reject,
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn update_should_share_reservation_by_the_new_resource_capacity() {
         let tdb = get_db();
-        let pool = tdb.get_pool().await;
-        let (rsvp, manager) = make_alice_reservation(pool).await;
-        println!("rsvp: {:?}", rsvp);
-        let rsvp = manager.change_status(rsvp.id).await.unwrap();
-        // change status again should do nothing
-        let rsvp = manager.change_status(rsvp.id).await.unwrap();
-        assert_eq!(rsvp.status, abi::ReservationStatus::Confirmed as i32);
+        let pool = get_pool(&tdb).await;
+        let manager = ReservationManager::new(pool.clone());
+        set_capacity(&manager, "training-room", 2).await;
+        let rsvp = manager
+            .reserve(make_meeting_reservation("training-room"))
+            .await
+            .unwrap();
+        let shared = |id| {
+            sqlx::query_scalar::<_, bool>("SELECT shared FROM rsvp.reservations WHERE id = $1")
+                .bind(id)
+                .fetch_one(&pool)
+        };
+        assert!(shared(rsvp.id).await.unwrap());
+
+        let patch = Reservation {
+            resource_id: "meeting-room-1".to_string(),
+            ..Default::default()
+        };
+        manager
+            .update(rsvp.id, patch, &paths(["resource_id"]), None)
+            .await
+            .unwrap();
+        assert!(!shared(rsvp.id).await.unwrap());
     }
 
     #[tokio::test]
-    async fn update_note_should_work() {
+    async fn lowering_capacity_should_keep_shared_reservations_taken() {
         let tdb = get_db();
-        let pool = tdb.get_pool().await;
-        let (rsvp, manager) = make_alice_reservation(pool.clone()).await;
-        println!("r: {:?}", rsvp);
-        let r = manager
-            .update_note(rsvp.id, "new note".to_string())
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        set_capacity(&manager, "training-room", 2).await;
+        let shared = manager
+            .reserve(make_meeting_reservation("training-room"))
             .await
             .unwrap();
-        assert_eq!(r.note, "new note");
+        let mut other = make_meeting_reservation("training-room");
+        other.start = Some(convert_to_timestamp(
+            convert_to_utc_time(other.end.as_ref().unwrap()) + chrono::Duration::hours(1),
+        ));
+        other.end = Some(convert_to_timestamp(
+            convert_to_utc_time(other.start.as_ref().unwrap()) + chrono::Duration::hours(1),
+        ));
+        let other = manager.reserve(other).await.unwrap();
+        set_capacity(&manager, "training-room", 1).await;
+
+        // the shared reservation still takes the only unit
+        let mut rsvp = make_meeting_reservation("training-room");
+        rsvp.user_id = "bobid".to_string();
+        let err = manager.reserve(rsvp).await.unwrap_err();
+        assert!(matches!(err, abi::Error::ConflictReservation(_)));
+        // moving a reservation onto it fails as well
+        let err = manager
+            .reschedule(
+                other.id,
+                convert_to_utc_time(shared.start.as_ref().unwrap()),
+                convert_to_utc_time(shared.end.as_ref().unwrap()),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, abi::Error::ConflictReservation(_)));
     }
+
     #[tokio::test]
-    async fn delete_reservation_should_work() {
+    async fn reserve_and_reschedule_should_keep_buffers_free() {
         let tdb = get_db();
-        let pool = tdb.get_pool().await;
-        let (rsvp, manager) = make_alice_reservation(pool.clone()).await;
-        println!("r: {:?}", rsvp);
-        let r = manager.delete(rsvp.id).await;
-        assert!(r.is_ok());
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        let secs = |seconds| Some(prost_types::Duration { seconds, nanos: 0 });
+        let room = abi::Resource {
+            buffer_before: secs(15 * 60),
+            buffer_after: secs(30 * 60),
+            ..abi::Resource::new("meeting-room-2", "Meeting room 2", "room")
+        };
+        manager
+            .update_resource(room, &paths(["buffer_before", "buffer_after"]))
+            .await
+            .unwrap();
+        let rsvp = manager
+            .reserve(make_meeting_reservation("meeting-room-2"))
+            .await
+            .unwrap();
+
+        // back-to-back reservation falls into the buffer after the first one
+        let mut next = make_meeting_reservation("meeting-room-2");
+        next.start = Some(convert_to_timestamp(
+            "2023-01-10T11:00:00-0700".parse().unwrap(),
+        ));
+        next.end = Some(convert_to_timestamp(
+            "2023-01-10T12:00:00-0700".parse().unwrap(),
+        ));
+        let err = manager.reserve(next.clone()).await.unwrap_err();
+        let info = ReservationConflictInfo::Parsed(Box::new(ReservationConflict {
+            new: ReservationWindow::new(
+                "meeting-room-2",
+                (
+                    "2023-01-10T11:00:00-0700".parse().unwrap(),
+                    "2023-01-10T12:00:00-0700".parse().unwrap(),
+                ),
+                (
+                    "2023-01-10T10:45:00-0700".parse().unwrap(),
+                    "2023-01-10T12:30:00-0700".parse().unwrap(),
+                ),
+            ),
+            old: ReservationWindow::new(
+                "meeting-room-2",
+                (
+                    "2023-01-10T10:00:00-0700".parse().unwrap(),
+                    "2023-01-10T11:00:00-0700".parse().unwrap(),
+                ),
+                (
+                    "2023-01-10T09:45:00-0700".parse().unwrap(),
+                    "2023-01-10T11:30:00-0700".parse().unwrap(),
+                ),
+            ),
+        }));
+        assert_eq!(err, abi::Error::ConflictReservation(info));
+
+        // a gap covering both buffers is fine
+        next.start = Some(convert_to_timestamp(
+            "2023-01-10T11:45:00-0700".parse().unwrap(),
+        ));
+        next.end = Some(convert_to_timestamp(
+            "2023-01-10T12:45:00-0700".parse().unwrap(),
+        ));
+        let next = manager.reserve(next).await.unwrap();
+
+        // rescheduling into the buffer of another reservation is rejected as well
+        let end = convert_to_utc_time(rsvp.end.as_ref().unwrap());
+        let err = manager
+            .reschedule(next.id, end, end + chrono::Duration::hours(1), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, abi::Error::ConflictReservation(_)));
     }
+
     #[tokio::test]
     async fn get_reservation_by_id_should_work() {
         let tdb = get_db();
-        let pool = tdb.get_pool().await;
+        let pool = get_pool(&tdb).await;
         let (rsvp, manager) = make_alice_reservation(pool.clone()).await;
-        println!("r: {:?}", rsvp);
         let r = manager.get(rsvp.id).await.unwrap();
-        assert_eq!(r.id, rsvp.id);
+        assert_eq!(r, rsvp);
     }
 
     // // query function test
     #[tokio::test]
     async fn query_reservations_should_work() {
         let tdb = get_db();
-        let pool = tdb.get_pool().await;
+        let pool = get_pool(&tdb).await;
         let (rsvp, manager) = make_alice_reservation(pool.clone()).await;
         let query = ReservationQueryBuilder::default()
             .user_id("aliceid")
@@ -314,16 +2518,25 @@ mod tests {
         assert_eq!(rx.recv().await, None);
 
         // change state to confirmed, query should get result
-        let rsvp = manager.change_status(rsvp.id).await.unwrap();
+        let rsvp = manager.change_status(rsvp.id, None).await.unwrap();
         let mut rx = manager.query(query).await;
         assert_eq!(rx.recv().await, Some(Ok(rsvp)));
+
+        // a quote in the values doesn't break the query
+        let query = ReservationQueryBuilder::default()
+            .user_id("alice'id")
+            .resource_id("ocean-view-room-713' OR 'a' = 'a")
+            .build()
+            .unwrap();
+        let mut rx = manager.query(query).await;
+        assert_eq!(rx.recv().await, None);
     }
 
     // test filter function
     #[tokio::test]
     async fn filter_reservations_should_work() {
         let tdb = get_db();
-        let pool = tdb.get_pool().await;
+        let pool = get_pool(&tdb).await;
         let (rsvp, manager) = make_alice_reservation(pool.clone()).await;
         let filter = ReservationFilterBuilder::default()
             .user_id("aliceid")
@@ -335,8 +2548,653 @@ mod tests {
         assert_eq!(pager.next, None);
         assert_eq!(rsvps.len(), 1);
         assert_eq!(rsvp, rsvps[0]);
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("aliceid' OR 'a' = 'a")
+            .build()
+            .unwrap();
+        let (_, rsvps) = manager.filter(filter).await.unwrap();
+        assert!(rsvps.is_empty());
+    }
+
+    #[tokio::test]
+    async fn query_and_filter_as_of_should_see_past_reservations() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let (rsvp, manager) = make_alice_reservation(pool.clone()).await;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let pending_at = convert_to_timestamp(Utc::now());
+        let confirmed = manager.change_status(rsvp.id, None).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let confirmed_at = convert_to_timestamp(Utc::now());
+        manager.delete(rsvp.id).await.unwrap();
+
+        let query = |status: abi::ReservationStatus, as_of: &Timestamp| {
+            ReservationQueryBuilder::default()
+                .user_id("aliceid")
+                .status(status as i32)
+                .as_of(as_of.clone())
+                .build()
+                .unwrap()
+        };
+        let mut rx = manager
+            .query(query(abi::ReservationStatus::Pending, &pending_at))
+            .await;
+        assert_eq!(rx.recv().await, Some(Ok(rsvp.clone())));
+        assert_eq!(rx.recv().await, None);
+        let mut rx = manager
+            .query(query(abi::ReservationStatus::Confirmed, &confirmed_at))
+            .await;
+        assert_eq!(rx.recv().await, Some(Ok(confirmed.clone())));
+        assert_eq!(rx.recv().await, None);
+
+        let filter = |as_of: Timestamp| {
+            ReservationFilterBuilder::default()
+                .user_id("aliceid")
+                .status(abi::ReservationStatus::Confirmed as i32)
+                .as_of(as_of)
+                .build()
+                .unwrap()
+        };
+        let (_, rsvps) = manager.filter(filter(confirmed_at)).await.unwrap();
+        assert_eq!(rsvps, vec![confirmed]);
+        // the reservation is deleted by now
+        let now = convert_to_timestamp(Utc::now());
+        let (_, rsvps) = manager.filter(filter(now)).await.unwrap();
+        assert!(rsvps.is_empty());
+
+        // the change log doesn't go back that far
+        let before = convert_to_timestamp("2022-01-01T00:00:00Z".parse().unwrap());
+        let err = manager.filter(filter(before.clone())).await.unwrap_err();
+        assert!(matches!(err, abi::Error::AsOfBeforeChangeLog(_)));
+        let mut rx = manager
+            .query(query(abi::ReservationStatus::Pending, &before))
+            .await;
+        assert!(matches!(
+            rx.recv().await,
+            Some(Err(abi::Error::AsOfBeforeChangeLog(_)))
+        ));
+        assert_eq!(rx.recv().await, None);
+    }
+
+    #[tokio::test]
+    async fn query_as_of_should_not_see_changes_of_earlier_started_transactions() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let (rsvp, manager) = make_alice_reservation(pool.clone()).await;
+        // this transaction starts before the confirmation but changes the reservation after it
+        let mut tx = pool.begin().await.unwrap();
+        sqlx::query("SELECT 1").execute(&mut tx).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let pending_at = convert_to_timestamp(Utc::now());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let confirmed = manager.change_status(rsvp.id, None).await.unwrap();
+        sqlx::query("UPDATE rsvp.reservations SET note = 'later' WHERE id = $1")
+            .bind(rsvp.id)
+            .execute(&mut tx)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let query = |status: abi::ReservationStatus, as_of: Timestamp| {
+            ReservationQueryBuilder::default()
+                .user_id("aliceid")
+                .status(status as i32)
+                .as_of(as_of)
+                .build()
+                .unwrap()
+        };
+        let mut rx = manager
+            .query(query(abi::ReservationStatus::Pending, pending_at))
+            .await;
+        assert_eq!(rx.recv().await, Some(Ok(rsvp)));
+        assert_eq!(rx.recv().await, None);
+        let now = convert_to_timestamp(Utc::now());
+        let mut rx = manager
+            .query(query(abi::ReservationStatus::Confirmed, now))
+            .await;
+        let rsvp = rx.recv().await.unwrap().unwrap();
+        assert_eq!(rsvp.id, confirmed.id);
+        assert_eq!(rsvp.note, "later");
+        assert_eq!(rx.recv().await, None);
+    }
+
+    #[tokio::test]
+    async fn listen_should_receive_reservation_changes() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let manager = ReservationManager::new(pool.clone());
+        let mut rx = manager.listen(ListenRequest::default()).await.unwrap();
+
+        let (rsvp, manager) = make_alice_reservation(pool).await;
+        let confirmed = manager.change_status(rsvp.id, None).await.unwrap();
+        manager.delete(rsvp.id).await.unwrap();
+
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, ReservationUpdateType::Create as i32);
+        assert_eq!(change.reservation, Some(rsvp.clone()));
+
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, ReservationUpdateType::Update as i32);
+        assert_eq!(change.reservation, Some(confirmed.clone()));
+        assert_eq!(change.old, Some(rsvp));
+
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, ReservationUpdateType::Delete as i32);
+        assert_eq!(change.reservation, Some(confirmed.clone()));
+        assert_eq!(change.old, Some(confirmed));
+    }
+
+    #[tokio::test]
+    async fn history_should_return_changes_in_order() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let (rsvp, manager) = make_alice_reservation(pool).await;
+        let confirmed = manager.change_status(rsvp.id, None).await.unwrap();
+        manager.delete(rsvp.id).await.unwrap();
+
+        let changes = manager.history(rsvp.id).await.unwrap();
+        let ops: Vec<_> = changes.iter().map(|c| c.op).collect();
+        assert_eq!(
+            ops,
+            vec![
+                ReservationUpdateType::Create as i32,
+                ReservationUpdateType::Update as i32,
+                ReservationUpdateType::Delete as i32,
+            ]
+        );
+        assert!(changes.windows(2).all(|w| w[0].change_id < w[1].change_id));
+        assert!(changes.iter().all(|c| c.changed_at.is_some()));
+        assert_eq!(changes[0].old, None);
+        assert_eq!(changes[0].new, Some(rsvp.clone()));
+        assert_eq!(changes[1].old, Some(rsvp.clone()));
+        assert_eq!(changes[1].new, Some(confirmed.clone()));
+        assert_eq!(changes[2].old, Some(confirmed));
+        assert_eq!(changes[2].new, None);
+
+        let err = manager.history(rsvp.id + 1).await.unwrap_err();
+        assert_eq!(err, abi::Error::NotFound);
+    }
+
+    #[tokio::test]
+    async fn every_field_change_should_be_recorded_with_actor() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let (rsvp, manager) = make_alice_reservation(pool).await;
+        let mut rx = manager.listen(ListenRequest::default()).await.unwrap();
+        let updated = manager
+            .with_actor("bobid")
+            .update(rsvp.id, note("moved by bob"), &paths(["note"]), None)
+            .await
+            .unwrap();
+
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, ReservationUpdateType::Update as i32);
+        assert_eq!(change.reservation, Some(updated.clone()));
+        assert_eq!(change.actor, "bobid");
+        assert!(change.changed_at.is_some());
+
+        let changes = manager.history(rsvp.id).await.unwrap();
+        assert_eq!(changes.len(), 2);
+        // made without an actor
+        assert_eq!(changes[0].actor, "");
+        assert_eq!(changes[1].actor, "bobid");
+        assert_eq!(changes[1].new, Some(updated));
+    }
+
+    #[tokio::test]
+    async fn long_actor_should_be_cut_to_the_change_log() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let (rsvp, manager) = make_alice_reservation(pool).await;
+        // the user id is cut at a character boundary
+        let actor = format!("a{}", "é".repeat(100));
+        manager
+            .with_actor(actor.as_str())
+            .update(rsvp.id, note("moved"), &paths(["note"]), None)
+            .await
+            .unwrap();
+        let changes = manager.history(rsvp.id).await.unwrap();
+        assert_eq!(changes[1].actor, format!("a{}", "é".repeat(31)));
+    }
+
+    #[tokio::test]
+    async fn listen_should_replay_changes_after_given_id() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let (rsvp, manager) = make_alice_reservation(pool).await;
+        let confirmed = manager.change_status(rsvp.id, None).await.unwrap();
+
+        let mut rx = manager
+            .listen(ListenRequest::new("", Some(1)))
+            .await
+            .unwrap();
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.change_id, 2);
+        assert_eq!(change.op, ReservationUpdateType::Update as i32);
+        assert_eq!(change.reservation, Some(confirmed));
+    }
+
+    #[tokio::test]
+    async fn listen_should_filter_changes() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let manager = ReservationManager::new(pool.clone());
+        let request = ListenRequest {
+            resource_id: "ocean-view-".into(),
+            ops: vec![ReservationUpdateType::Update as i32],
+            ..Default::default()
+        };
+        let mut rx = manager.listen(request).await.unwrap();
+
+        let (alice_rsvp, manager) = make_alice_reservation(pool.clone()).await;
+        manager.change_status(alice_rsvp.id, None).await.unwrap();
+        let (ssk_rsvp, manager) = make_ssk_reservation(pool).await;
+        let confirmed = manager.change_status(ssk_rsvp.id, None).await.unwrap();
+
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, ReservationUpdateType::Update as i32);
+        assert_eq!(change.reservation, Some(confirmed));
+    }
+
+    #[tokio::test]
+    async fn listen_should_send_changes_moving_out_of_the_filter() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let manager = ReservationManager::new(pool.clone());
+        let request = ListenRequest {
+            resource_id: "meeting-room-".into(),
+            ..Default::default()
+        };
+        let mut rx = manager.listen(request).await.unwrap();
+
+        let rsvp = manager
+            .reserve(make_meeting_reservation("meeting-room-1"))
+            .await
+            .unwrap();
+        let patch = Reservation {
+            resource_id: "projector-1".to_string(),
+            ..Default::default()
+        };
+        let moved = manager
+            .update(rsvp.id, patch.clone(), &paths(["resource_id"]), None)
+            .await
+            .unwrap();
+        // not sent once it is out of the filter
+        manager
+            .update(rsvp.id, note("away"), &paths(["note"]), None)
+            .await
+            .unwrap();
+        let patch = Reservation {
+            resource_id: "meeting-room-2".to_string(),
+            ..patch
+        };
+        let back = manager
+            .update(rsvp.id, patch, &paths(["resource_id"]), None)
+            .await
+            .unwrap();
+
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, ReservationUpdateType::Create as i32);
+        assert_eq!(change.old, None);
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, ReservationUpdateType::Update as i32);
+        assert_eq!(change.old, Some(rsvp));
+        assert_eq!(change.reservation, Some(moved));
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.reservation, Some(back));
+        assert_eq!(change.old.unwrap().resource_id, "projector-1");
+    }
+
+    #[tokio::test]
+    async fn listen_should_resume_from_consumer_cursor() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let manager = ReservationManager::new(pool.clone());
+        let mut rx = manager
+            .listen(ListenRequest::new("billing", None))
+            .await
+            .unwrap();
+        let (rsvp, manager) = make_alice_reservation(pool).await;
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.reservation, Some(rsvp.clone()));
+        let ack = |change_id| abi::AckRequest {
+            consumer_id: "billing".into(),
+            change_id,
+        };
+        manager.ack(ack(change.change_id)).await.unwrap();
+
+        // consumer goes away, the change made meanwhile is sent when it comes back
+        drop(rx);
+        let confirmed = manager.change_status(rsvp.id, None).await.unwrap();
+        let listen = || manager.listen(ListenRequest::new("billing", None));
+        let mut rx = listen().await.unwrap();
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, ReservationUpdateType::Update as i32);
+        assert_eq!(change.reservation, Some(confirmed.clone()));
+
+        // it's sent again until it's acknowledged
+        drop(rx);
+        let mut rx = listen().await.unwrap();
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.reservation, Some(confirmed));
+
+        let err = manager.ack(ack(change.change_id + 1)).await.unwrap_err();
+        assert_eq!(err, abi::Error::NotFound);
+    }
+
+    #[tokio::test]
+    async fn listen_should_send_changes_in_commit_order() {
+        let tdb = get_db();
+        let pool = get_pool(&tdb).await;
+        let manager = ReservationManager::new(pool.clone());
+        let mut rx = manager.listen(ListenRequest::default()).await.unwrap();
+
+        // the first change is committed after the second one
+        let mut tx = pool.begin().await.unwrap();
+        let first = make_meeting_reservation("meeting-room-1");
+        let first = insert_reservation(&mut tx, &first, None).await.unwrap();
+        let second = manager
+            .reserve(make_meeting_reservation("meeting-room-2"))
+            .await
+            .unwrap();
+        let held = tokio::time::timeout(std::time::Duration::from_millis(300), rx.recv()).await;
+        assert!(held.is_err());
+        tx.commit().await.unwrap();
+
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.reservation, Some(first));
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.reservation, Some(second));
+    }
+
+    #[tokio::test]
+    async fn check_conflicts_should_return_all_overlapping_reservations() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        let rsvp = manager
+            .reserve(make_meeting_reservation("meeting-room-1"))
+            .await
+            .unwrap();
+        let mut block = make_meeting_reservation("meeting-room-1");
+        block.start = rsvp.end.clone();
+        block.end = Some(convert_to_timestamp(
+            "2023-01-10T12:00:00-0700".parse().unwrap(),
+        ));
+        let block = manager
+            .block_resource(block, BlockMode::Reject)
+            .await
+            .unwrap()
+            .block
+            .unwrap();
+
+        let mut proposed = make_meeting_reservation("meeting-room-1");
+        proposed.end = block.end.clone();
+        let conflicts = manager.check_conflicts(&proposed).await.unwrap();
+        assert_eq!(conflicts, vec![rsvp.clone(), block]);
+        // nothing is reserved, and a reservation doesn't conflict with itself
+        let conflicts = manager.check_conflicts(&rsvp).await.unwrap();
+        assert!(conflicts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn check_conflicts_should_follow_capacity() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        set_capacity(&manager, "training-room", 2).await;
+        let mut rsvp = make_meeting_reservation("training-room");
+        let first = manager.reserve(rsvp.clone()).await.unwrap();
+        // a unit is left, so reserve would succeed
+        rsvp.user_id = "bobid".to_string();
+        assert!(manager.check_conflicts(&rsvp).await.unwrap().is_empty());
+        let second = manager.reserve(rsvp.clone()).await.unwrap();
+
+        rsvp.user_id = "carolid".to_string();
+        let conflicts = manager.check_conflicts(&rsvp).await.unwrap();
+        assert_eq!(conflicts, vec![first, second]);
+    }
+
+    #[tokio::test]
+    async fn availability_should_return_free_slots() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        let secs = |seconds| Some(prost_types::Duration { seconds, nanos: 0 });
+        let room = abi::Resource {
+            buffer_after: secs(30 * 60),
+            ..abi::Resource::new("meeting-room-1", "Meeting room 1", "room")
+        };
+        manager
+            .update_resource(room, &paths(["buffer_after"]))
+            .await
+            .unwrap();
+        let rsvp = manager
+            .reserve(make_meeting_reservation("meeting-room-1"))
+            .await
+            .unwrap();
+        let mut block = make_meeting_reservation("meeting-room-1");
+        block.start = Some(convert_to_timestamp(
+            "2023-01-10T14:00:00-0700".parse().unwrap(),
+        ));
+        block.end = Some(convert_to_timestamp(
+            "2023-01-10T15:00:00-0700".parse().unwrap(),
+        ));
+        manager
+            .block_resource(block, BlockMode::Reject)
+            .await
+            .unwrap();
+        // cancelled reservations don't take the resource
+        let mut cancelled = make_meeting_reservation("meeting-room-1");
+        cancelled.start = Some(convert_to_timestamp(
+            "2023-01-10T16:00:00-0700".parse().unwrap(),
+        ));
+        cancelled.end = Some(convert_to_timestamp(
+            "2023-01-10T17:00:00-0700".parse().unwrap(),
+        ));
+        let cancelled = manager.reserve(cancelled).await.unwrap();
+        manager.cancel(cancelled.id, None).await.unwrap();
+
+        let t = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        let window = (t("2023-01-10T08:00:00-0700"), t("2023-01-10T18:00:00-0700"));
+        let slots = manager
+            .availability("meeting-room-1", window, chrono::Duration::zero(), false)
+            .await
+            .unwrap();
+        assert_eq!(
+            slots,
+            vec![
+                abi::TimeSlot::new(window.0, convert_to_utc_time(rsvp.start.as_ref().unwrap())),
+                abi::TimeSlot::new(t("2023-01-10T11:00:00-0700"), t("2023-01-10T14:00:00-0700")),
+                abi::TimeSlot::new(t("2023-01-10T15:00:00-0700"), window.1),
+            ]
+        );
+
+        // the buffer after each reservation is kept free, and short gaps are skipped
+        let slots = manager
+            .availability("meeting-room-1", window, chrono::Duration::hours(2), true)
+            .await
+            .unwrap();
+        assert_eq!(
+            slots,
+            vec![
+                abi::TimeSlot::new(t("2023-01-10T11:30:00-0700"), t("2023-01-10T13:30:00-0700")),
+                abi::TimeSlot::new(t("2023-01-10T15:30:00-0700"), window.1),
+            ]
+        );
+
+        let err = manager
+            .availability("unknown-room", window, chrono::Duration::zero(), false)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::ResourceNotFound("unknown-room".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn reserve_any_should_skip_taken_members() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        let pool =
+            abi::ResourcePool::new("holds", "", ["hold-room-1", "hold-room-2", "hold-room-3"]);
+        manager.create_pool(pool).await.unwrap();
+        manager
+            .reserve(make_meeting_reservation("hold-room-1"))
+            .await
+            .unwrap();
+
+        let strategy = abi::PoolStrategy::FirstFit;
+        let rsvp = make_meeting_reservation("");
+        let reserved = manager
+            .reserve_any("holds", rsvp.clone(), strategy)
+            .await
+            .unwrap();
+        assert_eq!(reserved.resource_id, "hold-room-2");
+        // retired members are not tried
+        manager.retire_resource("hold-room-3").await.unwrap();
+        let err = manager
+            .reserve_any("holds", rsvp.clone(), strategy)
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::NoAvailableResource("holds".to_string()));
+
+        let err = manager
+            .reserve_any("nowhere", rsvp, strategy)
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::PoolNotFound("nowhere".to_string()));
+    }
+
+    #[tokio::test]
+    async fn reserve_any_should_skip_members_rejected_by_policy() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        let pool = abi::ResourcePool::new("holds", "", ["hold-room-1", "hold-room-2"]);
+        manager.create_pool(pool).await.unwrap();
+        let short_only = |id: &str| abi::Resource {
+            policy: Some(abi::BookingPolicy {
+                max_duration: Some(prost_types::Duration {
+                    seconds: 30 * 60,
+                    nanos: 0,
+                }),
+                ..Default::default()
+            }),
+            ..abi::Resource::new(id, id, "room")
+        };
+        manager
+            .update_resource(short_only("hold-room-1"), &paths(["policy"]))
+            .await
+            .unwrap();
+
+        let strategy = abi::PoolStrategy::FirstFit;
+        let reserved = manager
+            .reserve_any("holds", make_meeting_reservation(""), strategy)
+            .await
+            .unwrap();
+        assert_eq!(reserved.resource_id, "hold-room-2");
+
+        // hold-room-2 is taken now, so the pool is just full
+        let err = manager
+            .reserve_any("holds", make_meeting_reservation(""), strategy)
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::NoAvailableResource("holds".to_string()));
+
+        // when every member rejects the window, the rejection is reported
+        manager.cancel(reserved.id, None).await.unwrap();
+        manager
+            .update_resource(short_only("hold-room-2"), &paths(["policy"]))
+            .await
+            .unwrap();
+        let err = manager
+            .reserve_any("holds", make_meeting_reservation(""), strategy)
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::DurationTooLong(3600, 1800));
+    }
+
+    #[tokio::test]
+    async fn reserve_any_should_follow_strategy() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        let pool =
+            abi::ResourcePool::new("holds", "", ["hold-room-1", "hold-room-2", "hold-room-3"]);
+        manager.create_pool(pool).await.unwrap();
+        let rsvp = make_meeting_reservation("");
+        let reserve_any = |strategy, day| {
+            // members are never taken if each reservation is on its own day
+            let start =
+                convert_to_utc_time(rsvp.start.as_ref().unwrap()) + chrono::Duration::days(day);
+            let rsvp = abi::Reservation {
+                start: Some(convert_to_timestamp(start)),
+                end: Some(convert_to_timestamp(start + chrono::Duration::hours(1))),
+                ..rsvp.clone()
+            };
+            let manager = manager.clone();
+            async move {
+                manager
+                    .reserve_any("holds", rsvp, strategy)
+                    .await
+                    .unwrap()
+                    .resource_id
+            }
+        };
+        use abi::PoolStrategy::*;
+        assert_eq!(reserve_any(RoundRobin, 1).await, "hold-room-1");
+        assert_eq!(reserve_any(RoundRobin, 2).await, "hold-room-2");
+        assert_eq!(reserve_any(RoundRobin, 3).await, "hold-room-3");
+        assert_eq!(reserve_any(RoundRobin, 4).await, "hold-room-1");
+        // from day 3 on, hold-room-2 has no reservations
+        assert_eq!(reserve_any(LeastUsed, 3).await, "hold-room-2");
+        assert_eq!(reserve_any(FirstFit, 3).await, "hold-room-1");
+    }
+
+    #[tokio::test]
+    async fn concurrent_reserve_any_should_take_distinct_members() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(get_pool(&tdb).await);
+        let pool =
+            abi::ResourcePool::new("holds", "", ["hold-room-1", "hold-room-2", "hold-room-3"]);
+        manager.create_pool(pool).await.unwrap();
+        let tasks: Vec<_> = (0..4)
+            .map(|_| {
+                let manager = manager.clone();
+                tokio::spawn(async move {
+                    manager
+                        .reserve_any(
+                            "holds",
+                            make_meeting_reservation(""),
+                            abi::PoolStrategy::FirstFit,
+                        )
+                        .await
+                })
+            })
+            .collect();
+        let mut reserved = vec![];
+        for task in tasks {
+            match task.await.unwrap() {
+                Ok(rsvp) => reserved.push(rsvp.resource_id),
+                Err(e) => assert_eq!(e, abi::Error::NoAvailableResource("holds".to_string())),
+            }
+        }
+        reserved.sort();
+        assert_eq!(reserved, vec!["hold-room-1", "hold-room-2", "hold-room-3"]);
     }
 
+    const TEST_RESOURCES: &[&str] = &[
+        "ixia-test-1",
+        "ocean-view-room-713",
+        "meeting-room-1",
+        "meeting-room-2",
+        "projector-1",
+        "parking-spot-1",
+        "hold-room-1",
+        "hold-room-2",
+        "hold-room-3",
+        "training-room",
+        "parking-lot",
+    ];
+
     fn get_db() -> TestDb {
         TestDb::new(
             "localhost",
@@ -347,6 +3205,30 @@ mod tests {
         )
     }
 
+    /// get a pool of the test db with the resources used by the tests registered
+    async fn get_pool(tdb: &TestDb) -> PgPool {
+        let pool = tdb.get_pool().await;
+        let manager = ReservationManager::new(pool.clone());
+        for id in TEST_RESOURCES {
+            manager
+                .create_resource(abi::Resource::new(*id, *id, "test"))
+                .await
+                .unwrap();
+        }
+        pool
+    }
+
+    async fn set_capacity(manager: &ReservationManager, id: &str, capacity: i32) {
+        let resource = abi::Resource {
+            capacity,
+            ..abi::Resource::new(id, id, "test")
+        };
+        manager
+            .update_resource(resource, &paths(["capacity"]))
+            .await
+            .unwrap();
+    }
+
     async fn make_alice_reservation(pool: PgPool) -> (Reservation, ReservationManager) {
         make_reservation(
             pool,
@@ -371,6 +3253,34 @@ mod tests {
         .await
     }
 
+    fn make_meeting_reservation(rid: &str) -> Reservation {
+        abi::Reservation::new_pending(
+            "aliceid",
+            rid,
+            "2023-01-10T10:00:00-0700".parse().unwrap(),
+            "2023-01-10T11:00:00-0700".parse().unwrap(),
+            "weekly sync",
+        )
+    }
+
+    /// a patch changing the note only
+    fn note(note: &str) -> Reservation {
+        Reservation {
+            note: note.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn paths<const N: usize>(paths: [&str; N]) -> Vec<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    /// a reservation window without buffers
+    fn window(rid: &str, start: &str, end: &str) -> ReservationWindow {
+        let window = (start.parse().unwrap(), end.parse().unwrap());
+        ReservationWindow::new(rid, window, window)
+    }
+
     async fn make_reservation(
         pool: PgPool,
         uid: &str,
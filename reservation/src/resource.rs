@@ -0,0 +1,384 @@
+use std::ops::Bound;
+
+use crate::{manager::peak_usage, ReservationManager, ResourceRegistry};
+use abi::{Normalizer, Validator};
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{postgres::types::PgRange, types::Json, PgConnection};
+
+#[async_trait]
+impl ResourceRegistry for ReservationManager {
+    async fn create_resource(
+        &self,
+        mut resource: abi::Resource,
+    ) -> Result<abi::Resource, abi::Error> {
+        resource.normalize()?;
+        let [min_duration, max_duration, min_lead_time, max_horizon] = resource.policy_secs();
+        let (buffer_before, buffer_after) = resource.buffer_secs();
+        let created: Option<abi::Resource> = sqlx::query_as(
+            r#"
+            INSERT INTO rsvp.resources (id, name, kind, timezone, attributes, capacity,
+                min_duration, max_duration, min_lead_time, max_horizon, buffer_before, buffer_after)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ON CONFLICT (id) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(&resource.id)
+        .bind(&resource.name)
+        .bind(&resource.kind)
+        .bind(&resource.timezone)
+        .bind(Json(&resource.attributes))
+        .bind(resource.capacity)
+        .bind(min_duration)
+        .bind(max_duration)
+        .bind(min_lead_time)
+        .bind(max_horizon)
+        .bind(buffer_before)
+        .bind(buffer_after)
+        .fetch_optional(&self.pool)
+        .await?;
+        created.ok_or(abi::Error::ResourceAlreadyExists(resource.id))
+    }
+
+    async fn update_resource(
+        &self,
+        patch: abi::Resource,
+        paths: &[String],
+    ) -> Result<abi::Resource, abi::Error> {
+        // the resource row is locked, so no reservation is made while the capacity is checked
+        let mut tx = self.pool.begin().await?;
+        let old: Option<abi::Resource> =
+            sqlx::query_as("SELECT * FROM rsvp.resources WHERE id = $1 FOR UPDATE")
+                .bind(&patch.id)
+                .fetch_optional(&mut tx)
+                .await?;
+        let old = old.ok_or_else(|| abi::Error::ResourceNotFound(patch.id.clone()))?;
+        let mut resource = old.clone();
+        resource.apply_update(&patch, paths)?;
+        if resource.capacity < old.capacity {
+            let from_now = PgRange {
+                start: Bound::Included(Utc::now()),
+                end: Bound::Unbounded,
+            };
+            let peak = peak_usage(&mut tx, &resource.id, &from_now, resource.capacity, 0).await?;
+            if peak > resource.capacity as i64 {
+                return Err(abi::Error::CapacityInUse(
+                    resource.id,
+                    resource.capacity,
+                    peak,
+                ));
+            }
+        }
+
+        let [min_duration, max_duration, min_lead_time, max_horizon] = resource.policy_secs();
+        let (buffer_before, buffer_after) = resource.buffer_secs();
+        let updated: abi::Resource = sqlx::query_as(
+            r#"
+            UPDATE rsvp.resources
+            SET name = $2, kind = $3, timezone = $4, attributes = $5, capacity = $6,
+                min_duration = $7, max_duration = $8, min_lead_time = $9, max_horizon = $10,
+                buffer_before = $11, buffer_after = $12
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(&resource.id)
+        .bind(&resource.name)
+        .bind(&resource.kind)
+        .bind(&resource.timezone)
+        .bind(Json(&resource.attributes))
+        .bind(resource.capacity)
+        .bind(min_duration)
+        .bind(max_duration)
+        .bind(min_lead_time)
+        .bind(max_horizon)
+        .bind(buffer_before)
+        .bind(buffer_after)
+        .fetch_one(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(updated)
+    }
+
+    async fn retire_resource(&self, id: &str) -> Result<abi::Resource, abi::Error> {
+        let retired: Option<abi::Resource> = sqlx::query_as(
+            r#"
+            UPDATE rsvp.resources
+            SET active = false
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        retired.ok_or_else(|| abi::Error::ResourceNotFound(id.to_string()))
+    }
+
+    async fn list_resources(
+        &self,
+        kind: &str,
+        include_retired: bool,
+    ) -> Result<Vec<abi::Resource>, abi::Error> {
+        let resources = sqlx::query_as(
+            r#"
+            SELECT * FROM rsvp.resources
+            WHERE ($1 = '' OR kind = $1) AND (active OR $2)
+            ORDER BY id
+            "#,
+        )
+        .bind(kind)
+        .bind(include_retired)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(resources)
+    }
+
+    async fn create_pool(&self, pool: abi::ResourcePool) -> Result<abi::ResourcePool, abi::Error> {
+        pool.validate()?;
+        let mut tx = self.pool.begin().await?;
+        let created = sqlx::query(
+            r#"
+            INSERT INTO rsvp.resource_pools (id, name) VALUES ($1, $2)
+            ON CONFLICT (id) DO NOTHING
+            "#,
+        )
+        .bind(&pool.id)
+        .bind(&pool.name)
+        .execute(&mut tx)
+        .await?;
+        if created.rows_affected() == 0 {
+            return Err(abi::Error::PoolAlreadyExists(pool.id));
+        }
+        set_pool_members(&mut tx, &pool).await?;
+        tx.commit().await?;
+        Ok(pool)
+    }
+
+    async fn update_pool(&self, pool: abi::ResourcePool) -> Result<abi::ResourcePool, abi::Error> {
+        pool.validate()?;
+        let mut tx = self.pool.begin().await?;
+        let updated = sqlx::query("UPDATE rsvp.resource_pools SET name = $2 WHERE id = $1")
+            .bind(&pool.id)
+            .bind(&pool.name)
+            .execute(&mut tx)
+            .await?;
+        if updated.rows_affected() == 0 {
+            return Err(abi::Error::PoolNotFound(pool.id));
+        }
+        set_pool_members(&mut tx, &pool).await?;
+        tx.commit().await?;
+        Ok(pool)
+    }
+}
+
+/// replace the members of a pool, keeping the given order
+async fn set_pool_members(
+    conn: &mut PgConnection,
+    pool: &abi::ResourcePool,
+) -> Result<(), abi::Error> {
+    let unknown: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT m.id FROM unnest($1::varchar[]) AS m(id)
+        WHERE NOT EXISTS (SELECT 1 FROM rsvp.resources r WHERE r.id = m.id)
+        LIMIT 1
+        "#,
+    )
+    .bind(&pool.resource_ids)
+    .fetch_optional(&mut *conn)
+    .await?;
+    if let Some(id) = unknown {
+        return Err(abi::Error::ResourceNotFound(id));
+    }
+    sqlx::query("DELETE FROM rsvp.pool_members WHERE pool_id = $1")
+        .bind(&pool.id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO rsvp.pool_members (pool_id, resource_id, position)
+        SELECT $1, m.id, m.position FROM unnest($2::varchar[]) WITH ORDINALITY AS m(id, position)
+        "#,
+    )
+    .bind(&pool.id)
+    .bind(&pool.resource_ids)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rsvp;
+    use sqlx_db_tester::TestDb;
+
+    #[tokio::test]
+    async fn resource_lifecycle_should_work() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(tdb.get_pool().await);
+        let mut room = abi::Resource::new("room-101", "Room 101", "room");
+        room.timezone = "America/Los_Angeles".to_string();
+        room.attributes.insert("floor".to_string(), "1".to_string());
+        let created = manager.create_resource(room.clone()).await.unwrap();
+        assert_eq!(created.capacity, 1);
+        assert!(created.active);
+        assert_eq!(created.attributes["floor"], "1");
+
+        let err = manager.create_resource(room.clone()).await.unwrap_err();
+        assert_eq!(
+            err,
+            abi::Error::ResourceAlreadyExists("room-101".to_string())
+        );
+
+        room.name = "Ocean View 101".to_string();
+        room.capacity = 2;
+        let paths = ["name".to_string(), "capacity".to_string()];
+        let updated = manager.update_resource(room, &paths).await.unwrap();
+        assert_eq!(updated.name, "Ocean View 101");
+        assert_eq!(updated.capacity, 2);
+
+        manager
+            .create_resource(abi::Resource::new("desk-1", "Desk 1", "desk"))
+            .await
+            .unwrap();
+        let rooms = manager.list_resources("room", false).await.unwrap();
+        assert_eq!(rooms, vec![updated]);
+
+        let retired = manager.retire_resource("room-101").await.unwrap();
+        assert!(!retired.active);
+        assert!(manager
+            .list_resources("room", false)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(manager.list_resources("", true).await.unwrap().len(), 2);
+
+        let err = manager.retire_resource("room-102").await.unwrap_err();
+        assert_eq!(err, abi::Error::ResourceNotFound("room-102".to_string()));
+    }
+
+    #[tokio::test]
+    async fn update_resource_should_keep_other_fields_and_capacity_in_use() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(tdb.get_pool().await);
+        let mut room = abi::Resource::new("room-101", "Room 101", "room");
+        room.timezone = "America/Los_Angeles".to_string();
+        room.capacity = 3;
+        let created = manager.create_resource(room).await.unwrap();
+
+        // a rename keeps the rest
+        let patch = abi::Resource::new("room-101", "Ocean View 101", "");
+        let updated = manager
+            .update_resource(patch.clone(), &["name".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(
+            updated,
+            abi::Resource {
+                name: "Ocean View 101".to_string(),
+                ..created
+            }
+        );
+        let err = manager.update_resource(patch, &[]).await.unwrap_err();
+        assert_eq!(err, abi::Error::InvalidUpdateMask(String::new()));
+
+        // two reservations at once from tomorrow on
+        let start = (Utc::now() + chrono::Duration::days(1)).into();
+        for uid in ["aliceid", "bobid"] {
+            let rsvp = abi::Reservation::new_pending(
+                uid,
+                "room-101",
+                start,
+                start + chrono::Duration::hours(1),
+                "",
+            );
+            manager.reserve(rsvp).await.unwrap();
+        }
+        let capacity = |capacity| abi::Resource {
+            capacity,
+            ..abi::Resource::new("room-101", "", "")
+        };
+        let paths = ["capacity".to_string()];
+        let err = manager
+            .update_resource(capacity(1), &paths)
+            .await
+            .unwrap_err();
+        assert_eq!(err, abi::Error::CapacityInUse("room-101".to_string(), 1, 2));
+        let updated = manager.update_resource(capacity(2), &paths).await.unwrap();
+        assert_eq!(updated.capacity, 2);
+    }
+
+    #[tokio::test]
+    async fn reserve_should_reject_unknown_or_retired_resource() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(tdb.get_pool().await);
+        let rsvp = abi::Reservation::new_pending(
+            "aliceid",
+            "room-101",
+            "2023-01-10T10:00:00-0700".parse().unwrap(),
+            "2023-01-10T11:00:00-0700".parse().unwrap(),
+            "",
+        );
+        let err = manager.reserve(rsvp.clone()).await.unwrap_err();
+        assert_eq!(err, abi::Error::ResourceNotFound("room-101".to_string()));
+
+        manager
+            .create_resource(abi::Resource::new("room-101", "Room 101", "room"))
+            .await
+            .unwrap();
+        let reserved = manager.reserve(rsvp.clone()).await.unwrap();
+
+        manager.retire_resource("room-101").await.unwrap();
+        let err = manager.reserve(rsvp).await.unwrap_err();
+        assert_eq!(err, abi::Error::ResourceRetired("room-101".to_string()));
+        // reservations of a retired resource are kept
+        manager.get(reserved.id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn pool_lifecycle_should_work() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(tdb.get_pool().await);
+        for id in ["room-301", "room-302"] {
+            manager
+                .create_resource(abi::Resource::new(id, id, "room"))
+                .await
+                .unwrap();
+        }
+        let mut pool = abi::ResourcePool::new("floor-3", "Floor 3", ["room-302", "room-303"]);
+        let err = manager.create_pool(pool.clone()).await.unwrap_err();
+        assert_eq!(err, abi::Error::ResourceNotFound("room-303".to_string()));
+
+        pool.resource_ids = vec!["room-302".to_string(), "room-301".to_string()];
+        manager.create_pool(pool.clone()).await.unwrap();
+        let err = manager.create_pool(pool.clone()).await.unwrap_err();
+        assert_eq!(err, abi::Error::PoolAlreadyExists("floor-3".to_string()));
+
+        pool.resource_ids.pop();
+        manager.update_pool(pool.clone()).await.unwrap();
+        let members: Vec<String> = sqlx::query_scalar(
+            "SELECT resource_id FROM rsvp.pool_members WHERE pool_id = $1 ORDER BY position",
+        )
+        .bind("floor-3")
+        .fetch_all(&manager.pool)
+        .await
+        .unwrap();
+        assert_eq!(members, pool.resource_ids);
+
+        pool.id = "floor-4".to_string();
+        let err = manager.update_pool(pool).await.unwrap_err();
+        assert_eq!(err, abi::Error::PoolNotFound("floor-4".to_string()));
+    }
+
+    fn get_db() -> TestDb {
+        TestDb::new(
+            "localhost",
+            15432,
+            "postgres",
+            "7cOPpA7dnc",
+            "../migrations",
+        )
+    }
+}
@@ -0,0 +1,360 @@
+use crate::{
+    manager::{active_resource, insert_reservation},
+    ReservationManager, Waitlist,
+};
+use abi::Validator;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{postgres::types::PgRange, Acquire, PgConnection};
+use tracing::{info, warn};
+
+#[async_trait]
+impl Waitlist for ReservationManager {
+    async fn join_waitlist(
+        &self,
+        rsvp: abi::Reservation,
+    ) -> Result<abi::WaitlistEntry, abi::Error> {
+        rsvp.validate_window()?;
+        let resource: Option<abi::Resource> =
+            sqlx::query_as("SELECT * FROM rsvp.resources WHERE id = $1")
+                .bind(&rsvp.resource_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        active_resource(&rsvp.resource_id, resource)?;
+        let entry = sqlx::query_as(
+            r#"
+            INSERT INTO rsvp.waitlist (user_id, resource_id, timespan, note)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(&rsvp.user_id)
+        .bind(&rsvp.resource_id)
+        .bind(rsvp.get_time_span())
+        .bind(&rsvp.note)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(entry)
+    }
+
+    async fn leave_waitlist(&self, id: i64) -> Result<abi::WaitlistEntry, abi::Error> {
+        id.validate()?;
+        let entry: Option<abi::WaitlistEntry> =
+            sqlx::query_as("DELETE FROM rsvp.waitlist WHERE id = $1 RETURNING *")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+        entry.ok_or(abi::Error::WaitlistEntryNotFound(id))
+    }
+
+    async fn list_waitlist(
+        &self,
+        resource_id: &str,
+    ) -> Result<Vec<abi::WaitlistEntry>, abi::Error> {
+        let entries = sqlx::query_as(
+            r#"
+            SELECT * FROM rsvp.waitlist
+            WHERE resource_id = $1
+            ORDER BY created_at, id
+            "#,
+        )
+        .bind(resource_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(entries)
+    }
+}
+
+/// reserve the waitlist entries overlapping the windows freed by the given reservations, earliest
+/// entry first. An entry that still doesn't fit keeps waiting, one that never could, e.g. as its
+/// lead time has passed, is dropped. The promoted reservations are recorded by the reservation
+/// trigger, so listeners get a change event.
+pub(crate) async fn promote_waitlist(
+    conn: &mut PgConnection,
+    freed: &[abi::Reservation],
+) -> Result<Vec<abi::Reservation>, abi::Error> {
+    if freed.is_empty() {
+        return Ok(vec![]);
+    }
+    let ids: Vec<i64> = freed.iter().map(|rsvp| rsvp.id).collect();
+    // entries being promoted by another transaction are skipped
+    let entries: Vec<abi::WaitlistEntry> = sqlx::query_as(
+        r#"
+        SELECT * FROM rsvp.waitlist w
+        WHERE EXISTS (
+            SELECT 1 FROM rsvp.reservations r
+            WHERE r.id = ANY($1) AND r.resource_id = w.resource_id
+                AND r.padded_timespan && w.timespan
+        )
+        ORDER BY created_at, id
+        FOR UPDATE SKIP LOCKED
+        "#,
+    )
+    .bind(&ids)
+    .fetch_all(&mut *conn)
+    .await?;
+    promote_entries(conn, entries).await
+}
+
+/// same as `promote_waitlist`, for the padded window a reservation was moved out of
+pub(crate) async fn promote_waitlist_in(
+    conn: &mut PgConnection,
+    resource_id: &str,
+    padded_timespan: &PgRange<DateTime<Utc>>,
+) -> Result<Vec<abi::Reservation>, abi::Error> {
+    let entries: Vec<abi::WaitlistEntry> = sqlx::query_as(
+        r#"
+        SELECT * FROM rsvp.waitlist
+        WHERE resource_id = $1 AND timespan && $2
+        ORDER BY created_at, id
+        FOR UPDATE SKIP LOCKED
+        "#,
+    )
+    .bind(resource_id)
+    .bind(padded_timespan)
+    .fetch_all(&mut *conn)
+    .await?;
+    promote_entries(conn, entries).await
+}
+
+async fn promote_entries(
+    conn: &mut PgConnection,
+    entries: Vec<abi::WaitlistEntry>,
+) -> Result<Vec<abi::Reservation>, abi::Error> {
+    let mut promoted = vec![];
+    for entry in entries {
+        // a savepoint, so a failed insert doesn't abort the transaction
+        let mut savepoint = conn.begin().await?;
+        let rsvp = match insert_reservation(&mut savepoint, &entry.to_reservation(), None).await {
+            Ok(rsvp) => rsvp,
+            // the window is still taken
+            Err(abi::Error::ConflictReservation(_) | abi::Error::CapacityExceeded(..)) => continue,
+            // the horizon moves with time, so the entry could be promoted later
+            Err(e @ abi::Error::BeyondHorizon(_)) => {
+                info!("waitlist entry {} keeps waiting: {}", entry.id, e);
+                continue;
+            }
+            Err(abi::Error::DbError(e)) => return Err(abi::Error::DbError(e)),
+            // e.g. the resource is retired or the lead time has passed, it never gets better
+            Err(e) => {
+                savepoint.rollback().await?;
+                warn!(
+                    "waitlist entry {} can't be promoted, dropped: {}",
+                    entry.id, e
+                );
+                sqlx::query("DELETE FROM rsvp.waitlist WHERE id = $1")
+                    .bind(entry.id)
+                    .execute(&mut *conn)
+                    .await?;
+                continue;
+            }
+        };
+        sqlx::query("DELETE FROM rsvp.waitlist WHERE id = $1")
+            .bind(entry.id)
+            .execute(&mut savepoint)
+            .await?;
+        savepoint.commit().await?;
+        promoted.push(rsvp);
+    }
+    Ok(promoted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ResourceRegistry, Rsvp};
+    use abi::ReservationUpdateType;
+    use sqlx_db_tester::TestDb;
+
+    #[tokio::test]
+    async fn cancel_should_promote_earliest_waitlist_entry() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(tdb.get_pool().await);
+        manager
+            .create_resource(abi::Resource::new("hall-1", "Hall 1", "venue"))
+            .await
+            .unwrap();
+        let rsvp = manager.reserve(make_reservation("aliceid")).await.unwrap();
+        let err = manager
+            .reserve(make_reservation("bobid"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, abi::Error::ConflictReservation(_)));
+
+        let bob = manager
+            .join_waitlist(make_reservation("bobid"))
+            .await
+            .unwrap();
+        let carol = manager
+            .join_waitlist(make_reservation("carolid"))
+            .await
+            .unwrap();
+        assert_eq!(
+            manager.list_waitlist("hall-1").await.unwrap(),
+            vec![bob.clone(), carol.clone()]
+        );
+
+        let mut rx = manager
+            .listen(abi::ListenRequest::new("", None))
+            .await
+            .unwrap();
+        manager.cancel(rsvp.id, None).await.unwrap();
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, ReservationUpdateType::Update as i32);
+        // bob is promoted, carol keeps waiting as the window is taken again
+        let change = rx.recv().await.unwrap().unwrap();
+        assert_eq!(change.op, ReservationUpdateType::Create as i32);
+        let promoted = change.reservation.unwrap();
+        assert_eq!(promoted.user_id, "bobid");
+        assert_eq!(promoted.status, abi::ReservationStatus::Pending as i32);
+        assert_eq!(
+            manager.list_waitlist("hall-1").await.unwrap(),
+            vec![carol.clone()]
+        );
+
+        manager.leave_waitlist(carol.id).await.unwrap();
+        let err = manager.leave_waitlist(carol.id).await.unwrap_err();
+        assert_eq!(err, abi::Error::WaitlistEntryNotFound(carol.id));
+    }
+
+    #[tokio::test]
+    async fn expired_hold_should_promote_waitlist_entry() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(tdb.get_pool().await);
+        manager
+            .create_resource(abi::Resource::new("hall-1", "Hall 1", "venue"))
+            .await
+            .unwrap();
+        let mut hold = make_reservation("aliceid");
+        hold.expires_at = Some(abi::convert_to_timestamp(
+            chrono::Utc::now() - chrono::Duration::seconds(1),
+        ));
+        manager.reserve(hold).await.unwrap();
+        manager
+            .join_waitlist(make_reservation("bobid"))
+            .await
+            .unwrap();
+
+        manager.release_expired_holds().await.unwrap();
+        assert!(manager.list_waitlist("hall-1").await.unwrap().is_empty());
+        let err = manager
+            .reserve(make_reservation("carolid"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, abi::Error::ConflictReservation(_)));
+    }
+
+    #[tokio::test]
+    async fn moving_a_reservation_should_promote_waitlist_entry() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(tdb.get_pool().await);
+        for id in ["hall-1", "hall-2"] {
+            manager
+                .create_resource(abi::Resource::new(id, id, "venue"))
+                .await
+                .unwrap();
+        }
+        let rsvp = manager.reserve(make_reservation("aliceid")).await.unwrap();
+        manager
+            .join_waitlist(make_reservation("bobid"))
+            .await
+            .unwrap();
+        manager
+            .join_waitlist(make_reservation("carolid"))
+            .await
+            .unwrap();
+
+        // rescheduled to the next day, bob gets the window
+        let start = "2023-01-11T10:00:00-0700".parse().unwrap();
+        let end = "2023-01-11T18:00:00-0700".parse().unwrap();
+        let rsvp = manager.reschedule(rsvp.id, start, end, None).await.unwrap();
+        assert_eq!(manager.list_waitlist("hall-1").await.unwrap().len(), 1);
+        let bob = manager
+            .check_conflicts(&make_reservation("davidid"))
+            .await
+            .unwrap();
+        assert_eq!(bob[0].user_id, "bobid");
+
+        // moved to another hall, carol gets the next day
+        manager
+            .join_waitlist(abi::Reservation {
+                start: rsvp.start.clone(),
+                end: rsvp.end.clone(),
+                ..make_reservation("carolid")
+            })
+            .await
+            .unwrap();
+        let patch = abi::Reservation {
+            resource_id: "hall-2".to_string(),
+            ..Default::default()
+        };
+        manager
+            .update(rsvp.id, patch, &["resource_id".to_string()], None)
+            .await
+            .unwrap();
+        let carol = manager.check_conflicts(&rsvp).await.unwrap();
+        assert_eq!(carol[0].user_id, "carolid");
+        // the first entry of carol still waits for the first day
+        assert_eq!(manager.list_waitlist("hall-1").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn entry_that_never_fits_should_be_dropped() {
+        let tdb = get_db();
+        let manager = ReservationManager::new(tdb.get_pool().await);
+        let policy = abi::BookingPolicy {
+            min_lead_time: Some(prost_types::Duration {
+                seconds: 3600,
+                nanos: 0,
+            }),
+            ..Default::default()
+        };
+        manager
+            .create_resource(abi::Resource {
+                policy: Some(policy),
+                ..abi::Resource::new("hall-1", "Hall 1", "venue")
+            })
+            .await
+            .unwrap();
+        // blocks are not restricted by the policy, the entry starts too soon to be reserved
+        let block = manager
+            .block_resource(make_reservation("aliceid"), abi::BlockMode::Reject)
+            .await
+            .unwrap()
+            .block
+            .unwrap();
+        manager
+            .join_waitlist(make_reservation("bobid"))
+            .await
+            .unwrap();
+
+        manager.unblock_resource(block.id).await.unwrap();
+        assert!(manager.list_waitlist("hall-1").await.unwrap().is_empty());
+        // nothing is reserved for the dropped entry
+        let conflicts = manager
+            .check_conflicts(&make_reservation("carolid"))
+            .await
+            .unwrap();
+        assert!(conflicts.is_empty());
+    }
+
+    fn make_reservation(uid: &str) -> abi::Reservation {
+        abi::Reservation::new_pending(
+            uid,
+            "hall-1",
+            "2023-01-10T10:00:00-0700".parse().unwrap(),
+            "2023-01-10T18:00:00-0700".parse().unwrap(),
+            "conference",
+        )
+    }
+
+    fn get_db() -> TestDb {
+        TestDb::new(
+            "localhost",
+            15432,
+            "postgres",
+            "7cOPpA7dnc",
+            "../migrations",
+        )
+    }
+}
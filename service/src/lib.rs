@@ -1,10 +1,13 @@
-use std::pin::Pin;
+use std::{pin::Pin, sync::Arc, time::Duration};
 
-use abi::{reservation_service_server::ReservationServiceServer, Config, Reservation};
+use abi::{
+    reservation_service_server::ReservationServiceServer, AuthConfig, Caller, Config,
+    ListenResponse, Reservation,
+};
 use futures::Stream;
 use reservation::ReservationManager;
 use tokio::sync::mpsc;
-use tonic::{transport::Server, Status};
+use tonic::{service::Interceptor, transport::Server, Request, Status};
 use tracing::info;
 
 mod service;
@@ -13,22 +16,47 @@ pub mod test_utils;
 
 pub struct RsvpService {
     manager: ReservationManager,
+    /// how long the response of a mutation is kept for its idempotency key
+    idempotency_ttl: chrono::Duration,
 }
 
 type ReservationStream = Pin<Box<dyn Stream<Item = Result<Reservation, Status>> + Send>>;
+type ListenResponseStream = Pin<Box<dyn Stream<Item = Result<ListenResponse, Status>> + Send>>;
 
 pub struct TonicReceiverStream<T> {
     inner: mpsc::Receiver<Result<T, abi::Error>>,
 }
 
+/// take the caller from the api token of a request, the handlers read it from the request extensions
+#[derive(Clone)]
+struct Authenticator {
+    auth: Arc<AuthConfig>,
+}
+
+impl Interceptor for Authenticator {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let caller = Caller::authenticate(request.metadata(), &self.auth)?;
+        request.extensions_mut().insert(caller);
+        Ok(request)
+    }
+}
+
 pub async fn start_server(config: &Config) -> Result<(), anyhow::Error> {
     let subscriber = tracing_subscriber::fmt::Subscriber::builder()
         .with_max_level(tracing::Level::DEBUG)
         .finish();
-    tracing::subscriber::set_global_default(subscriber).unwrap();
+    // the global subscriber might already be set, e.g. when several servers run in one test process
+    let _ = tracing::subscriber::set_global_default(subscriber);
     let addr = format!("{}:{}", config.server.host, config.server.port);
     let svc = RsvpService::from_config(config).await?;
-    let svc = ReservationServiceServer::new(svc);
+    svc.manager
+        .spawn_hold_reaper(Duration::from_secs(config.server.reap_interval));
+    let svc = ReservationServiceServer::with_interceptor(
+        svc,
+        Authenticator {
+            auth: Arc::new(config.auth.clone()),
+        },
+    );
     info!("Starting server at {}", addr);
     Server::builder()
         .add_service(svc)
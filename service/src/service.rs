@@ -1,13 +1,29 @@
-use std::task::Poll;
+use std::{future::Future, task::Poll};
 
-use crate::{ReservationStream, RsvpService, TonicReceiverStream};
+use crate::{ListenResponseStream, ReservationStream, RsvpService, TonicReceiverStream};
 use abi::{
-    reservation_service_server::ReservationService, CancelRequest, CancelResponse, Config,
-    ConfirmRequest, ConfirmResponse, FilterRequest, FilterResponse, GetRequest, GetResponse,
-    ListenRequest, QueryRequest, ReserveRequest, ReserveResponse, UpdateRequest, UpdateResponse,
+    convert_to_utc_time, reservation_service_server::ReservationService, AckRequest, AckResponse,
+    AvailabilityRequest, AvailabilityResponse, BatchReserveRequest, BatchReserveResponse,
+    BlockMode, BlockResourceRequest, BlockResourceResponse, Caller, CancelRequest, CancelResponse,
+    CancelSeriesRequest, CancelSeriesResponse, CheckConflictsRequest, CheckConflictsResponse,
+    Config, ConfirmRequest, ConfirmResponse, CreatePoolRequest, CreatePoolResponse,
+    CreateResourceRequest, CreateResourceResponse, FilterRequest, FilterResponse,
+    GetHistoryRequest, GetHistoryResponse, GetRequest, GetResponse, JoinWaitlistRequest,
+    JoinWaitlistResponse, LeaveWaitlistRequest, LeaveWaitlistResponse, ListResourcesRequest,
+    ListResourcesResponse, ListWaitlistRequest, ListWaitlistResponse, ListenRequest, PoolStrategy,
+    QueryRequest, RRule, RescheduleRequest, RescheduleResponse, ReserveAnyRequest,
+    ReserveAnyResponse, ReserveRequest, ReserveResponse, ReserveSeriesRequest,
+    ReserveSeriesResponse, RetireResourceRequest, RetireResourceResponse, SeriesConflictMode,
+    UnblockResourceRequest, UnblockResourceResponse, UpdatePoolRequest, UpdatePoolResponse,
+    UpdateRequest, UpdateResourceRequest, UpdateResourceResponse, UpdateResponse,
+    UpdateSeriesRequest, UpdateSeriesResponse, Validator,
+};
+use chrono::Utc;
+use futures::{Stream, TryStreamExt};
+use prost::Message;
+use reservation::{
+    Idempotency, IdempotencyKey, ReservationManager, ResourceRegistry, Rsvp, Waitlist,
 };
-use futures::Stream;
-use reservation::{ReservationManager, Rsvp};
 use tokio::sync::mpsc;
 
 use tonic::{Request, Response, Status};
@@ -15,9 +31,7 @@ use tracing::info;
 
 impl RsvpService {
     pub async fn new(config: Config) -> Result<Self, anyhow::Error> {
-        Ok(Self {
-            manager: ReservationManager::from_config(&config.db).await?,
-        })
+        Self::from_config(&config).await
     }
 }
 
@@ -25,8 +39,93 @@ impl RsvpService {
     pub async fn from_config(config: &Config) -> Result<Self, anyhow::Error> {
         Ok(Self {
             manager: ReservationManager::from_config(&config.db).await?,
+            idempotency_ttl: chrono::Duration::seconds(config.server.idempotency_ttl as i64),
         })
     }
+
+    /// run a mutation once per idempotency key, a retry with the same key gets the reservation
+    /// saved by the mutation. The key is saved in the transaction of the mutation, so a mutation
+    /// done is never run again
+    async fn idempotent<T, F>(
+        &self,
+        manager: ReservationManager,
+        key: Option<IdempotencyKey>,
+        respond: impl Fn(abi::Reservation) -> T,
+        mutation: impl FnOnce(ReservationManager) -> F,
+    ) -> Result<Response<T>, Status>
+    where
+        F: Future<Output = Result<abi::Reservation, Status>>,
+    {
+        let key = match key {
+            Some(key) => key,
+            None => return Ok(Response::new(respond(mutation(manager).await?))),
+        };
+        if let Some(saved) = self.saved_reservation(&key).await? {
+            return Ok(Response::new(respond(saved)));
+        }
+        match mutation(manager.with_idempotency_key(key.clone())).await {
+            Ok(rsvp) => Ok(Response::new(respond(rsvp))),
+            // a request with the same key may have done the mutation meanwhile
+            Err(status) => match self.saved_reservation(&key).await? {
+                Some(saved) => Ok(Response::new(respond(saved))),
+                None => Err(status),
+            },
+        }
+    }
+
+    async fn saved_reservation(
+        &self,
+        key: &IdempotencyKey,
+    ) -> Result<Option<abi::Reservation>, abi::Error> {
+        self.manager
+            .saved_reservation(key, self.idempotency_ttl)
+            .await
+    }
+
+    /// a manager recording the changes it makes as made by the caller of the request
+    fn manager_for<T>(&self, request: &Request<T>) -> ReservationManager {
+        self.manager.with_actor(caller(request).user_id)
+    }
+
+    /// fail unless the caller of the request owns the reservation or is an admin
+    async fn check_owner<T>(&self, request: &Request<T>, id: i64) -> Result<(), Status> {
+        let rsvp = self.manager.get(id).await?;
+        caller(request).check_owner(&rsvp)?;
+        Ok(())
+    }
+}
+
+/// the caller authenticated by the interceptor, anonymous if it did not run
+fn caller<T>(request: &Request<T>) -> Caller {
+    request
+        .extensions()
+        .get::<Caller>()
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// the idempotency key of a request for the caller and operation, the `x-idempotency-key` metadata
+/// is used if the field is empty
+fn idempotency_key<T: Message>(
+    request: &Request<T>,
+    field: &str,
+    operation: &str,
+) -> Option<IdempotencyKey> {
+    let key = if !field.is_empty() {
+        field
+    } else {
+        request
+            .metadata()
+            .get("x-idempotency-key")
+            .and_then(|v| v.to_str().ok())
+            .filter(|v| !v.is_empty())?
+    };
+    Some(IdempotencyKey {
+        caller: caller(request).user_id,
+        operation: operation.to_string(),
+        key: key.to_string(),
+        request: request.get_ref().encode_to_vec(),
+    })
 }
 
 #[tonic::async_trait]
@@ -36,49 +135,378 @@ impl ReservationService for RsvpService {
         &self,
         request: Request<ReserveRequest>,
     ) -> Result<Response<ReserveResponse>, Status> {
+        let manager = self.manager_for(&request);
+        let key = idempotency_key(&request, &request.get_ref().idempotency_key, "reserve");
         let request = request.into_inner();
-        if request.reservation.is_none() {
-            return Err(Status::invalid_argument("reservation is required"));
-        }
-        let reservation = request.reservation.unwrap();
-        let reservation = self.manager.reserve(reservation).await?;
-        Ok(Response::new(ReserveResponse {
+        let respond = |reservation| ReserveResponse {
+            reservation: Some(reservation),
+        };
+        self.idempotent(manager, key, respond, |manager| async move {
+            if request.reservation.is_none() {
+                return Err(Status::invalid_argument("reservation is required"));
+            }
+            let expires_at = request.hold_expires_at(Utc::now())?;
+            let mut reservation = request.reservation.unwrap();
+            reservation.expires_at = expires_at;
+            Ok(manager.reserve(reservation).await?)
+        })
+        .await
+    }
+    /// reserve an available member of a pool
+    async fn reserve_any(
+        &self,
+        request: Request<ReserveAnyRequest>,
+    ) -> Result<Response<ReserveAnyResponse>, Status> {
+        let manager = self.manager_for(&request);
+        let request = request.into_inner();
+        let expires_at = request.hold_expires_at(Utc::now())?;
+        let reservation = request
+            .reservation
+            .ok_or_else(|| Status::invalid_argument("reservation is required"))?;
+        let reservation = abi::Reservation {
+            expires_at,
+            ..reservation
+        };
+        let strategy = PoolStrategy::from_i32(request.strategy)
+            .ok_or(abi::Error::InvalidPoolStrategy(request.strategy))?;
+        let reservation = manager
+            .reserve_any(&request.pool_id, reservation, strategy)
+            .await?;
+        Ok(Response::new(ReserveAnyResponse {
             reservation: Some(reservation),
         }))
     }
-    /// confirm a pending reservation,if reservation is not pending, do nothing
+    /// make several reservations atomically, if any of them conflicts, none of them is made
+    async fn batch_reserve(
+        &self,
+        request: Request<BatchReserveRequest>,
+    ) -> Result<Response<BatchReserveResponse>, Status> {
+        let manager = self.manager_for(&request);
+        let request = request.into_inner();
+        if request.reservations.is_empty() {
+            return Err(Status::invalid_argument("reservations are required"));
+        }
+        let expires_at = request.hold_expires_at(Utc::now())?;
+        let reservations = request
+            .reservations
+            .into_iter()
+            .map(|rsvp| abi::Reservation {
+                expires_at: expires_at.clone(),
+                ..rsvp
+            })
+            .collect();
+        let reservations = manager.reserve_many(reservations).await?;
+        Ok(Response::new(BatchReserveResponse { reservations }))
+    }
+    /// make a recurring reservation
+    async fn reserve_series(
+        &self,
+        request: Request<ReserveSeriesRequest>,
+    ) -> Result<Response<ReserveSeriesResponse>, Status> {
+        let manager = self.manager_for(&request);
+        let request = request.into_inner();
+        let expires_at = request.hold_expires_at(Utc::now())?;
+        let reservation = request
+            .reservation
+            .ok_or_else(|| Status::invalid_argument("reservation is required"))?;
+        let reservation = abi::Reservation {
+            expires_at,
+            ..reservation
+        };
+        let rrule: RRule = request.rrule.parse()?;
+        let mode = SeriesConflictMode::from_i32(request.conflict_mode)
+            .ok_or(abi::Error::InvalidConflictMode(request.conflict_mode))?;
+        let response = manager.reserve_series(reservation, rrule, mode).await?;
+        Ok(Response::new(response))
+    }
+    /// cancel an occurrence of a recurring reservation and all the following ones
+    async fn cancel_series(
+        &self,
+        request: Request<CancelSeriesRequest>,
+    ) -> Result<Response<CancelSeriesResponse>, Status> {
+        self.check_owner(&request, request.get_ref().id).await?;
+        let manager = self.manager_for(&request);
+        let request = request.into_inner();
+        let reservations = manager.cancel_series(request.id).await?;
+        Ok(Response::new(CancelSeriesResponse { reservations }))
+    }
+    /// update the note or move an occurrence of a recurring reservation and all the following ones
+    async fn update_series(
+        &self,
+        request: Request<UpdateSeriesRequest>,
+    ) -> Result<Response<UpdateSeriesResponse>, Status> {
+        self.check_owner(&request, request.get_ref().id).await?;
+        let manager = self.manager_for(&request);
+        let request = request.into_inner();
+        let shift = request
+            .shift
+            .as_ref()
+            .map(abi::convert_to_duration)
+            .unwrap_or_else(chrono::Duration::zero);
+        let reservations = manager
+            .update_series(request.id, request.note, shift)
+            .await?;
+        Ok(Response::new(UpdateSeriesResponse { reservations }))
+    }
+    /// confirm a pending reservation, fail if it is not pending
     async fn confirm(
         &self,
         request: Request<ConfirmRequest>,
     ) -> Result<Response<ConfirmResponse>, Status> {
+        self.check_owner(&request, request.get_ref().id).await?;
+        let manager = self.manager_for(&request);
+        let key = idempotency_key(&request, &request.get_ref().idempotency_key, "confirm");
         let request = request.into_inner();
-        let reservation = self.manager.change_status(request.id).await?;
-        Ok(Response::new(ConfirmResponse {
+        let respond = |reservation| ConfirmResponse {
             reservation: Some(reservation),
-        }))
+        };
+        self.idempotent(manager, key, respond, |manager| async move {
+            Ok(manager
+                .change_status(request.id, request.expected_version)
+                .await?)
+        })
+        .await
     }
-    /// update a reservation note
+    /// update the fields of a reservation listed in the update mask
     async fn update(
         &self,
         request: Request<UpdateRequest>,
     ) -> Result<Response<UpdateResponse>, Status> {
+        self.check_owner(&request, request.get_ref().id).await?;
+        let manager = self.manager_for(&request);
         let request = request.into_inner();
-        let reservation = self.manager.update_note(request.id, request.note).await?;
+        let patch = request.reservation.clone().unwrap_or_default();
+        let reservation = manager
+            .update(request.id, patch, request.paths(), request.expected_version)
+            .await?;
         Ok(Response::new(UpdateResponse {
             reservation: Some(reservation),
         }))
     }
+    /// move a reservation to another time window, fail if the new window conflicts with others
+    async fn reschedule(
+        &self,
+        request: Request<RescheduleRequest>,
+    ) -> Result<Response<RescheduleResponse>, Status> {
+        self.check_owner(&request, request.get_ref().id).await?;
+        let manager = self.manager_for(&request);
+        let key = idempotency_key(&request, &request.get_ref().idempotency_key, "reschedule");
+        let request = request.into_inner();
+        request.validate()?;
+        let respond = |reservation| RescheduleResponse {
+            reservation: Some(reservation),
+        };
+        self.idempotent(manager, key, respond, |manager| async move {
+            let start = convert_to_utc_time(request.start.as_ref().unwrap());
+            let end = convert_to_utc_time(request.end.as_ref().unwrap());
+            Ok(manager
+                .reschedule(request.id, start, end, request.expected_version)
+                .await?)
+        })
+        .await
+    }
+    /// find all the reservations a proposed one would conflict with
+    async fn check_conflicts(
+        &self,
+        request: Request<CheckConflictsRequest>,
+    ) -> Result<Response<CheckConflictsResponse>, Status> {
+        let caller = caller(&request);
+        let reservation = request
+            .into_inner()
+            .reservation
+            .ok_or_else(|| Status::invalid_argument("reservation is required"))?;
+        let conflicts = self.manager.check_conflicts(&reservation).await?;
+        let conflicts = conflicts
+            .into_iter()
+            .map(|rsvp| rsvp.redact_for(&caller))
+            .collect();
+        Ok(Response::new(CheckConflictsResponse { conflicts }))
+    }
+    /// find the free intervals of a resource inside a window
+    async fn availability(
+        &self,
+        request: Request<AvailabilityRequest>,
+    ) -> Result<Response<AvailabilityResponse>, Status> {
+        let request = request.into_inner();
+        request.validate()?;
+        let start = convert_to_utc_time(request.start.as_ref().unwrap());
+        let end = convert_to_utc_time(request.end.as_ref().unwrap());
+        let slots = self
+            .manager
+            .availability(
+                &request.resource_id,
+                (start, end),
+                request.min_duration(),
+                request.with_buffers,
+            )
+            .await?;
+        Ok(Response::new(AvailabilityResponse { slots }))
+    }
     ///cancel a reservation by id
     async fn cancel(
         &self,
         request: Request<CancelRequest>,
     ) -> Result<Response<CancelResponse>, Status> {
+        self.check_owner(&request, request.get_ref().id).await?;
+        let manager = self.manager_for(&request);
+        let key = idempotency_key(&request, &request.get_ref().idempotency_key, "cancel");
         let request = request.into_inner();
-        let reservation = self.manager.delete(request.id).await?;
-        Ok(Response::new(CancelResponse {
+        let respond = |reservation| CancelResponse {
             reservation: Some(reservation),
+        };
+        self.idempotent(manager, key, respond, |manager| async move {
+            Ok(manager.cancel(request.id, request.expected_version).await?)
+        })
+        .await
+    }
+    /// block a resource for a window
+    async fn block_resource(
+        &self,
+        request: Request<BlockResourceRequest>,
+    ) -> Result<Response<BlockResourceResponse>, Status> {
+        caller(&request).check_admin()?;
+        let manager = self.manager_for(&request);
+        let request = request.into_inner();
+        let block = request
+            .block
+            .ok_or_else(|| Status::invalid_argument("block is required"))?;
+        let mode =
+            BlockMode::from_i32(request.mode).ok_or(abi::Error::InvalidBlockMode(request.mode))?;
+        let response = manager.block_resource(block, mode).await?;
+        Ok(Response::new(response))
+    }
+    /// lift a block by id
+    async fn unblock_resource(
+        &self,
+        request: Request<UnblockResourceRequest>,
+    ) -> Result<Response<UnblockResourceResponse>, Status> {
+        caller(&request).check_admin()?;
+        let manager = self.manager_for(&request);
+        let request = request.into_inner();
+        let block = manager.unblock_resource(request.id).await?;
+        Ok(Response::new(UnblockResourceResponse {
+            block: Some(block),
+        }))
+    }
+    /// register a resource
+    async fn create_resource(
+        &self,
+        request: Request<CreateResourceRequest>,
+    ) -> Result<Response<CreateResourceResponse>, Status> {
+        caller(&request).check_admin()?;
+        let request = request.into_inner();
+        let resource = request
+            .resource
+            .ok_or_else(|| Status::invalid_argument("resource is required"))?;
+        let resource = self.manager.create_resource(resource).await?;
+        Ok(Response::new(CreateResourceResponse {
+            resource: Some(resource),
         }))
     }
+    /// update the fields of a resource listed in the update mask
+    async fn update_resource(
+        &self,
+        request: Request<UpdateResourceRequest>,
+    ) -> Result<Response<UpdateResourceResponse>, Status> {
+        caller(&request).check_admin()?;
+        let request = request.into_inner();
+        let resource = request
+            .resource
+            .clone()
+            .ok_or_else(|| Status::invalid_argument("resource is required"))?;
+        let resource = self
+            .manager
+            .update_resource(resource, request.paths())
+            .await?;
+        Ok(Response::new(UpdateResourceResponse {
+            resource: Some(resource),
+        }))
+    }
+    /// queue for a window that is taken
+    async fn join_waitlist(
+        &self,
+        request: Request<JoinWaitlistRequest>,
+    ) -> Result<Response<JoinWaitlistResponse>, Status> {
+        let request = request.into_inner();
+        let reservation = request
+            .reservation
+            .ok_or_else(|| Status::invalid_argument("reservation is required"))?;
+        let entry = self.manager.join_waitlist(reservation).await?;
+        Ok(Response::new(JoinWaitlistResponse { entry: Some(entry) }))
+    }
+    /// leave the waitlist
+    async fn leave_waitlist(
+        &self,
+        request: Request<LeaveWaitlistRequest>,
+    ) -> Result<Response<LeaveWaitlistResponse>, Status> {
+        let request = request.into_inner();
+        let entry = self.manager.leave_waitlist(request.id).await?;
+        Ok(Response::new(LeaveWaitlistResponse { entry: Some(entry) }))
+    }
+    /// list the entries queueing for a resource
+    async fn list_waitlist(
+        &self,
+        request: Request<ListWaitlistRequest>,
+    ) -> Result<Response<ListWaitlistResponse>, Status> {
+        let caller = caller(&request);
+        let request = request.into_inner();
+        let entries = self.manager.list_waitlist(&request.resource_id).await?;
+        let entries = entries
+            .into_iter()
+            .map(|entry| entry.redact_for(&caller))
+            .collect();
+        Ok(Response::new(ListWaitlistResponse { entries }))
+    }
+    /// create a pool of resources
+    async fn create_pool(
+        &self,
+        request: Request<CreatePoolRequest>,
+    ) -> Result<Response<CreatePoolResponse>, Status> {
+        caller(&request).check_admin()?;
+        let request = request.into_inner();
+        let pool = request
+            .pool
+            .ok_or_else(|| Status::invalid_argument("pool is required"))?;
+        let pool = self.manager.create_pool(pool).await?;
+        Ok(Response::new(CreatePoolResponse { pool: Some(pool) }))
+    }
+    /// rename a pool or replace its members
+    async fn update_pool(
+        &self,
+        request: Request<UpdatePoolRequest>,
+    ) -> Result<Response<UpdatePoolResponse>, Status> {
+        caller(&request).check_admin()?;
+        let request = request.into_inner();
+        let pool = request
+            .pool
+            .ok_or_else(|| Status::invalid_argument("pool is required"))?;
+        let pool = self.manager.update_pool(pool).await?;
+        Ok(Response::new(UpdatePoolResponse { pool: Some(pool) }))
+    }
+    /// retire a resource
+    async fn retire_resource(
+        &self,
+        request: Request<RetireResourceRequest>,
+    ) -> Result<Response<RetireResourceResponse>, Status> {
+        caller(&request).check_admin()?;
+        let request = request.into_inner();
+        let resource = self.manager.retire_resource(&request.id).await?;
+        Ok(Response::new(RetireResourceResponse {
+            resource: Some(resource),
+        }))
+    }
+    /// list resources
+    async fn list_resources(
+        &self,
+        request: Request<ListResourcesRequest>,
+    ) -> Result<Response<ListResourcesResponse>, Status> {
+        let request = request.into_inner();
+        let resources = self
+            .manager
+            .list_resources(&request.kind, request.include_retired)
+            .await?;
+        Ok(Response::new(ListResourcesResponse { resources }))
+    }
     /// get a reservation by id
     async fn get(&self, request: Request<GetRequest>) -> Result<Response<GetResponse>, Status> {
         let request = request.into_inner();
@@ -87,6 +515,20 @@ impl ReservationService for RsvpService {
             reservation: Some(reservation),
         }))
     }
+    /// get the changes of a reservation in the order they were made
+    async fn get_history(
+        &self,
+        request: Request<GetHistoryRequest>,
+    ) -> Result<Response<GetHistoryResponse>, Status> {
+        let caller = caller(&request);
+        let request = request.into_inner();
+        let changes = self.manager.history(request.reservation_id).await?;
+        let changes = changes
+            .into_iter()
+            .map(|change| change.redact_for(&caller))
+            .collect();
+        Ok(Response::new(GetHistoryResponse { changes }))
+    }
     ///Server streaming response type for the query method.
     type queryStream = ReservationStream;
     /// query reservations by resource_id, user_id, status, start time, end time
@@ -123,15 +565,31 @@ impl ReservationService for RsvpService {
         }))
     }
     ///Server streaming response type for the listen method.
-    type listenStream = ReservationStream;
+    type listenStream = ListenResponseStream;
     /// another system could monitor newly added/updated/cancelled/confirmed reservations
     async fn listen(
         &self,
         request: Request<ListenRequest>,
     ) -> Result<Response<Self::listenStream>, Status> {
-        let _request = request.into_inner();
-        // let reservations = self.manager.listen(request).await?;
-        todo!()
+        let caller = caller(&request);
+        let request = request.into_inner();
+        if !request.user_id.is_empty() && !caller.owns(&request.user_id) {
+            return Err(abi::Error::PermissionDenied(format!(
+                "changes of user {} are not yours",
+                request.user_id
+            ))
+            .into());
+        }
+        let changes = self.manager.listen(request).await?;
+        let stream =
+            TonicReceiverStream::new(changes).map_ok(move |change| change.redact_for(&caller));
+        Ok(Response::new(Box::pin(stream)))
+    }
+    /// save the read progress of a listening consumer
+    async fn ack(&self, request: Request<AckRequest>) -> Result<Response<AckResponse>, Status> {
+        let request = request.into_inner();
+        self.manager.ack(request).await?;
+        Ok(Response::new(AckResponse {}))
     }
 }
 
@@ -163,7 +621,10 @@ impl<T> Stream for TonicReceiverStream<T> {
 #[cfg(test)]
 mod tests {
 
-    use abi::{convert_to_timestamp, ReservationFilter, ReservationQuery, ReservationStatus};
+    use abi::{
+        convert_to_timestamp, ReservationFilter, ReservationQuery, ReservationStatus,
+        ReservationUpdateType,
+    };
     use futures::{future, TryStreamExt};
     use tonic::Code;
     use tracing::log::info;
@@ -172,10 +633,162 @@ mod tests {
 
     use super::*;
 
+    /// a request made by the given user
+    fn user_request<T>(user_id: &str, message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        request.extensions_mut().insert(Caller::new(user_id, false));
+        request
+    }
+
+    /// a request made by an admin
+    fn admin_request<T>(message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        request.extensions_mut().insert(Caller::new("admin", true));
+        request
+    }
+
+    /// get a service with the resources used by the tests registered
+    async fn get_service(config: &Config) -> RsvpService {
+        let service = RsvpService::from_config(config).await.unwrap();
+        for id in ["test-room-317", "test-room-318", "projector-317"] {
+            let request = CreateResourceRequest {
+                resource: Some(abi::Resource::new(id, id, "test")),
+            };
+            service
+                .create_resource(admin_request(request))
+                .await
+                .unwrap();
+        }
+        service
+    }
+
+    #[tokio::test]
+    async fn rpc_reserve_should_report_policy_violations() {
+        let config = TestConfig::default();
+        let service = get_service(&config).await;
+        let resource = abi::Resource {
+            policy: Some(abi::BookingPolicy {
+                max_duration: Some(prost_types::Duration {
+                    seconds: 4 * 3600,
+                    nanos: 0,
+                }),
+                max_horizon: Some(prost_types::Duration {
+                    seconds: 30 * 86400,
+                    nanos: 0,
+                }),
+                ..Default::default()
+            }),
+            ..abi::Resource::new("desk-1", "Desk 1", "desk")
+        };
+        let request = CreateResourceRequest {
+            resource: Some(resource),
+        };
+        service
+            .create_resource(admin_request(request))
+            .await
+            .unwrap();
+
+        let start = Utc::now() + chrono::Duration::days(1);
+        let mut rsvp = abi::Reservation {
+            start: Some(convert_to_timestamp(start)),
+            end: Some(convert_to_timestamp(start + chrono::Duration::hours(5))),
+            ..abi::Reservation::new_pending(
+                "aliceid",
+                "desk-1",
+                "2022-12-25T15:00:00-0700".parse().unwrap(),
+                "2022-12-27T12:00:00-0700".parse().unwrap(),
+                "",
+            )
+        };
+        let status = service
+            .reserve(Request::new(ReserveRequest::new(rsvp.clone())))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(
+            status.message(),
+            "Reservation lasts 5h, longer than the maximum of 4h"
+        );
+
+        let start = start + chrono::Duration::days(60);
+        rsvp.start = Some(convert_to_timestamp(start));
+        rsvp.end = Some(convert_to_timestamp(start + chrono::Duration::hours(1)));
+        let status = service
+            .reserve(Request::new(ReserveRequest::new(rsvp)))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::FailedPrecondition);
+    }
+
+    #[tokio::test]
+    async fn rpc_resource_lifecycle_should_work() {
+        let config = TestConfig::default();
+        let service = get_service(&config).await;
+        let mut resource = abi::Resource::new("parking-lot", "Parking lot", "parking");
+        resource.capacity = 120;
+        let request = CreateResourceRequest {
+            resource: Some(resource.clone()),
+        };
+        let created = service
+            .create_resource(admin_request(request.clone()))
+            .await
+            .unwrap()
+            .into_inner()
+            .resource
+            .unwrap();
+        assert_eq!(created.capacity, 120);
+        let status = service
+            .create_resource(admin_request(request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::AlreadyExists);
+
+        resource.timezone = "Nowhere/City".to_string();
+        let request = UpdateResourceRequest::new(resource, ["timezone"]);
+        let status = service
+            .update_resource(admin_request(request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        let request = ListResourcesRequest {
+            kind: "parking".to_string(),
+            include_retired: false,
+        };
+        let response = service
+            .list_resources(Request::new(request.clone()))
+            .await
+            .unwrap();
+        assert_eq!(response.into_inner().resources, vec![created]);
+
+        let request_retire = RetireResourceRequest {
+            id: "parking-lot".to_string(),
+        };
+        service
+            .retire_resource(admin_request(request_retire))
+            .await
+            .unwrap();
+        let response = service.list_resources(Request::new(request)).await.unwrap();
+        assert!(response.into_inner().resources.is_empty());
+
+        let rsvp = abi::Reservation::new_pending(
+            "aliceid",
+            "parking-lot",
+            "2022-12-25T15:00:00-0700".parse().unwrap(),
+            "2022-12-27T12:00:00-0700".parse().unwrap(),
+            "",
+        );
+        let status = service
+            .reserve(Request::new(ReserveRequest::new(rsvp)))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::FailedPrecondition);
+    }
+
     #[tokio::test]
     async fn rpc_reserve_should_work() {
         let config = TestConfig::default();
-        let service = RsvpService::from_config(&config).await.unwrap();
+        let service = get_service(&config).await;
         let request = ReserveRequest {
             reservation: Some(abi::Reservation::new_pending(
                 "aliceid",
@@ -184,15 +797,244 @@ mod tests {
                 "2022-12-27T12:00:00-0700".parse().unwrap(),
                 "I'll arrive at 3pm. Please help to upgrade to execuitive room if possible.",
             )),
+            hold_ttl: None,
+            idempotency_key: String::new(),
         };
         let response = service.reserve(Request::new(request)).await.unwrap();
         let reservation_res = response.into_inner().reservation;
         assert!(reservation_res.is_some());
     }
+    #[tokio::test]
+    async fn rpc_batch_reserve_should_work() {
+        let config = TestConfig::default();
+        let service = get_service(&config).await;
+        let make_rsvp = |rid: &str| {
+            abi::Reservation::new_pending(
+                "aliceid",
+                rid,
+                "2022-12-25T15:00:00-0700".parse().unwrap(),
+                "2022-12-27T12:00:00-0700".parse().unwrap(),
+                "",
+            )
+        };
+        let request = BatchReserveRequest {
+            reservations: vec![make_rsvp("test-room-317"), make_rsvp("projector-317")],
+            hold_ttl: None,
+        };
+        let response = service.batch_reserve(Request::new(request)).await.unwrap();
+        assert_eq!(response.into_inner().reservations.len(), 2);
+
+        let request = BatchReserveRequest {
+            reservations: vec![make_rsvp("test-room-318"), make_rsvp("projector-317")],
+            hold_ttl: None,
+        };
+        let status = service
+            .batch_reserve(Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::FailedPrecondition);
+    }
+    #[tokio::test]
+    async fn rpc_series_should_work() {
+        let config = TestConfig::default();
+        let service = get_service(&config).await;
+        let request = ReserveSeriesRequest {
+            reservation: Some(abi::Reservation::new_pending(
+                "aliceid",
+                "test-room-317",
+                "2023-01-03T10:00:00-0700".parse().unwrap(),
+                "2023-01-03T11:00:00-0700".parse().unwrap(),
+                "weekly sync",
+            )),
+            rrule: "FREQ=WEEKLY;BYDAY=TU;COUNT=4".to_string(),
+            conflict_mode: SeriesConflictMode::Fail as i32,
+            hold_ttl: None,
+        };
+        let response = service
+            .reserve_series(Request::new(request))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.series_id, 1);
+        assert_eq!(response.reservations.len(), 4);
+
+        let request = UpdateSeriesRequest {
+            id: response.reservations[1].id,
+            note: Some("moved to zoom".to_string()),
+            shift: None,
+        };
+        let reservations = service
+            .update_series(user_request("aliceid", request))
+            .await
+            .unwrap()
+            .into_inner()
+            .reservations;
+        assert_eq!(reservations.len(), 3);
+        assert!(reservations.iter().all(|r| r.note == "moved to zoom"));
+
+        let request = CancelSeriesRequest {
+            id: response.reservations[2].id,
+        };
+        let reservations = service
+            .cancel_series(user_request("aliceid", request))
+            .await
+            .unwrap()
+            .into_inner()
+            .reservations;
+        assert_eq!(reservations.len(), 2);
+
+        let request = ReserveSeriesRequest {
+            rrule: "FREQ=WEEKLY".to_string(),
+            ..Default::default()
+        };
+        let status = service
+            .reserve_series(Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+    #[tokio::test]
+    async fn rpc_reserve_with_hold_ttl_should_set_expiry() {
+        let config = TestConfig::default();
+        let service = get_service(&config).await;
+        let rsvp = abi::Reservation::new_pending(
+            "aliceid",
+            "test-room-317",
+            "2022-12-25T15:00:00-0700".parse().unwrap(),
+            "2022-12-27T12:00:00-0700".parse().unwrap(),
+            "hold it while I pay",
+        );
+        let request = ReserveRequest::new(rsvp.clone()).with_hold_ttl(prost_types::Duration {
+            seconds: 600,
+            nanos: 0,
+        });
+        let now = Utc::now();
+        let response = service.reserve(Request::new(request)).await.unwrap();
+        let expires_at = response
+            .into_inner()
+            .reservation
+            .unwrap()
+            .expires_at
+            .unwrap();
+        let ttl = convert_to_utc_time(&expires_at) - now;
+        assert!(ttl > chrono::Duration::seconds(590) && ttl <= chrono::Duration::seconds(601));
+
+        let request = ReserveRequest::new(rsvp).with_hold_ttl(prost_types::Duration {
+            seconds: 0,
+            nanos: 0,
+        });
+        let status = service.reserve(Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn rpc_batch_and_series_should_set_expiry_from_hold_ttl() {
+        let config = TestConfig::default();
+        let service = get_service(&config).await;
+        // an expiry sent by the client is not trusted
+        let rsvp = abi::Reservation {
+            expires_at: Some(convert_to_timestamp(Utc::now())),
+            ..abi::Reservation::new_pending(
+                "aliceid",
+                "test-room-317",
+                "2022-12-25T15:00:00-0700".parse().unwrap(),
+                "2022-12-25T16:00:00-0700".parse().unwrap(),
+                "",
+            )
+        };
+        let request = BatchReserveRequest {
+            reservations: vec![rsvp.clone()],
+            hold_ttl: None,
+        };
+        let response = service.batch_reserve(Request::new(request)).await.unwrap();
+        assert_eq!(response.into_inner().reservations[0].expires_at, None);
+
+        let ttl = |seconds| Some(prost_types::Duration { seconds, nanos: 0 });
+        let request = ReserveSeriesRequest {
+            reservation: Some(abi::Reservation {
+                resource_id: "test-room-318".to_string(),
+                ..rsvp.clone()
+            }),
+            rrule: "FREQ=DAILY;COUNT=2".to_string(),
+            conflict_mode: SeriesConflictMode::Fail as i32,
+            hold_ttl: ttl(600),
+        };
+        let response = service
+            .reserve_series(Request::new(request))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(response
+            .reservations
+            .iter()
+            .all(|r| convert_to_utc_time(r.expires_at.as_ref().unwrap()) > Utc::now()));
+
+        let request = BatchReserveRequest {
+            reservations: vec![rsvp],
+            hold_ttl: ttl(-1),
+        };
+        let status = service
+            .batch_reserve(Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn rpc_retry_with_idempotency_key_should_replay_response() {
+        let config = TestConfig::default();
+        let service = get_service(&config).await;
+        let mut request = ReserveRequest::new(abi::Reservation::new_pending(
+            "aliceid",
+            "test-room-317",
+            "2022-12-25T15:00:00-0700".parse().unwrap(),
+            "2022-12-27T12:00:00-0700".parse().unwrap(),
+            "reserve once",
+        ));
+        request.idempotency_key = "reserve-1".to_string();
+        let first = service
+            .reserve(Request::new(request.clone()))
+            .await
+            .unwrap()
+            .into_inner();
+        // without the key the retry would conflict with the first reservation
+        let retried = service
+            .reserve(Request::new(request.clone()))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(first, retried);
+
+        // the key can't be reused for another request
+        let mut other = request.clone();
+        other.reservation.as_mut().unwrap().note = "reserve twice".to_string();
+        let status = service.reserve(Request::new(other)).await.unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        // keys are apart for each caller
+        let mut retried = Request::new(request);
+        retried.extensions_mut().insert(Caller::new("bobid", false));
+        let status = service.reserve(retried).await.unwrap_err();
+        assert_eq!(status.code(), Code::FailedPrecondition);
+
+        let id = first.reservation.unwrap().id;
+
+        let cancel = || {
+            let mut request = user_request("aliceid", CancelRequest::new(id));
+            request
+                .metadata_mut()
+                .insert("x-idempotency-key", "cancel-1".parse().unwrap());
+            request
+        };
+        let first = service.cancel(cancel()).await.unwrap().into_inner();
+        let retried = service.cancel(cancel()).await.unwrap().into_inner();
+        assert_eq!(first, retried);
+    }
+
     #[tokio::test]
     async fn rpc_confirm_should_work() {
         let config = TestConfig::default();
-        let service = RsvpService::from_config(&config).await.unwrap();
+        let service = get_service(&config).await;
         let request = ReserveRequest {
             reservation: Some(abi::Reservation::new_pending(
                 "aliceid",
@@ -201,17 +1043,22 @@ mod tests {
                 "2022-12-27T12:00:00-0700".parse().unwrap(),
                 "I'll arrive at 3pm. Please help to upgrade to execuitive room if possible.",
             )),
+            hold_ttl: None,
+            idempotency_key: String::new(),
         };
         service.reserve(Request::new(request)).await.unwrap();
-        let request = ConfirmRequest { id: 1 };
-        let response = service.confirm(Request::new(request)).await.unwrap();
+        let request = ConfirmRequest::new(1);
+        let response = service
+            .confirm(user_request("aliceid", request))
+            .await
+            .unwrap();
         let reservation_res = response.into_inner().reservation;
         assert!(reservation_res.is_some());
     }
     #[tokio::test]
     async fn rpc_update_should_work() {
         let config = TestConfig::default();
-        let service = RsvpService::from_config(&config).await.unwrap();
+        let service = get_service(&config).await;
         let request = ReserveRequest {
             reservation: Some(abi::Reservation::new_pending(
                 "aliceid",
@@ -220,22 +1067,91 @@ mod tests {
                 "2022-12-27T12:00:00-0700".parse().unwrap(),
                 "I'll arrive at 3pm. Please help to upgrade to execuitive room if possible.",
             )),
+            hold_ttl: None,
+            idempotency_key: String::new(),
         };
         service.reserve(Request::new(request)).await.unwrap();
-        let request = UpdateRequest {
-            id: 1,
+        let patch = abi::Reservation {
             note: "I'll arrive at 4pm. Please help to upgrade to execuitive room if possible."
                 .to_string(),
+            ..Default::default()
         };
-        let response = service.update(Request::new(request)).await.unwrap();
+        let request = UpdateRequest::new(1, patch, ["note"]);
+        let response = service
+            .update(user_request("aliceid", request))
+            .await
+            .unwrap();
         let reservation_res = response.into_inner().reservation;
         assert!(reservation_res.is_some());
+
+        // transfer to bob
+        let patch = abi::Reservation {
+            user_id: "bobid".to_string(),
+            ..Default::default()
+        };
+        let request = UpdateRequest::new(1, patch.clone(), ["user_id"]);
+        let response = service
+            .update(user_request("aliceid", request))
+            .await
+            .unwrap();
+        assert_eq!(response.into_inner().reservation.unwrap().user_id, "bobid");
+
+        // alice can't change it anymore
+        let request = UpdateRequest::new(1, patch.clone(), ["note"]);
+        let status = service
+            .update(user_request("aliceid", request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::PermissionDenied);
+
+        let request = UpdateRequest::new(1, patch, ["status"]);
+        let status = service
+            .update(user_request("bobid", request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+    //move a reservation to another time window
+    #[tokio::test]
+    async fn rpc_reschedule_should_work() {
+        let config = TestConfig::default();
+        let service = get_service(&config).await;
+        let request = ReserveRequest {
+            reservation: Some(abi::Reservation::new_pending(
+                "aliceid",
+                "test-room-317",
+                "2022-12-25T15:00:00-0700".parse().unwrap(),
+                "2022-12-27T12:00:00-0700".parse().unwrap(),
+                "I'll arrive at 3pm. Please help to upgrade to execuitive room if possible.",
+            )),
+            hold_ttl: None,
+            idempotency_key: String::new(),
+        };
+        service.reserve(Request::new(request)).await.unwrap();
+        let start = convert_to_timestamp("2022-12-26T15:00:00-0700".parse().unwrap());
+        let end = convert_to_timestamp("2022-12-28T12:00:00-0700".parse().unwrap());
+        let request = RescheduleRequest::new(1, start.clone(), end.clone());
+        let response = service
+            .reschedule(user_request("aliceid", request))
+            .await
+            .unwrap();
+        let reservation = response.into_inner().reservation.unwrap();
+        assert_eq!(reservation.start, Some(start.clone()));
+        assert_eq!(reservation.end, Some(end.clone()));
+
+        // start must be earlier than end
+        let request = RescheduleRequest::new(1, end, start);
+        let status = service
+            .reschedule(user_request("aliceid", request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
     }
     //cancel a reservation by id
     #[tokio::test]
     async fn rpc_cancel_should_work() {
         let config = TestConfig::default();
-        let service = RsvpService::from_config(&config).await.unwrap();
+        let service = get_service(&config).await;
         let request = ReserveRequest {
             reservation: Some(abi::Reservation::new_pending(
                 "aliceid",
@@ -244,18 +1160,511 @@ mod tests {
                 "2022-12-27T12:00:00-0700".parse().unwrap(),
                 "I'll arrive at 3pm. Please help to upgrade to execuitive room if possible.",
             )),
+            hold_ttl: None,
+            idempotency_key: String::new(),
         };
         service.reserve(Request::new(request)).await.unwrap();
-        let request = CancelRequest { id: 1 };
-        let response = service.cancel(Request::new(request)).await.unwrap();
-        let reservation_res = response.into_inner().reservation;
-        assert!(reservation_res.is_some());
+        let request = CancelRequest::new(1);
+        let response = service
+            .cancel(user_request("aliceid", request))
+            .await
+            .unwrap();
+        let reservation_res = response.into_inner().reservation.unwrap();
+        assert_eq!(
+            reservation_res.status,
+            abi::ReservationStatus::Cancelled as i32
+        );
+    }
+    #[tokio::test]
+    async fn rpc_reserve_any_should_work() {
+        let config = TestConfig::default();
+        let service = get_service(&config).await;
+        let pool = abi::ResourcePool::new("rooms-317", "", ["test-room-317", "test-room-318"]);
+        let request = CreatePoolRequest {
+            pool: Some(pool.clone()),
+        };
+        service.create_pool(admin_request(request)).await.unwrap();
+        let rsvp = abi::Reservation::new_pending(
+            "aliceid",
+            "",
+            "2022-12-25T15:00:00-0700".parse().unwrap(),
+            "2022-12-27T12:00:00-0700".parse().unwrap(),
+            "hello",
+        );
+        let request = ReserveAnyRequest::new("rooms-317", rsvp, PoolStrategy::FirstFit);
+        for rid in ["test-room-317", "test-room-318"] {
+            let response = service
+                .reserve_any(Request::new(request.clone()))
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(response.reservation.unwrap().resource_id, rid);
+        }
+        let status = service
+            .reserve_any(Request::new(request.clone()))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::FailedPrecondition);
+
+        let request = ReserveAnyRequest {
+            strategy: 9,
+            ..request
+        };
+        let status = service
+            .reserve_any(Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn rpc_check_conflicts_should_hide_others_reservations() {
+        let config = TestConfig::default();
+        let service = get_service(&config).await;
+        let mut reserved = vec![];
+        for (uid, start, end) in [
+            (
+                "aliceid",
+                "2022-12-25T15:00:00-0700",
+                "2022-12-26T12:00:00-0700",
+            ),
+            (
+                "bobid",
+                "2022-12-26T15:00:00-0700",
+                "2022-12-27T12:00:00-0700",
+            ),
+        ] {
+            let rsvp = abi::Reservation::new_pending(
+                uid,
+                "test-room-317",
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "private",
+            );
+            let response = service
+                .reserve(Request::new(ReserveRequest::new(rsvp)))
+                .await
+                .unwrap()
+                .into_inner();
+            reserved.push(response.reservation.unwrap());
+        }
+        let proposed = abi::Reservation::new_pending(
+            "aliceid",
+            "test-room-317",
+            "2022-12-25T00:00:00-0700".parse().unwrap(),
+            "2022-12-28T00:00:00-0700".parse().unwrap(),
+            "",
+        );
+        let mut request = Request::new(CheckConflictsRequest {
+            reservation: Some(proposed.clone()),
+        });
+        request
+            .extensions_mut()
+            .insert(Caller::new("aliceid", false));
+        let conflicts = service
+            .check_conflicts(request)
+            .await
+            .unwrap()
+            .into_inner()
+            .conflicts;
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0], reserved[0]);
+        assert_eq!(conflicts[1].id, reserved[1].id);
+        assert_eq!(conflicts[1].status, reserved[1].status);
+        assert_eq!(conflicts[1].user_id, "");
+
+        let mut request = Request::new(CheckConflictsRequest {
+            reservation: Some(proposed),
+        });
+        request.extensions_mut().insert(Caller::new("bobid", true));
+        let conflicts = service
+            .check_conflicts(request)
+            .await
+            .unwrap()
+            .into_inner()
+            .conflicts;
+        assert_eq!(conflicts, reserved);
+    }
+
+    #[tokio::test]
+    async fn rpc_waitlist_should_work() {
+        let config = TestConfig::default();
+        let service = get_service(&config).await;
+        let rsvp = abi::Reservation::new_pending(
+            "aliceid",
+            "test-room-317",
+            "2022-12-25T15:00:00-0700".parse().unwrap(),
+            "2022-12-27T12:00:00-0700".parse().unwrap(),
+            "hello",
+        );
+        let reserved = service
+            .reserve(Request::new(ReserveRequest::new(rsvp.clone())))
+            .await
+            .unwrap()
+            .into_inner()
+            .reservation
+            .unwrap();
+        let request = JoinWaitlistRequest {
+            reservation: Some(abi::Reservation {
+                user_id: "bobid".to_string(),
+                ..rsvp
+            }),
+        };
+        let entry = service
+            .join_waitlist(Request::new(request))
+            .await
+            .unwrap()
+            .into_inner()
+            .entry
+            .unwrap();
+        let request = ListWaitlistRequest {
+            resource_id: "test-room-317".to_string(),
+        };
+        let entries = service
+            .list_waitlist(user_request("bobid", request.clone()))
+            .await
+            .unwrap()
+            .into_inner()
+            .entries;
+        assert_eq!(entries, vec![entry.clone()]);
+        // others don't see who is queueing
+        let entries = service
+            .list_waitlist(user_request("aliceid", request.clone()))
+            .await
+            .unwrap()
+            .into_inner()
+            .entries;
+        assert_eq!(entries[0].id, entry.id);
+        assert_eq!(entries[0].user_id, "");
+        assert_eq!(entries[0].note, "");
+
+        service
+            .cancel(user_request("aliceid", CancelRequest::new(reserved.id)))
+            .await
+            .unwrap();
+        let entries = service
+            .list_waitlist(Request::new(request))
+            .await
+            .unwrap()
+            .into_inner()
+            .entries;
+        assert!(entries.is_empty());
+        let status = service
+            .leave_waitlist(Request::new(LeaveWaitlistRequest { id: entry.id }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn rpc_should_deny_others_and_non_admins() {
+        let config = TestConfig::default();
+        let service = get_service(&config).await;
+        let denied = |status: Status| assert_eq!(status.code(), Code::PermissionDenied);
+
+        // operations on resources and pools are for admins only
+        let resource = abi::Resource::new("desk-1", "Desk 1", "desk");
+        let request = CreateResourceRequest {
+            resource: Some(resource.clone()),
+        };
+        denied(
+            service
+                .create_resource(user_request("aliceid", request.clone()))
+                .await
+                .unwrap_err(),
+        );
+        denied(
+            service
+                .create_resource(Request::new(request))
+                .await
+                .unwrap_err(),
+        );
+        let request = UpdateResourceRequest::new(resource, ["name"]);
+        denied(
+            service
+                .update_resource(user_request("aliceid", request))
+                .await
+                .unwrap_err(),
+        );
+        let request = RetireResourceRequest {
+            id: "test-room-317".to_string(),
+        };
+        denied(
+            service
+                .retire_resource(user_request("aliceid", request))
+                .await
+                .unwrap_err(),
+        );
+        let pool = abi::ResourcePool::new("rooms-317", "", ["test-room-317"]);
+        let request = CreatePoolRequest {
+            pool: Some(pool.clone()),
+        };
+        denied(
+            service
+                .create_pool(user_request("aliceid", request))
+                .await
+                .unwrap_err(),
+        );
+        let request = UpdatePoolRequest { pool: Some(pool) };
+        denied(
+            service
+                .update_pool(user_request("aliceid", request))
+                .await
+                .unwrap_err(),
+        );
+        let rsvp = abi::Reservation::new_pending(
+            "aliceid",
+            "test-room-317",
+            "2022-12-25T15:00:00-0700".parse().unwrap(),
+            "2022-12-27T12:00:00-0700".parse().unwrap(),
+            "hello",
+        );
+        let request = BlockResourceRequest {
+            block: Some(rsvp.clone()),
+            mode: BlockMode::Reject as i32,
+        };
+        denied(
+            service
+                .block_resource(user_request("aliceid", request))
+                .await
+                .unwrap_err(),
+        );
+        let request = UnblockResourceRequest { id: 1 };
+        denied(
+            service
+                .unblock_resource(user_request("aliceid", request))
+                .await
+                .unwrap_err(),
+        );
+
+        // a reservation is only changed by its owner or an admin
+        let id = service
+            .reserve(Request::new(ReserveRequest::new(rsvp)))
+            .await
+            .unwrap()
+            .into_inner()
+            .reservation
+            .unwrap()
+            .id;
+        denied(
+            service
+                .confirm(user_request("bobid", ConfirmRequest::new(id)))
+                .await
+                .unwrap_err(),
+        );
+        denied(
+            service
+                .cancel(user_request("bobid", CancelRequest::new(id)))
+                .await
+                .unwrap_err(),
+        );
+        denied(
+            service
+                .cancel(Request::new(CancelRequest::new(id)))
+                .await
+                .unwrap_err(),
+        );
+        let patch = abi::Reservation {
+            user_id: "bobid".to_string(),
+            ..Default::default()
+        };
+        let request = UpdateRequest::new(id, patch, ["user_id"]);
+        denied(
+            service
+                .update(user_request("bobid", request))
+                .await
+                .unwrap_err(),
+        );
+        let request = RescheduleRequest::new(
+            id,
+            convert_to_timestamp("2022-12-28T15:00:00-0700".parse().unwrap()),
+            convert_to_timestamp("2022-12-29T12:00:00-0700".parse().unwrap()),
+        );
+        denied(
+            service
+                .reschedule(user_request("bobid", request))
+                .await
+                .unwrap_err(),
+        );
+        denied(
+            service
+                .cancel_series(user_request("bobid", CancelSeriesRequest { id }))
+                .await
+                .unwrap_err(),
+        );
+        let request = UpdateSeriesRequest {
+            id,
+            note: Some("mine".to_string()),
+            shift: None,
+        };
+        denied(
+            service
+                .update_series(user_request("bobid", request))
+                .await
+                .unwrap_err(),
+        );
+
+        // an admin may cancel it
+        let reservation = service
+            .cancel(admin_request(CancelRequest::new(id)))
+            .await
+            .unwrap()
+            .into_inner()
+            .reservation
+            .unwrap();
+        assert_eq!(reservation.status, ReservationStatus::Cancelled as i32);
+    }
+
+    #[tokio::test]
+    async fn rpc_update_with_stale_version_should_abort() {
+        let config = TestConfig::default();
+        let service = get_service(&config).await;
+        let rsvp = abi::Reservation::new_pending(
+            "aliceid",
+            "test-room-317",
+            "2022-12-25T15:00:00-0700".parse().unwrap(),
+            "2022-12-27T12:00:00-0700".parse().unwrap(),
+            "hello",
+        );
+        let rsvp = service
+            .reserve(Request::new(ReserveRequest::new(rsvp)))
+            .await
+            .unwrap()
+            .into_inner()
+            .reservation
+            .unwrap();
+        let request = UpdateRequest {
+            expected_version: Some(rsvp.version),
+            ..UpdateRequest::new(rsvp.id, rsvp.clone(), ["note"])
+        };
+        service
+            .update(user_request("aliceid", request.clone()))
+            .await
+            .unwrap();
+        let status = service
+            .update(user_request("aliceid", request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Aborted);
+    }
+
+    #[tokio::test]
+    async fn rpc_availability_should_work() {
+        let config = TestConfig::default();
+        let service = get_service(&config).await;
+        let rsvp = abi::Reservation::new_pending(
+            "aliceid",
+            "test-room-317",
+            "2022-12-25T15:00:00-0700".parse().unwrap(),
+            "2022-12-27T12:00:00-0700".parse().unwrap(),
+            "hello",
+        );
+        service
+            .reserve(Request::new(ReserveRequest::new(rsvp.clone())))
+            .await
+            .unwrap();
+        let request = AvailabilityRequest::new(
+            "test-room-317",
+            "2022-12-25T00:00:00-0700".parse().unwrap(),
+            "2022-12-28T00:00:00-0700".parse().unwrap(),
+            chrono::Duration::hours(1),
+        );
+        let response = service
+            .availability(Request::new(request.clone()))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(
+            response.slots,
+            vec![
+                abi::TimeSlot {
+                    start: request.start.clone(),
+                    end: rsvp.start
+                },
+                abi::TimeSlot {
+                    start: rsvp.end,
+                    end: request.end.clone()
+                },
+            ]
+        );
+
+        let request = AvailabilityRequest {
+            end: request.start.clone(),
+            ..request
+        };
+        let status = service
+            .availability(Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn rpc_block_and_unblock_resource_should_work() {
+        let config = TestConfig::default();
+        let service = get_service(&config).await;
+        let rsvp = abi::Reservation::new_pending(
+            "aliceid",
+            "test-room-317",
+            "2022-12-25T15:00:00-0700".parse().unwrap(),
+            "2022-12-27T12:00:00-0700".parse().unwrap(),
+            "hello",
+        );
+        service
+            .reserve(Request::new(ReserveRequest::new(rsvp.clone())))
+            .await
+            .unwrap();
+        let mut block = abi::Reservation::new_pending(
+            "operator",
+            "test-room-317",
+            "2022-12-26T00:00:00-0700".parse().unwrap(),
+            "2022-12-31T00:00:00-0700".parse().unwrap(),
+            "maintenance",
+        );
+        let request = BlockResourceRequest {
+            block: Some(block.clone()),
+            mode: BlockMode::Reject as i32,
+        };
+        let status = service
+            .block_resource(admin_request(request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::FailedPrecondition);
+
+        let request = BlockResourceRequest {
+            block: Some(block.clone()),
+            mode: BlockMode::CancelPending as i32,
+        };
+        let response = service
+            .block_resource(admin_request(request))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.cancelled.len(), 1);
+        block = response.block.unwrap();
+        assert_eq!(block.status, abi::ReservationStatus::Blocked as i32);
+
+        let status = service
+            .reserve(Request::new(ReserveRequest::new(rsvp.clone())))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::FailedPrecondition);
+
+        let request = UnblockResourceRequest { id: block.id };
+        service
+            .unblock_resource(admin_request(request))
+            .await
+            .unwrap();
+        service
+            .reserve(Request::new(ReserveRequest::new(rsvp)))
+            .await
+            .unwrap();
     }
     //get a reservation by id
     #[tokio::test]
     async fn rpc_get_should_work() {
         let config = TestConfig::default();
-        let service = RsvpService::from_config(&config).await.unwrap();
+        let service = get_service(&config).await;
         let request = ReserveRequest {
             reservation: Some(abi::Reservation::new_pending(
                 "aliceid",
@@ -264,6 +1673,8 @@ mod tests {
                 "2022-12-27T12:00:00-0700".parse().unwrap(),
                 "I'll arrive at 3pm. Please help to upgrade to execuitive room if possible.",
             )),
+            hold_ttl: None,
+            idempotency_key: String::new(),
         };
         service.reserve(Request::new(request)).await.unwrap();
         let request = GetRequest { id: 1 };
@@ -275,7 +1686,7 @@ mod tests {
     #[tokio::test]
     async fn rpc_filter_should_work() {
         let config = TestConfig::default();
-        let service = RsvpService::from_config(&config).await.unwrap();
+        let service = get_service(&config).await;
         let request = ReserveRequest {
             reservation: Some(abi::Reservation::new_pending(
                 "aliceid",
@@ -284,6 +1695,8 @@ mod tests {
                 "2022-12-27T12:00:00-0700".parse().unwrap(),
                 "I'll arrive at 3pm. Please help to upgrade to execuitive room if possible.",
             )),
+            hold_ttl: None,
+            idempotency_key: String::new(),
         };
         service.reserve(Request::new(request)).await.unwrap();
         let request = FilterRequest {
@@ -306,7 +1719,7 @@ mod tests {
             .finish();
         tracing::subscriber::set_global_default(subscriber).unwrap();
         let config = TestConfig::default();
-        let service = RsvpService::from_config(&config).await.unwrap();
+        let service = get_service(&config).await;
         let request = ReserveRequest {
             reservation: Some(abi::Reservation::new_pending(
                 "aliceid",
@@ -315,6 +1728,8 @@ mod tests {
                 "2022-12-27T12:00:00-0700".parse().unwrap(),
                 "I'll arrive at 3pm. Please help to upgrade to execuitive room if possible.",
             )),
+            hold_ttl: None,
+            idempotency_key: String::new(),
         };
         service.reserve(Request::new(request)).await.unwrap();
         let request = QueryRequest {
@@ -346,4 +1761,64 @@ mod tests {
         assert!(fut.is_ok());
         // assert!(!reservations.is_empty());
     }
+    // listen to reservation changes
+    #[tokio::test]
+    async fn rpc_listen_should_work() {
+        let config = TestConfig::default();
+        let service = get_service(&config).await;
+        let response = service
+            .listen(Request::new(ListenRequest::default()))
+            .await
+            .unwrap();
+        let mut stream = response.into_inner();
+        let request = ReserveRequest {
+            reservation: Some(abi::Reservation::new_pending(
+                "aliceid",
+                "test-room-317",
+                "2022-12-25T15:00:00-0700".parse().unwrap(),
+                "2022-12-27T12:00:00-0700".parse().unwrap(),
+                "I'll arrive at 3pm. Please help to upgrade to execuitive room if possible.",
+            )),
+            hold_ttl: None,
+            idempotency_key: String::new(),
+        };
+        service.reserve(Request::new(request)).await.unwrap();
+        let change = stream.try_next().await.unwrap().unwrap();
+        assert_eq!(change.op, ReservationUpdateType::Create as i32);
+        let reservation = change.reservation.unwrap();
+        assert_eq!(reservation.id, 1);
+        // an anonymous listener sees no owner or note
+        assert_eq!(reservation.user_id, "");
+        assert_eq!(reservation.note, "");
+
+        // the owner sees its own reservations, and who changed them if it did
+        let mut stream = service
+            .listen(user_request("aliceid", ListenRequest::new("", Some(0))))
+            .await
+            .unwrap()
+            .into_inner();
+        service
+            .cancel(user_request("aliceid", CancelRequest::new(1)))
+            .await
+            .unwrap();
+        let change = stream.try_next().await.unwrap().unwrap();
+        assert_eq!(change.reservation.unwrap().user_id, "aliceid");
+        let change = stream.try_next().await.unwrap().unwrap();
+        assert_eq!(change.op, ReservationUpdateType::Update as i32);
+        assert_eq!(change.actor, "aliceid");
+        assert_eq!(change.old.unwrap().user_id, "aliceid");
+
+        // others only listen to their own changes
+        let request = ListenRequest {
+            user_id: "aliceid".to_string(),
+            ..Default::default()
+        };
+        let status = service
+            .listen(user_request("bobid", request.clone()))
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(status.code(), Code::PermissionDenied);
+        assert!(service.listen(admin_request(request)).await.is_ok());
+    }
 }
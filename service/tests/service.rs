@@ -3,19 +3,21 @@ mod test_utils;
 use std::time::Duration;
 
 use abi::{
-    reservation_service_client::ReservationServiceClient, Config, FilterResponse, Reservation,
-    ReservationFilterBuilder, ReservationQueryBuilder, ReserveRequest,
+    reservation_service_client::ReservationServiceClient, CheckConflictsRequest, Config,
+    CreateResourceRequest, FilterResponse, Reservation, ReservationFilterBuilder,
+    ReservationQueryBuilder, ReserveRequest, Resource,
 };
 use reservation_service::start_server;
 use test_utils::TestConfig;
 use tokio::time;
-use tonic::transport::Channel;
+use tonic::{transport::Channel, Code, Request};
 use tracing::info;
 
 #[tokio::test]
 async fn grpc_server_should_work() {
     let config = TestConfig::with_server_port(50000);
     let mut client = get_test_client(&config).await;
+    create_resource(&mut client, "ixia-3230").await;
     // make a reservation
     let mut rsvp = Reservation::new_pending(
         "tyr",
@@ -32,6 +34,8 @@ async fn grpc_server_should_work() {
         .reservation
         .unwrap();
     rsvp.id = ret.id;
+    // a new reservation starts at version 1
+    rsvp.version = 1;
     assert_eq!(rsvp, ret);
     // then we try to make a conflicting reservation
     let rsvp2 = Reservation::new_pending(
@@ -44,9 +48,16 @@ async fn grpc_server_should_work() {
     let ret = client.reserve(ReserveRequest::new(rsvp2.clone())).await;
     assert!(ret.is_err());
 
+    // only the owner or an admin can confirm it
+    let err = client
+        .confirm(abi::ConfirmRequest::new(rsvp.id))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+
     // then we confirm the first reservation
     let ret = client
-        .confirm(abi::ConfirmRequest::new(rsvp.id))
+        .confirm(with_token(abi::ConfirmRequest::new(rsvp.id), "admin-token"))
         .await
         .unwrap()
         .into_inner()
@@ -102,6 +113,71 @@ async fn grpc_filter_should_work() {
     let mut next_filter = filter.clone();
     next_filter.cursor = pager.next;
 }
+#[tokio::test]
+async fn grpc_caller_should_be_authenticated_by_token() {
+    let tconfig = TestConfig::with_server_port(50003);
+    let mut client = get_test_client(&tconfig).await;
+    make_reservation(&mut client, 1).await;
+    let proposed = Reservation::new_pending(
+        "bob",
+        "router-0",
+        "2022-12-26T15:00:00-0700".parse().unwrap(),
+        "2022-12-30T12:00:00-0700".parse().unwrap(),
+        "",
+    );
+    let check = |metadata: &[(&'static str, &str)]| {
+        let mut request = Request::new(CheckConflictsRequest {
+            reservation: Some(proposed.clone()),
+        });
+        for (key, value) in metadata {
+            request.metadata_mut().insert(*key, value.parse().unwrap());
+        }
+        request
+    };
+    // a self-asserted user id is ignored
+    let conflicts = client
+        .check_conflicts(check(&[("x-user-id", "alice"), ("x-user-role", "admin")]))
+        .await
+        .unwrap()
+        .into_inner()
+        .conflicts;
+    assert_eq!(conflicts[0].user_id, "");
+
+    let conflicts = client
+        .check_conflicts(check(&[("authorization", "Bearer alice-token")]))
+        .await
+        .unwrap()
+        .into_inner()
+        .conflicts;
+    assert_eq!(conflicts[0].user_id, "alice");
+
+    let err = client
+        .check_conflicts(check(&[("authorization", "Bearer mallory-token")]))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+}
+
+async fn create_resource(client: &mut ReservationServiceClient<Channel>, id: &str) {
+    let request = CreateResourceRequest {
+        resource: Some(Resource::new(id, id, "device")),
+    };
+    client
+        .create_resource(with_token(request, "admin-token"))
+        .await
+        .unwrap();
+}
+
+/// a request made with the given api token
+fn with_token<T>(message: T, token: &str) -> Request<T> {
+    let mut request = Request::new(message);
+    request.metadata_mut().insert(
+        "authorization",
+        format!("Bearer {}", token).parse().unwrap(),
+    );
+    request
+}
+
 async fn get_test_client(tconfig: &TestConfig) -> ReservationServiceClient<Channel> {
     let config = &tconfig.config;
     setup_server(config).await;
@@ -120,6 +196,7 @@ async fn setup_server(config: &Config) {
 
 async fn make_reservation(client: &mut ReservationServiceClient<Channel>, count: u32) {
     for i in 0..count {
+        create_resource(client, &format!("router-{}", i)).await;
         let mut rsvp = Reservation::new_pending(
             "alice",
             format!("router-{}", i),
@@ -135,6 +212,7 @@ async fn make_reservation(client: &mut ReservationServiceClient<Channel>, count:
             .reservation
             .unwrap();
         rsvp.id = ret.id;
+        rsvp.version = 1;
         assert_eq!(rsvp, ret);
     }
 }